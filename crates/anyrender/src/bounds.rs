@@ -0,0 +1,159 @@
+//! Helpers for computing conservative device-space bounds of drawing operations
+
+use crate::Glyph;
+use kurbo::{Affine, Cap, Join, Rect, Shape, Stroke, Vec2};
+use peniko::FontData;
+use skrifa::raw::tables::{glyf::Glyf, hmtx::Hmtx, loca::Loca};
+use skrifa::raw::{FontRef, TableProvider, types::GlyphId};
+
+/// The tolerance used when converting curved shapes into paths to compute their bounds
+const TOLERANCE: f64 = 0.1;
+
+/// Compute the bounds of a shape after applying a transform
+pub(crate) fn shape_bounds(transform: Affine, shape: &impl Shape) -> Rect {
+    transform.transform_rect_bbox(shape.bounding_box())
+}
+
+/// Compute the bounds of a stroked shape after applying a transform
+pub(crate) fn stroke_bounds(style: &Stroke, transform: Affine, shape: &impl Shape) -> Rect {
+    let outset = stroke_outset(style);
+    transform.transform_rect_bbox(shape.bounding_box().inflate(outset, outset))
+}
+
+/// Compute the bounds of a box shadow (the gaussian blur is treated as extending 3 standard deviations
+/// beyond the box)
+pub(crate) fn box_shadow_bounds(transform: Affine, rect: Rect, std_dev: f64) -> Rect {
+    let outset = 3.0 * std_dev.abs();
+    transform.transform_rect_bbox(rect.abs().inflate(outset, outset))
+}
+
+/// Compute the bounds of a run of glyphs
+pub(crate) fn glyph_run_bounds<'a>(
+    font: &FontData,
+    font_size: f32,
+    stroke: Option<&Stroke>,
+    transform: Affine,
+    glyph_transform: Option<Affine>,
+    glyphs: impl Iterator<Item = &'a Glyph>,
+) -> Rect {
+    let metrics = GlyphMetrics::new(font, font_size);
+    let outset = stroke.map(stroke_outset).unwrap_or(0.0);
+    let glyph_transform = glyph_transform.unwrap_or_default();
+
+    let local_bounds = glyphs
+        .map(|glyph| {
+            let glyph_bounds = metrics.glyph_bounds(glyph.id).inflate(outset, outset);
+            glyph_transform.transform_rect_bbox(glyph_bounds)
+                + Vec2::new(glyph.x as f64, glyph.y as f64)
+        })
+        .reduce(|a, b| a.union(b))
+        .unwrap_or(Rect::ZERO);
+
+    transform.transform_rect_bbox(local_bounds)
+}
+
+/// Compute the bounds of a shape after applying a transform, which (unlike [`shape_bounds`]) stay tight when
+/// the transform includes a rotation or skew
+pub(crate) fn transformed_shape_bounds(shape: &impl Shape, transform: Affine) -> Rect {
+    if let Some(rect) = shape.as_rect() {
+        return transform.transform_rect_bbox(rect);
+    }
+    kurbo::segments(shape.path_elements(TOLERANCE).map(|el| transform * el))
+        .map(|seg| seg.bounding_box())
+        .reduce(|a, b| a.union(b))
+        .unwrap_or(Rect::ZERO)
+}
+
+/// The maximum distance the outline of a stroke can extend beyond the bounds of the stroked shape
+pub(crate) fn stroke_outset(stroke: &Stroke) -> f64 {
+    let half_width = stroke.width.abs() / 2.0;
+    let join_factor = match stroke.join {
+        Join::Miter => stroke.miter_limit.max(1.0),
+        Join::Bevel | Join::Round => 1.0,
+    };
+    let cap_factor = match (stroke.start_cap, stroke.end_cap) {
+        (Cap::Square, _) | (_, Cap::Square) => std::f64::consts::SQRT_2,
+        _ => 1.0,
+    };
+    half_width * join_factor.max(cap_factor)
+}
+
+/// Font metrics used to compute the bounds of glyph runs
+struct GlyphMetrics<'a> {
+    hmtx: Option<Hmtx<'a>>,
+    /// The `loca` and `glyf` tables, used to look up the bounding box of each glyph
+    glyf: Option<(Loca<'a>, Glyf<'a>)>,
+    scale: f64,
+    /// The bounding box of all glyphs in the font (in font units, y-up)
+    font_bounds: Rect,
+}
+
+impl<'a> GlyphMetrics<'a> {
+    fn new(font_data: &'a FontData, font_size: f32) -> Self {
+        let font_size = font_size as f64;
+        let font = FontRef::from_index(font_data.data.data(), font_data.index).ok();
+        let head = font.as_ref().and_then(|font| font.head().ok());
+        let (Some(font), Some(head)) = (font, head) else {
+            return Self {
+                hmtx: None,
+                glyf: None,
+                scale: 1.0,
+                font_bounds: Rect::new(0.0, 0.0, font_size, font_size),
+            };
+        };
+
+        // The per-glyph bounding boxes in `glyf` are for the default instance of variable fonts, so use the
+        // font's bounding box for them instead
+        let glyf = match font.fvar() {
+            Ok(_) => None,
+            Err(_) => font.loca(None).ok().zip(font.glyf().ok()),
+        };
+        Self {
+            hmtx: font.hmtx().ok(),
+            glyf,
+            scale: font_size / head.units_per_em().max(1) as f64,
+            font_bounds: Rect::new(
+                head.x_min() as f64,
+                head.y_min() as f64,
+                head.x_max() as f64,
+                head.y_max() as f64,
+            ),
+        }
+    }
+
+    /// The bounds of a glyph relative to its origin (in y-down coordinates).
+    ///
+    /// Horizontally this covers the glyph's advance and its ink, so that ink which overhangs the advance (as in
+    /// italic fonts) is included. The ink extent is the glyph's bounding box from the `glyf` table when there is
+    /// one, and otherwise the bounding box of every glyph in the font (from the `head` table). The vertical
+    /// extent is always that of the whole font.
+    fn glyph_bounds(&self, glyph_id: u32) -> Rect {
+        let glyph_id = GlyphId::new(glyph_id);
+        let Some(hmtx) = &self.hmtx else {
+            return self.font_bounds_rect(self.font_bounds.x0, self.font_bounds.x1);
+        };
+        let advance = hmtx.advance(glyph_id).unwrap_or(0) as f64;
+        let ink = match &self.glyf {
+            Some((loca, glyf)) => match loca.get_glyf(glyph_id, glyf) {
+                Ok(Some(glyph)) => Some((glyph.x_min() as f64, glyph.x_max() as f64)),
+                // Glyphs without an outline (such as spaces) have no ink
+                Ok(None) => None,
+                Err(_) => Some((self.font_bounds.x0, self.font_bounds.x1)),
+            },
+            None => Some((self.font_bounds.x0, self.font_bounds.x1)),
+        };
+        let (x_min, x_max) = ink.unwrap_or((0.0, advance));
+        self.font_bounds_rect(x_min.min(0.0), x_max.max(advance))
+    }
+
+    /// A rect spanning from `x_min` to `x_max` horizontally (in font units), and the font's bounding box
+    /// vertically
+    fn font_bounds_rect(&self, x_min: f64, x_max: f64) -> Rect {
+        Rect::new(
+            x_min * self.scale,
+            -self.font_bounds.y1 * self.scale,
+            x_max * self.scale,
+            -self.font_bounds.y0 * self.scale,
+        )
+    }
+}
@@ -0,0 +1,288 @@
+//! A stateful, Canvas2D-style painter built on top of a [`PaintScene`]
+
+use crate::{Glyph, Paint, PaintRef, PaintScene, SceneFragment};
+use kurbo::{Affine, Rect, Shape, Stroke, Vec2};
+use peniko::{BlendMode, Color, Fill, FontData, Gradient, ImageBrushRef};
+
+/// The drawing state saved by [`CanvasPainter::save`]
+#[derive(Copy, Clone, Debug)]
+struct CanvasState {
+    transform: Affine,
+    global_alpha: f32,
+    /// The number of layers pushed (by clipping or [`CanvasPainter::push_layer`]) since the state was saved
+    layer_count: usize,
+}
+
+impl Default for CanvasState {
+    fn default() -> Self {
+        Self {
+            transform: Affine::IDENTITY,
+            global_alpha: 1.0,
+            layer_count: 0,
+        }
+    }
+}
+
+/// Multiply the alpha of a brush. Gradients are copied into `gradient` so that their stops can be modified.
+fn multiply_alpha<'a>(
+    brush: PaintRef<'a>,
+    alpha: f32,
+    gradient: &'a mut Option<Gradient>,
+) -> PaintRef<'a> {
+    if alpha == 1.0 {
+        return brush;
+    }
+    match brush {
+        Paint::Solid(color) => Paint::Solid(color.multiply_alpha(alpha)),
+        Paint::Gradient(g) => Paint::Gradient(gradient.insert(g.clone().multiply_alpha(alpha))),
+        Paint::Image(image) => Paint::Image(image.multiply_alpha(alpha)),
+        // Custom paints are opaque to us, so their alpha can't be modified
+        Paint::Custom(custom) => Paint::Custom(custom),
+    }
+}
+
+/// A painter with a current transform, clip and global alpha which can be saved and restored, in the style of
+/// the HTML Canvas2D API.
+///
+/// [`PaintScene`] itself is stateless: every command takes its own transform, and the layer stack doesn't save
+/// or modify transforms. `CanvasPainter` wraps any [`PaintScene`] and tracks this state so that callers don't have
+/// to thread it through by hand:
+///
+/// - [`translate`](Self::translate), [`scale`](Self::scale), [`rotate`](Self::rotate) and
+///   [`concat`](Self::concat) modify the current transform, which applies to every subsequent drawing command
+/// - [`clip`](Self::clip) and [`push_layer`](Self::push_layer) push layers which stay active until the state is
+///   restored
+/// - [`set_global_alpha`](Self::set_global_alpha) multiplies the alpha of every subsequent drawing command
+/// - [`save`](Self::save) saves all of the above, and [`restore`](Self::restore) restores it, popping any layers
+///   pushed in between
+///
+/// Layers which are still pushed when the painter is unwrapped with [`into_inner`](Self::into_inner) are popped.
+pub struct CanvasPainter<S: PaintScene> {
+    scene: S,
+    state: CanvasState,
+    stack: Vec<CanvasState>,
+}
+
+impl<S: PaintScene> CanvasPainter<S> {
+    pub fn new(scene: S) -> Self {
+        Self {
+            scene,
+            state: CanvasState::default(),
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn scene(&self) -> &S {
+        &self.scene
+    }
+
+    /// Access the underlying scene directly. Commands drawn to it don't use the painter's state.
+    pub fn scene_mut(&mut self) -> &mut S {
+        &mut self.scene
+    }
+
+    /// Restore every saved state (popping all layers) and unwrap the underlying scene
+    pub fn into_inner(mut self) -> S {
+        self.restore_all();
+        self.scene
+    }
+
+    /// Save the current transform, clip and global alpha
+    pub fn save(&mut self) {
+        self.stack.push(self.state);
+        self.state.layer_count = 0;
+    }
+
+    /// Restore the most recently saved state, popping any layers pushed since it was saved.
+    /// Does nothing if there is no saved state.
+    pub fn restore(&mut self) {
+        if let Some(state) = self.stack.pop() {
+            self.pop_layers();
+            self.state = state;
+        }
+    }
+
+    /// Restore the state to how it was before the first call to [`save`](Self::save), popping every layer
+    pub fn restore_all(&mut self) {
+        while !self.stack.is_empty() {
+            self.restore();
+        }
+        self.pop_layers();
+    }
+
+    /// The number of saved states
+    pub fn save_count(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn pop_layers(&mut self) {
+        for _ in 0..self.state.layer_count {
+            self.scene.pop_layer();
+        }
+        self.state.layer_count = 0;
+    }
+
+    pub fn transform(&self) -> Affine {
+        self.state.transform
+    }
+
+    /// Replace the current transform
+    pub fn set_transform(&mut self, transform: Affine) {
+        self.state.transform = transform;
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.state.transform = Affine::IDENTITY;
+    }
+
+    /// Apply `transform` before the current transform (so that it applies in the current coordinate space)
+    pub fn concat(&mut self, transform: Affine) {
+        self.state.transform *= transform;
+    }
+
+    pub fn translate(&mut self, offset: impl Into<Vec2>) {
+        self.concat(Affine::translate(offset));
+    }
+
+    pub fn scale(&mut self, scale: f64) {
+        self.concat(Affine::scale(scale));
+    }
+
+    pub fn scale_non_uniform(&mut self, scale_x: f64, scale_y: f64) {
+        self.concat(Affine::scale_non_uniform(scale_x, scale_y));
+    }
+
+    /// Rotate clockwise (in a y-down coordinate space) by `angle` radians
+    pub fn rotate(&mut self, angle: f64) {
+        self.concat(Affine::rotate(angle));
+    }
+
+    pub fn global_alpha(&self) -> f32 {
+        self.state.global_alpha
+    }
+
+    /// Set the alpha which every subsequent drawing command is multiplied by
+    pub fn set_global_alpha(&mut self, alpha: f32) {
+        self.state.global_alpha = alpha;
+    }
+
+    /// Intersect the current clip with `shape` (in the current coordinate space)
+    pub fn clip(&mut self, shape: &impl Shape) {
+        self.scene.push_clip_layer(self.state.transform, shape);
+        self.state.layer_count += 1;
+    }
+
+    /// Push a layer, clipped by `clip` (in the current coordinate space), which subsequent drawing commands are
+    /// composited into. The layer is popped when the current state is restored.
+    pub fn push_layer(&mut self, blend: impl Into<BlendMode>, alpha: f32, clip: &impl Shape) {
+        self.scene
+            .push_layer(blend, alpha, self.state.transform, clip);
+        self.state.layer_count += 1;
+    }
+
+    /// Fill a shape using the non-zero fill rule
+    pub fn fill<'a>(&mut self, shape: &impl Shape, brush: impl Into<PaintRef<'a>>) {
+        self.fill_with_rule(Fill::NonZero, shape, brush);
+    }
+
+    pub fn fill_with_rule<'a>(
+        &mut self,
+        rule: Fill,
+        shape: &impl Shape,
+        brush: impl Into<PaintRef<'a>>,
+    ) {
+        let mut gradient = None;
+        let brush = multiply_alpha(brush.into(), self.state.global_alpha, &mut gradient);
+        self.scene
+            .fill(rule, self.state.transform, brush, None, shape);
+    }
+
+    pub fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        shape: &impl Shape,
+        brush: impl Into<PaintRef<'a>>,
+    ) {
+        let mut gradient = None;
+        let brush = multiply_alpha(brush.into(), self.state.global_alpha, &mut gradient);
+        self.scene
+            .stroke(style, self.state.transform, brush, None, shape);
+    }
+
+    /// Fill a run of glyphs positioned in the current coordinate space
+    pub fn fill_glyphs<'a>(
+        &mut self,
+        font: &FontData,
+        font_size: f32,
+        brush: impl Into<PaintRef<'a>>,
+        glyphs: impl IntoIterator<Item = Glyph>,
+    ) {
+        let brush = brush.into();
+        self.scene.draw_glyphs(
+            font,
+            font_size,
+            false,
+            &[],
+            Fill::NonZero,
+            brush,
+            self.state.global_alpha,
+            self.state.transform,
+            None,
+            glyphs.into_iter(),
+        );
+    }
+
+    /// Stroke the outlines of a run of glyphs positioned in the current coordinate space
+    pub fn stroke_glyphs<'a>(
+        &mut self,
+        style: &Stroke,
+        font: &FontData,
+        font_size: f32,
+        brush: impl Into<PaintRef<'a>>,
+        glyphs: impl IntoIterator<Item = Glyph>,
+    ) {
+        let brush = brush.into();
+        self.scene.draw_glyphs(
+            font,
+            font_size,
+            false,
+            &[],
+            style,
+            brush,
+            self.state.global_alpha,
+            self.state.transform,
+            None,
+            glyphs.into_iter(),
+        );
+    }
+
+    /// Draw an image with its top-left corner at the origin of the current coordinate space
+    pub fn draw_image(&mut self, image: ImageBrushRef) {
+        let image = image.multiply_alpha(self.state.global_alpha);
+        self.scene.draw_image(image, self.state.transform);
+    }
+
+    pub fn draw_box_shadow(&mut self, rect: Rect, color: Color, radius: f64, std_dev: f64) {
+        self.scene.draw_box_shadow(
+            self.state.transform,
+            rect,
+            color.multiply_alpha(self.state.global_alpha),
+            radius,
+            std_dev,
+        );
+    }
+
+    /// Draw a scene fragment in the current coordinate space. If the global alpha isn't 1, the fragment is drawn
+    /// into a layer with that alpha (so that overlapping content within the fragment isn't blended twice).
+    pub fn draw_fragment(&mut self, fragment: &SceneFragment) {
+        let alpha = self.state.global_alpha;
+        if alpha == 1.0 {
+            self.scene.append_fragment(fragment, self.state.transform);
+        } else if let Some(bounds) = fragment.bounds() {
+            self.scene
+                .push_layer(peniko::Mix::Normal, alpha, self.state.transform, &bounds);
+            self.scene.append_fragment(fragment, self.state.transform);
+            self.scene.pop_layer();
+        }
+    }
+}
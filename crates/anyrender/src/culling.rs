@@ -0,0 +1,214 @@
+//! A [`PaintScene`] adapter which drops drawing commands that fall outside of a viewport
+
+use crate::{Glyph, NormalizedCoord, PaintRef, PaintScene, bounds};
+use kurbo::{Affine, Rect, Shape, Stroke};
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+
+/// A layer pushed onto a [`CullingPaintScene`]
+#[derive(Copy, Clone, Debug)]
+struct CullingLayer {
+    /// The (conservative) device-space clip of the layer, including the clips of enclosing layers
+    clip: Rect,
+    /// Whether the layer was forwarded to the inner scene (and therefore needs to be popped)
+    forwarded: bool,
+}
+
+/// A [`PaintScene`] that wraps another [`PaintScene`] and drops drawing commands which fall entirely outside of
+/// a viewport rect or entirely outside of the current clip.
+///
+/// Bounds are computed conservatively (from transformed bounding boxes), so some commands which are not visible
+/// may still be forwarded, but no visible command will be dropped. Layers whose clip falls outside of the viewport
+/// are dropped along with everything drawn into them, and `push_layer`/`pop_layer` calls forwarded to the inner
+/// scene are always kept balanced.
+pub struct CullingPaintScene<S: PaintScene> {
+    inner: S,
+    viewport: Rect,
+    layers: Vec<CullingLayer>,
+    culled_count: usize,
+}
+
+impl<S: PaintScene> CullingPaintScene<S> {
+    /// Wrap `inner`, culling commands that fall outside of `viewport` (in device space)
+    pub fn new(inner: S, viewport: Rect) -> Self {
+        Self {
+            inner,
+            viewport,
+            layers: Vec::new(),
+            culled_count: 0,
+        }
+    }
+
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Set the viewport. This should only be called when there are no layers pushed.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    /// The number of drawing commands (and layers) which have been dropped since the scene was created or reset
+    pub fn culled_count(&self) -> usize {
+        self.culled_count
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// The current clip: the viewport intersected with the clips of all pushed layers
+    fn clip(&self) -> Rect {
+        self.layers
+            .last()
+            .map(|layer| layer.clip)
+            .unwrap_or(self.viewport)
+    }
+
+    /// Whether content with the specified bounds would be visible. Records the command as culled if not.
+    fn is_visible(&mut self, bounds: Rect) -> bool {
+        let visible =
+            self.layers.last().is_none_or(|layer| layer.forwarded) && bounds.overlaps(self.clip());
+        if !visible {
+            self.culled_count += 1;
+        }
+        visible
+    }
+
+    /// Push a layer onto the layer stack, returning whether it should be forwarded to the inner scene
+    fn push(&mut self, bounds: Rect) -> bool {
+        let clip = self.clip();
+        let forwarded = self.is_visible(bounds);
+        self.layers.push(CullingLayer {
+            clip: clip.intersect(bounds),
+            forwarded,
+        });
+        forwarded
+    }
+}
+
+impl<S: PaintScene> PaintScene for CullingPaintScene<S> {
+    fn reset(&mut self) {
+        self.layers.clear();
+        self.culled_count = 0;
+        self.inner.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        if self.push(bounds::shape_bounds(transform, clip)) {
+            self.inner.push_layer(blend, alpha, transform, clip);
+        }
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        if self.push(bounds::shape_bounds(transform, clip)) {
+            self.inner.push_clip_layer(transform, clip);
+        }
+    }
+
+    fn pop_layer(&mut self) {
+        // Unbalanced pops are forwarded so that the inner scene can handle them as it would without culling
+        if self.layers.pop().is_none_or(|layer| layer.forwarded) {
+            self.inner.pop_layer();
+        }
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        if self.is_visible(bounds::stroke_bounds(style, transform, shape)) {
+            self.inner
+                .stroke(style, transform, brush, brush_transform, shape);
+        }
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        if self.is_visible(bounds::shape_bounds(transform, shape)) {
+            self.inner
+                .fill(style, transform, brush, brush_transform, shape);
+        }
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let style = style.into();
+        let stroke = match style {
+            StyleRef::Fill(_) => None,
+            StyleRef::Stroke(stroke) => Some(stroke),
+        };
+        let glyphs: Vec<Glyph> = glyphs.collect();
+        let bounds = bounds::glyph_run_bounds(
+            font,
+            font_size,
+            stroke,
+            transform,
+            glyph_transform,
+            glyphs.iter(),
+        );
+
+        if self.is_visible(bounds) {
+            self.inner.draw_glyphs(
+                font,
+                font_size,
+                hint,
+                normalized_coords,
+                style,
+                brush,
+                brush_alpha,
+                transform,
+                glyph_transform,
+                glyphs.into_iter(),
+            );
+        }
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        if self.is_visible(bounds::box_shadow_bounds(transform, rect, std_dev)) {
+            self.inner
+                .draw_box_shadow(transform, rect, brush, radius, std_dev);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Computing the regions which differ between two recorded scenes
+
+use crate::recording::{RenderCommand, Scene};
+use kurbo::Rect;
+
+/// Above this edit distance between two scenes, the diff gives up and treats all remaining commands as changed
+const MAX_EDIT_DISTANCE: usize = 1024;
+
+/// A layer push or drawing command, along with the layers it is drawn into
+struct DiffItem<'a> {
+    /// The `PushLayer`/`PushClipLayer` commands enclosing the command
+    layers: Vec<&'a RenderCommand>,
+    command: &'a RenderCommand,
+    /// The device-space bounds of the command, clipped by the enclosing layers (`None` if it is clipped out)
+    bounds: Option<Rect>,
+}
+
+impl PartialEq for DiffItem<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.command == other.command && self.layers == other.layers
+    }
+}
+
+fn diff_items(scene: &Scene) -> Vec<DiffItem<'_>> {
+    let mut items = Vec::with_capacity(scene.commands.len());
+    let mut layers: Vec<&RenderCommand> = Vec::new();
+    let mut clips: Vec<Option<Rect>> = Vec::new();
+
+    for command in &scene.commands {
+        if let RenderCommand::PopLayer = command {
+            layers.pop();
+            clips.pop();
+            continue;
+        }
+
+        let bounds = match (command.bounds(), clips.last()) {
+            (Some(bounds), Some(Some(clip))) if bounds.overlaps(*clip) => {
+                Some(bounds.intersect(*clip))
+            }
+            (Some(_), Some(_)) => None,
+            (bounds, None) => bounds,
+            (None, Some(_)) => None,
+        };
+        items.push(DiffItem {
+            layers: layers.clone(),
+            command,
+            bounds,
+        });
+
+        if let RenderCommand::PushLayer(_) | RenderCommand::PushClipLayer(_) = command {
+            layers.push(command);
+            clips.push(bounds);
+        }
+    }
+
+    items
+}
+
+/// Compute which items of `a` and `b` are part of a longest common subsequence of the two slices, using Myers'
+/// diff algorithm. If the edit distance exceeds [`MAX_EDIT_DISTANCE`] then no items are marked.
+fn mark_common<T: PartialEq>(a: &[T], b: &[T], a_common: &mut [bool], b_common: &mut [bool]) {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // Snapshots of `v[-d-1..=d+1]` taken at the start of each round `d`
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut found = None;
+    'search: for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found = Some(d);
+                break 'search;
+            }
+        }
+    }
+    let Some(distance) = found else {
+        return;
+    };
+
+    // Walk back through the trace marking the diagonal moves (matching items)
+    let (mut x, mut y) = (n, m);
+    for d in (0..=distance).rev() {
+        let snapshot = &trace[d as usize];
+        let get = |k: isize| snapshot[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            a_common[(x - 1) as usize] = true;
+            b_common[(y - 1) as usize] = true;
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            x = prev_x;
+            y = prev_y;
+        }
+    }
+}
+
+/// Merge overlapping rects until no two rects overlap
+fn merge_rects(mut rects: Vec<Rect>) -> Vec<Rect> {
+    let mut merged = true;
+    while merged {
+        merged = false;
+        let mut i = 0;
+        while i < rects.len() {
+            let mut j = i + 1;
+            while j < rects.len() {
+                if rects[i].overlaps(rects[j]) {
+                    let other = rects.swap_remove(j);
+                    rects[i] = rects[i].union(other);
+                    merged = true;
+                } else {
+                    j += 1;
+                }
+            }
+            i += 1;
+        }
+    }
+    rects
+}
+
+impl Scene {
+    /// Compute the device-space regions in which rendering this scene may produce different output to rendering
+    /// `previous`.
+    ///
+    /// Commands are compared structurally (including their transforms and the layers they are drawn into), and the
+    /// clipped bounds of every command that was added, removed or changed are returned as a set of non-overlapping
+    /// rectangles. An empty result means that both scenes render identically.
+    pub fn damage(&self, previous: &Scene) -> Vec<Rect> {
+        let old = diff_items(previous);
+        let new = diff_items(self);
+
+        // Skip the common prefix and suffix before diffing the remainder
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let old = &old[prefix..old.len() - suffix];
+        let new = &new[prefix..new.len() - suffix];
+
+        let mut old_common = vec![false; old.len()];
+        let mut new_common = vec![false; new.len()];
+        mark_common(old, new, &mut old_common, &mut new_common);
+
+        let changed = old
+            .iter()
+            .zip(old_common)
+            .chain(new.iter().zip(new_common))
+            .filter(|(_, common)| !common)
+            .filter_map(|(item, _)| item.bounds)
+            .collect();
+
+        merge_rects(changed)
+    }
+}
@@ -0,0 +1,510 @@
+//! Object-safe versions of the AnyRender traits which allow backends to be selected at runtime
+//!
+//! [`PaintScene`], [`WindowRenderer`] and [`ImageRenderer`] use generic parameters (`impl Shape`,
+//! `impl Into<PaintRef>`, closures, etc) so they cannot be used as trait objects. The `Dyn*` traits in
+//! this module mirror them using only concrete types. They are implemented for every type that implements
+//! the corresponding generic trait, and [`PaintScene`] and [`WindowRenderer`] are in turn implemented
+//! for the trait objects. So code can be written against the generic traits and still be driven by a
+//! `Box<dyn DynWindowRenderer>`.
+//!
+//! Note that as a trait object implements both the `Dyn*` trait and the generic trait, method calls on it are
+//! ambiguous if both traits are in scope. Generally you will only want to import one of them.
+
+use crate::recording::RecordedShape;
+use crate::{
+    Glyph, ImageRenderer, NormalizedCoord, PaintRef, PaintScene, RenderError, Scene, SceneFragment,
+    WindowHandle, WindowRenderer,
+};
+use kurbo::{Affine, Rect, Shape, Stroke};
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+use std::sync::Arc;
+
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+/// Object-safe version of [`PaintScene`]
+///
+/// This is implemented for all types that implement [`PaintScene`], and [`PaintScene`] is implemented
+/// for `dyn DynPaintScene`. See the [`PaintScene`] docs for documentation of the individual methods.
+///
+/// Shapes are passed as a [`RecordedShape`], so primitive shapes keep their fast paths while any other shape
+/// is converted into a path.
+pub trait DynPaintScene {
+    fn reset(&mut self);
+
+    fn push_layer(&mut self, blend: BlendMode, alpha: f32, transform: Affine, clip: &RecordedShape);
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &RecordedShape);
+
+    fn pop_layer(&mut self);
+
+    fn stroke(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: PaintRef<'_>,
+        brush_transform: Option<Affine>,
+        shape: &RecordedShape,
+    );
+
+    fn fill(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: PaintRef<'_>,
+        brush_transform: Option<Affine>,
+        shape: &RecordedShape,
+    );
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_glyphs(
+        &mut self,
+        font: &FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &[NormalizedCoord],
+        style: StyleRef<'_>,
+        brush: PaintRef<'_>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: &mut dyn Iterator<Item = Glyph>,
+    );
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    );
+
+    fn append_scene(&mut self, scene: Scene, scene_transform: Affine);
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine);
+}
+
+impl<S: PaintScene> DynPaintScene for S {
+    fn reset(&mut self) {
+        PaintScene::reset(self);
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: BlendMode,
+        alpha: f32,
+        transform: Affine,
+        clip: &RecordedShape,
+    ) {
+        PaintScene::push_layer(self, blend, alpha, transform, clip);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &RecordedShape) {
+        PaintScene::push_clip_layer(self, transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        PaintScene::pop_layer(self);
+    }
+
+    fn stroke(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: PaintRef<'_>,
+        brush_transform: Option<Affine>,
+        shape: &RecordedShape,
+    ) {
+        PaintScene::stroke(self, style, transform, brush, brush_transform, shape);
+    }
+
+    fn fill(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: PaintRef<'_>,
+        brush_transform: Option<Affine>,
+        shape: &RecordedShape,
+    ) {
+        PaintScene::fill(self, style, transform, brush, brush_transform, shape);
+    }
+
+    fn draw_glyphs(
+        &mut self,
+        font: &FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &[NormalizedCoord],
+        style: StyleRef<'_>,
+        brush: PaintRef<'_>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: &mut dyn Iterator<Item = Glyph>,
+    ) {
+        PaintScene::draw_glyphs(
+            self,
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs,
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        PaintScene::draw_box_shadow(self, transform, rect, brush, radius, std_dev);
+    }
+
+    fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
+        PaintScene::append_scene(self, scene, scene_transform);
+    }
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        PaintScene::append_fragment(self, fragment, transform);
+    }
+}
+
+impl PaintScene for dyn DynPaintScene + '_ {
+    fn reset(&mut self) {
+        DynPaintScene::reset(self);
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        DynPaintScene::push_layer(
+            self,
+            blend.into(),
+            alpha,
+            transform,
+            &RecordedShape::from_shape(clip, DEFAULT_TOLERANCE),
+        );
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        DynPaintScene::push_clip_layer(
+            self,
+            transform,
+            &RecordedShape::from_shape(clip, DEFAULT_TOLERANCE),
+        );
+    }
+
+    fn pop_layer(&mut self) {
+        DynPaintScene::pop_layer(self);
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        DynPaintScene::stroke(
+            self,
+            style,
+            transform,
+            brush.into(),
+            brush_transform,
+            &RecordedShape::from_shape(shape, DEFAULT_TOLERANCE),
+        );
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        DynPaintScene::fill(
+            self,
+            style,
+            transform,
+            brush.into(),
+            brush_transform,
+            &RecordedShape::from_shape(shape, DEFAULT_TOLERANCE),
+        );
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        mut glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        DynPaintScene::draw_glyphs(
+            self,
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style.into(),
+            brush.into(),
+            brush_alpha,
+            transform,
+            glyph_transform,
+            &mut glyphs,
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        DynPaintScene::draw_box_shadow(self, transform, rect, brush, radius, std_dev);
+    }
+
+    fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
+        DynPaintScene::append_scene(self, scene, scene_transform);
+    }
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        DynPaintScene::append_fragment(self, fragment, transform);
+    }
+}
+
+/// Object-safe version of [`WindowRenderer`]
+///
+/// This is implemented for all types that implement [`WindowRenderer`], and [`WindowRenderer`] is
+/// implemented for `Box<dyn DynWindowRenderer>`.
+pub trait DynWindowRenderer {
+    fn resume(&mut self, window: Arc<dyn WindowHandle>, width: u32, height: u32);
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError>;
+    fn suspend(&mut self);
+    fn is_active(&self) -> bool;
+    fn set_size(&mut self, width: u32, height: u32);
+    /// Render a frame. `draw_fn` is called at most once.
+    fn render(&mut self, draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene));
+    /// Render a frame. `draw_fn` is called at most once.
+    fn try_render(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+    ) -> Result<(), RenderError>;
+}
+
+impl<R: WindowRenderer> DynWindowRenderer for R {
+    fn resume(&mut self, window: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        WindowRenderer::resume(self, window, width, height);
+    }
+
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
+        WindowRenderer::try_resume(self, window, width, height)
+    }
+
+    fn suspend(&mut self) {
+        WindowRenderer::suspend(self);
+    }
+
+    fn is_active(&self) -> bool {
+        WindowRenderer::is_active(self)
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        WindowRenderer::set_size(self, width, height);
+    }
+
+    fn render(&mut self, draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene)) {
+        WindowRenderer::render(self, |scene| draw_fn(scene));
+    }
+
+    fn try_render(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+    ) -> Result<(), RenderError> {
+        WindowRenderer::try_render(self, |scene| draw_fn(scene))
+    }
+}
+
+impl WindowRenderer for Box<dyn DynWindowRenderer> {
+    type ScenePainter<'a>
+        = &'a mut (dyn DynPaintScene + 'a)
+    where
+        Self: 'a;
+
+    fn resume(&mut self, window: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        (**self).resume(window, width, height);
+    }
+
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
+        (**self).try_resume(window, width, height)
+    }
+
+    fn suspend(&mut self) {
+        (**self).suspend();
+    }
+
+    fn is_active(&self) -> bool {
+        (**self).is_active()
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        (**self).set_size(width, height);
+    }
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        let mut draw_fn = Some(draw_fn);
+        (**self).render(&mut |mut scene| {
+            if let Some(draw_fn) = draw_fn.take() {
+                draw_fn(&mut scene);
+            }
+        });
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        let mut draw_fn = Some(draw_fn);
+        (**self).try_render(&mut |mut scene| {
+            if let Some(draw_fn) = draw_fn.take() {
+                draw_fn(&mut scene);
+            }
+        })
+    }
+}
+
+/// Object-safe version of [`ImageRenderer`]
+///
+/// This is implemented for all types that implement [`ImageRenderer`]. As trait objects cannot be
+/// constructed through a trait, renderers should be created with [`ImageRenderer::new`] (or
+/// [`ImageRenderer::try_new`]) and then boxed.
+pub trait DynImageRenderer {
+    fn resize(&mut self, width: u32, height: u32);
+    fn reset(&mut self);
+    /// Render a frame into `vec`. `draw_fn` is called at most once.
+    fn render_to_vec(&mut self, draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene), vec: &mut Vec<u8>);
+    /// Render a frame into `buffer`. `draw_fn` is called at most once.
+    fn render(&mut self, draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene), buffer: &mut [u8]);
+    /// Render a frame into `vec`. `draw_fn` is called at most once.
+    fn try_render_to_vec(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        vec: &mut Vec<u8>,
+    ) -> Result<(), RenderError>;
+    /// Render a frame into `buffer`. `draw_fn` is called at most once.
+    fn try_render(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        buffer: &mut [u8],
+    ) -> Result<(), RenderError>;
+    /// Render the pixels within `region` into `buffer`. `draw_fn` is called at most once.
+    fn render_region(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    );
+    /// Render the pixels within `region` into `buffer`. `draw_fn` is called at most once.
+    fn try_render_region(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), RenderError>;
+}
+
+impl<R: ImageRenderer> DynImageRenderer for R {
+    fn resize(&mut self, width: u32, height: u32) {
+        ImageRenderer::resize(self, width, height);
+    }
+
+    fn reset(&mut self) {
+        ImageRenderer::reset(self);
+    }
+
+    fn render_to_vec(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        vec: &mut Vec<u8>,
+    ) {
+        ImageRenderer::render_to_vec(self, |scene| draw_fn(scene), vec);
+    }
+
+    fn render(&mut self, draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene), buffer: &mut [u8]) {
+        ImageRenderer::render(self, |scene| draw_fn(scene), buffer);
+    }
+
+    fn try_render_to_vec(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        vec: &mut Vec<u8>,
+    ) -> Result<(), RenderError> {
+        ImageRenderer::try_render_to_vec(self, |scene| draw_fn(scene), vec)
+    }
+
+    fn try_render(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        buffer: &mut [u8],
+    ) -> Result<(), RenderError> {
+        ImageRenderer::try_render(self, |scene| draw_fn(scene), buffer)
+    }
+
+    fn render_region(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) {
+        ImageRenderer::render_region(self, |scene| draw_fn(scene), region, buffer, stride);
+    }
+
+    fn try_render_region(
+        &mut self,
+        draw_fn: &mut dyn FnMut(&mut dyn DynPaintScene),
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), RenderError> {
+        ImageRenderer::try_render_region(self, |scene| draw_fn(scene), region, buffer, stride)
+    }
+}
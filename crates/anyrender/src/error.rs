@@ -0,0 +1,56 @@
+//! Error type for AnyRender renderers
+
+use std::error::Error;
+use std::fmt::Display;
+
+/// A type-erased backend error
+pub type BoxedError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Errors that can occur in a [`WindowRenderer`](crate::WindowRenderer) or [`ImageRenderer`](crate::ImageRenderer).
+///
+/// The backend-specific cause of the error can be retrieved using [`Error::source`].
+#[derive(Debug)]
+pub enum RenderError {
+    /// Failed to create the renderer (for example because there is no compatible device).
+    Init(BoxedError),
+    /// Failed to resume the renderer (for example because there is no compatible device
+    /// or because creating a surface for the window failed).
+    Resume(BoxedError),
+    /// Failed to render a frame (for example because the surface or output buffer could not be acquired).
+    Render(BoxedError),
+}
+
+impl RenderError {
+    /// Create a [`RenderError::Init`] from a backend error
+    pub fn init(err: impl Into<BoxedError>) -> Self {
+        Self::Init(err.into())
+    }
+
+    /// Create a [`RenderError::Resume`] from a backend error
+    pub fn resume(err: impl Into<BoxedError>) -> Self {
+        Self::Resume(err.into())
+    }
+
+    /// Create a [`RenderError::Render`] from a backend error
+    pub fn render(err: impl Into<BoxedError>) -> Self {
+        Self::Render(err.into())
+    }
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Init(inner) => write!(f, "Couldn't create renderer: {inner}"),
+            Self::Resume(inner) => write!(f, "Couldn't resume renderer: {inner}"),
+            Self::Render(inner) => write!(f, "Couldn't render frame: {inner}"),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Init(inner) | Self::Resume(inner) | Self::Render(inner) => Some(inner.as_ref()),
+        }
+    }
+}
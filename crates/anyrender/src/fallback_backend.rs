@@ -0,0 +1,134 @@
+//! A [`WindowRenderer`] which tries a list of backends in order until one of them resumes successfully
+
+use crate::{DynPaintScene, DynWindowRenderer, RenderError, WindowHandle, WindowRenderer};
+use std::sync::Arc;
+
+type BackendConstructor = Box<dyn Fn() -> Box<dyn DynWindowRenderer>>;
+
+/// A [`WindowRenderer`] that wraps an ordered list of backends.
+///
+/// On resume, each backend is constructed and resumed in turn until one succeeds. Backends that fail to resume
+/// (see [`WindowRenderer::try_resume`]) are skipped for the lifetime of the renderer. This allows an application
+/// to prefer a GPU backend while still running on machines where no compatible device is available:
+///
+/// ```ignore
+/// let renderer = FallbackWindowRenderer::new()
+///     .with_backend(VelloWindowRenderer::new)
+///     .with_backend(SoftbufferWindowRenderer::<VelloCpuImageRenderer>::new);
+/// ```
+#[derive(Default)]
+pub struct FallbackWindowRenderer {
+    backends: Vec<BackendConstructor>,
+    /// Index of the backend currently in use (or the next backend to try)
+    current: usize,
+    renderer: Option<Box<dyn DynWindowRenderer>>,
+    failures: Vec<(usize, RenderError)>,
+}
+
+impl FallbackWindowRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a backend to the end of the list of backends to try
+    pub fn with_backend<R, F>(mut self, constructor: F) -> Self
+    where
+        R: WindowRenderer + 'static,
+        F: Fn() -> R + 'static,
+    {
+        self.push_backend(constructor);
+        self
+    }
+
+    /// Add a backend to the end of the list of backends to try
+    pub fn push_backend<R, F>(&mut self, constructor: F)
+    where
+        R: WindowRenderer + 'static,
+        F: Fn() -> R + 'static,
+    {
+        self.backends
+            .push(Box::new(move || Box::new(constructor()) as _));
+    }
+
+    /// The index (in the order they were added) of the backend that is currently active
+    pub fn active_backend(&self) -> Option<usize> {
+        WindowRenderer::is_active(self).then_some(self.current)
+    }
+
+    /// The errors returned by backends which failed to resume, along with the index of the backend
+    pub fn failures(&self) -> &[(usize, RenderError)] {
+        &self.failures
+    }
+}
+
+impl WindowRenderer for FallbackWindowRenderer {
+    type ScenePainter<'a>
+        = &'a mut (dyn DynPaintScene + 'a)
+    where
+        Self: 'a;
+
+    fn resume(&mut self, window: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        WindowRenderer::try_resume(self, window, width, height)
+            .expect("Error resuming fallback renderer");
+    }
+
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
+        while self.current < self.backends.len() {
+            let renderer = self
+                .renderer
+                .get_or_insert_with(|| (self.backends[self.current])());
+            match DynWindowRenderer::try_resume(&mut **renderer, window.clone(), width, height) {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    self.renderer = None;
+                    self.failures.push((self.current, err));
+                    self.current += 1;
+                }
+            }
+        }
+
+        Err(RenderError::resume(format!(
+            "none of the {} fallback backends could be resumed",
+            self.backends.len()
+        )))
+    }
+
+    fn suspend(&mut self) {
+        if let Some(renderer) = &mut self.renderer {
+            DynWindowRenderer::suspend(&mut **renderer);
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.renderer
+            .as_ref()
+            .is_some_and(|renderer| DynWindowRenderer::is_active(&**renderer))
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        if let Some(renderer) = &mut self.renderer {
+            DynWindowRenderer::set_size(&mut **renderer, width, height);
+        }
+    }
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        if let Some(renderer) = &mut self.renderer {
+            WindowRenderer::render(renderer, draw_fn);
+        }
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        match &mut self.renderer {
+            Some(renderer) => WindowRenderer::try_render(renderer, draw_fn),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Converting glyph runs into outlines
+
+use crate::{Glyph, NormalizedCoord};
+use kurbo::{Affine, BezPath, Point};
+use peniko::FontData;
+use skrifa::{
+    FontRef, GlyphId, MetadataProvider,
+    instance::{LocationRef, Size},
+    outline::{DrawSettings, OutlinePen},
+    raw::types::F2Dot14,
+};
+
+/// An [`OutlinePen`] which appends to a [`BezPath`], applying a transform to each point
+struct BezPathPen<'a> {
+    path: &'a mut BezPath,
+    transform: Affine,
+}
+
+impl BezPathPen<'_> {
+    fn point(&self, x: f32, y: f32) -> Point {
+        self.transform * Point::new(x as f64, y as f64)
+    }
+}
+
+impl OutlinePen for BezPathPen<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path.move_to(self.point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path.line_to(self.point(x, y));
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.path.quad_to(self.point(cx0, cy0), self.point(x, y));
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.path
+            .curve_to(self.point(cx0, cy0), self.point(cx1, cy1), self.point(x, y));
+    }
+
+    fn close(&mut self) {
+        self.path.close_path();
+    }
+}
+
+/// Combine the outlines of a run of glyphs into a single path (in the coordinate space of the run).
+///
+/// This is useful for backends which can't draw glyphs directly, and instead fill or stroke their outlines.
+/// Glyphs that can't be found in the font are skipped. Returns an empty path if the font can't be parsed.
+pub fn glyph_run_outline(
+    font: &FontData,
+    font_size: f32,
+    normalized_coords: &[NormalizedCoord],
+    glyph_transform: Option<Affine>,
+    glyphs: impl Iterator<Item = Glyph>,
+) -> BezPath {
+    let mut path = BezPath::new();
+    let Ok(font) = FontRef::from_index(font.data.data(), font.index) else {
+        return path;
+    };
+    let outlines = font.outline_glyphs();
+    let coords: Vec<F2Dot14> = normalized_coords
+        .iter()
+        .map(|coord| F2Dot14::from_bits(*coord))
+        .collect();
+    let location = LocationRef::new(&coords);
+    // Font outlines are y-up
+    let glyph_transform = glyph_transform.unwrap_or_default() * Affine::FLIP_Y;
+
+    for glyph in glyphs {
+        let Some(outline) = outlines.get(GlyphId::new(glyph.id)) else {
+            continue;
+        };
+        let mut pen = BezPathPen {
+            path: &mut path,
+            transform: Affine::translate((glyph.x as f64, glyph.y as f64)) * glyph_transform,
+        };
+        let _ = outline.draw(
+            DrawSettings::unhinted(Size::new(font_size), location),
+            &mut pen,
+        );
+    }
+
+    path
+}
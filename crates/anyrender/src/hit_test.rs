@@ -0,0 +1,258 @@
+//! Finding the commands in a recorded [`Scene`] which cover a point or intersect a rect
+
+use crate::glyph::glyph_run_outline;
+use crate::recording::{RenderCommand, Scene};
+use kurbo::{Affine, BezPath, Line, ParamCurve, Point, Rect, Shape, Stroke, StrokeOpts};
+use peniko::{Fill, Style};
+
+/// The position of a command within a [`Scene`], as returned by [`Scene::hit_test`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CommandIndex {
+    /// The index of the command in [`Scene::commands`]
+    pub index: usize,
+    /// If the command at `index` is a fragment, the index of the command within the fragment (followed by the
+    /// index within each further nested fragment). Empty for commands drawn directly into the scene.
+    pub fragment_path: Vec<usize>,
+}
+
+impl From<usize> for CommandIndex {
+    fn from(index: usize) -> Self {
+        Self {
+            index,
+            fragment_path: Vec::new(),
+        }
+    }
+}
+
+/// The query used to hit test a scene
+#[derive(Copy, Clone, Debug)]
+enum Query {
+    Point(Point),
+    Rect(Rect),
+}
+
+/// The device-space area covered by a command or clip
+struct Region {
+    /// The outline of the region, flattened into lines
+    path: BezPath,
+    fill: Fill,
+}
+
+impl Region {
+    fn new(path: BezPath, fill: Fill, tolerance: f64) -> Self {
+        // Winding numbers computed against curves can be wrong for points level with the joins between curves,
+        // so the path is flattened into lines (which share their endpoints exactly)
+        let mut flattened = BezPath::new();
+        kurbo::flatten(path, tolerance, |el| flattened.push(el));
+        Self {
+            path: flattened,
+            fill,
+        }
+    }
+
+    /// The region covered by stroking `shape`
+    fn stroke(style: &Stroke, transform: Affine, shape: &impl Shape, tolerance: f64) -> Self {
+        let outline = kurbo::stroke(
+            shape.path_elements(tolerance),
+            style,
+            &StrokeOpts::default(),
+            tolerance,
+        );
+        Self::new(transform * outline, Fill::NonZero, tolerance)
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        let winding = self.path.winding(point);
+        match self.fill {
+            Fill::NonZero => winding != 0,
+            Fill::EvenOdd => winding % 2 != 0,
+        }
+    }
+
+    fn intersects(&self, rect: Rect) -> bool {
+        if !self.path.bounding_box().overlaps(rect) {
+            return false;
+        }
+        // If the region's boundary doesn't pass through the rect then the rect is either entirely inside or
+        // entirely outside of the region, so checking a single corner is enough
+        if self.contains(rect.origin()) {
+            return true;
+        }
+        let edges = [
+            Line::new((rect.x0, rect.y0), (rect.x1, rect.y0)),
+            Line::new((rect.x1, rect.y0), (rect.x1, rect.y1)),
+            Line::new((rect.x1, rect.y1), (rect.x0, rect.y1)),
+            Line::new((rect.x0, rect.y1), (rect.x0, rect.y0)),
+        ];
+        self.path.segments().any(|seg| {
+            rect.contains(seg.start())
+                || rect.contains(seg.end())
+                || edges
+                    .iter()
+                    .any(|edge| !seg.intersect_line(*edge).is_empty())
+        })
+    }
+
+    fn matches(&self, query: Query) -> bool {
+        match query {
+            Query::Point(point) => self.contains(point),
+            Query::Rect(rect) => self.intersects(rect),
+        }
+    }
+}
+
+impl Query {
+    /// A quick check against a command's bounds, before its exact geometry is computed
+    fn may_match(&self, bounds: Rect) -> bool {
+        match *self {
+            Query::Point(point) => bounds.contains(point),
+            Query::Rect(rect) => bounds.overlaps(rect),
+        }
+    }
+}
+
+/// State used while walking the commands of a scene (and any fragments it contains)
+struct HitTester {
+    query: Query,
+    tolerance: f64,
+    /// Whether the clip of each enclosing layer matches the query.
+    ///
+    /// For rect queries this is conservative: a command is considered a hit if both it and each enclosing clip
+    /// intersect the rect, even if their intersection doesn't.
+    clips: Vec<bool>,
+    hits: Vec<CommandIndex>,
+}
+
+impl HitTester {
+    fn is_clipped(&self) -> bool {
+        self.clips.contains(&false)
+    }
+
+    fn push_clip(&mut self, transform: Affine, clip: &impl Shape) {
+        let matches = !self.is_clipped()
+            && Region::new(
+                transform * clip.to_path(self.tolerance),
+                Fill::NonZero,
+                self.tolerance,
+            )
+            .matches(self.query);
+        self.clips.push(matches);
+    }
+
+    /// The region covered by a drawing command, or `None` if it isn't a drawing command
+    fn command_region(&self, command: &RenderCommand, transform: Affine) -> Option<Region> {
+        let tolerance = self.tolerance;
+        let region = match command {
+            RenderCommand::Fill(cmd) => Region::new(
+                transform * cmd.transform * cmd.shape.to_path(tolerance),
+                cmd.fill,
+                tolerance,
+            ),
+            RenderCommand::Stroke(cmd) => {
+                Region::stroke(&cmd.style, transform * cmd.transform, &cmd.shape, tolerance)
+            }
+            RenderCommand::GlyphRun(cmd) => {
+                let outline = glyph_run_outline(
+                    &cmd.font_data,
+                    cmd.font_size,
+                    &cmd.normalized_coords,
+                    cmd.glyph_transform,
+                    cmd.glyphs.iter().copied(),
+                );
+                match &cmd.style {
+                    Style::Fill(_) => Region::new(
+                        transform * cmd.transform * outline,
+                        Fill::NonZero,
+                        tolerance,
+                    ),
+                    Style::Stroke(style) => {
+                        Region::stroke(style, transform * cmd.transform, &outline, tolerance)
+                    }
+                }
+            }
+            RenderCommand::BoxShadow(cmd) => Region::new(
+                transform * cmd.transform * cmd.rect.to_rounded_rect(cmd.radius).to_path(tolerance),
+                Fill::NonZero,
+                tolerance,
+            ),
+            _ => return None,
+        };
+        Some(region)
+    }
+
+    fn visit(&mut self, commands: &[RenderCommand], transform: Affine, path: &mut Vec<usize>) {
+        let clip_depth = self.clips.len();
+        for (index, command) in commands.iter().enumerate() {
+            path.push(index);
+            match command {
+                RenderCommand::PushLayer(cmd) => {
+                    self.push_clip(transform * cmd.transform, &cmd.clip)
+                }
+                RenderCommand::PushClipLayer(cmd) => {
+                    self.push_clip(transform * cmd.transform, &cmd.clip)
+                }
+                RenderCommand::PopLayer => {
+                    // Ignore unbalanced pops (including those which would pop layers pushed outside a fragment)
+                    if self.clips.len() > clip_depth {
+                        self.clips.pop();
+                    }
+                }
+                RenderCommand::Fragment(cmd) => {
+                    if !self.is_clipped() {
+                        self.visit(cmd.fragment.commands(), transform * cmd.transform, path);
+                    }
+                }
+                _ => {
+                    let bounds = command.bounds().map(|b| transform.transform_rect_bbox(b));
+                    if !self.is_clipped()
+                        && bounds.is_some_and(|bounds| self.query.may_match(bounds))
+                        && self
+                            .command_region(command, transform)
+                            .is_some_and(|region| region.matches(self.query))
+                    {
+                        self.hits.push(CommandIndex {
+                            index: path[0],
+                            fragment_path: path[1..].to_vec(),
+                        });
+                    }
+                }
+            }
+            path.pop();
+        }
+        // Layers left open within a fragment don't apply to the rest of the scene
+        self.clips.truncate(clip_depth);
+    }
+}
+
+impl Scene {
+    /// Find the drawing commands which cover a device-space point, ordered from topmost (drawn last) to
+    /// bottommost.
+    ///
+    /// Hit testing uses the exact geometry of fills (respecting their fill rule), strokes, glyph outlines and
+    /// box shadows (excluding the blur), and excludes commands where the point is clipped out by an enclosing
+    /// layer. Brushes are ignored, so transparent commands are still hit.
+    pub fn hit_test(&self, point: Point) -> Vec<CommandIndex> {
+        self.query(Query::Point(point))
+    }
+
+    /// Find the drawing commands whose geometry intersects a device-space rect, ordered from topmost (drawn last)
+    /// to bottommost.
+    ///
+    /// Clips are handled conservatively: a command is included if both it and each enclosing clip intersect the
+    /// rect, even if their intersection doesn't.
+    pub fn hit_test_rect(&self, rect: Rect) -> Vec<CommandIndex> {
+        self.query(Query::Rect(rect.abs()))
+    }
+
+    fn query(&self, query: Query) -> Vec<CommandIndex> {
+        let mut tester = HitTester {
+            query,
+            tolerance: self.tolerance,
+            clips: Vec::new(),
+            hits: Vec::new(),
+        };
+        tester.visit(&self.commands, Affine::IDENTITY, &mut Vec::new());
+        tester.hits.reverse();
+        tester.hits
+    }
+}
@@ -17,6 +17,15 @@
 //!   - The [`ImageRenderer`] trait which provides an abstraction for rendering to a `Vec<u8>` RGBA8 buffer.
 //!   - The [`WindowRenderer`] trait which provides an abstraction for rendering to a surface/window
 //!
+//! ### Choosing a backend at runtime
+//!
+//! The core traits are generic and cannot be used as trait objects. The [`DynPaintScene`], [`DynWindowRenderer`]
+//! and [`DynImageRenderer`] traits are object-safe equivalents that are implemented for every backend, which allows
+//! a backend to be stored as (for example) a `Box<dyn DynWindowRenderer>`.
+//!
+//! [`FallbackWindowRenderer`] builds on this to try a list of backends in order, falling back to the next backend
+//! if one fails to resume.
+//!
 //! ### SVG
 //!
 //! The [anyrender_svg](https://docs.rs/anyrender_svg) crate allows SVGs to be rendered using AnyRender
@@ -28,7 +37,7 @@
 //!  - [anyrender_vello_cpu](https://docs.rs/anyrender_vello_cpu)
 
 use kurbo::{Affine, Rect, Shape, Stroke};
-use peniko::{BlendMode, Brush, Color, Fill, FontData, ImageBrushRef, StyleRef};
+use peniko::{BlendMode, Color, Fill, FontData, ImageBrushRef, StyleRef};
 use recording::RenderCommand;
 use std::sync::Arc;
 
@@ -38,8 +47,30 @@ pub mod types;
 pub use types::*;
 mod null_backend;
 pub use null_backend::*;
+mod dyn_backend;
+pub use dyn_backend::*;
+mod fallback_backend;
+pub use fallback_backend::*;
+mod error;
+pub use error::*;
+mod bounds;
+mod canvas;
+pub use canvas::*;
+mod culling;
+pub use culling::*;
+mod sanitizing;
+pub use sanitizing::*;
+mod tee;
+pub use tee::*;
+mod validating;
+pub use validating::*;
+mod damage;
+mod glyph;
+pub use glyph::glyph_run_outline;
+mod hit_test;
+pub use hit_test::CommandIndex;
 pub mod recording;
-pub use recording::Scene;
+pub use recording::{Scene, SceneFragment};
 
 /// Abstraction for rendering a scene to a window
 pub trait WindowRenderer {
@@ -51,6 +82,31 @@ pub trait WindowRenderer {
     fn is_active(&self) -> bool;
     fn set_size(&mut self, width: u32, height: u32);
     fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F);
+
+    /// Fallible version of [`resume`](WindowRenderer::resume).
+    ///
+    /// Backends which can fail to resume (for example because there is no compatible device) should override
+    /// this method to return an error rather than panicking. The default implementation calls `resume`.
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
+        self.resume(window, width, height);
+        Ok(())
+    }
+
+    /// Fallible version of [`render`](WindowRenderer::render).
+    ///
+    /// The default implementation calls `render`.
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        self.render(draw_fn);
+        Ok(())
+    }
 }
 
 /// Abstraction for rendering a scene to an image buffer
@@ -67,6 +123,124 @@ pub trait ImageRenderer {
         vec: &mut Vec<u8>,
     );
     fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F, buffer: &mut [u8]);
+
+    /// Fallible version of [`new`](ImageRenderer::new).
+    ///
+    /// Backends which can fail to initialise (for example because there is no compatible device) should override
+    /// this method to return an error rather than panicking. The default implementation calls `new`.
+    fn try_new(width: u32, height: u32) -> Result<Self, RenderError>
+    where
+        Self: Sized,
+    {
+        Ok(Self::new(width, height))
+    }
+
+    /// Fallible version of [`render_to_vec`](ImageRenderer::render_to_vec).
+    ///
+    /// The default implementation calls `render_to_vec`.
+    fn try_render_to_vec<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        vec: &mut Vec<u8>,
+    ) -> Result<(), RenderError> {
+        self.render_to_vec(draw_fn, vec);
+        Ok(())
+    }
+
+    /// Fallible version of [`render`](ImageRenderer::render).
+    ///
+    /// The default implementation calls `render`.
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        buffer: &mut [u8],
+    ) -> Result<(), RenderError> {
+        self.render(draw_fn, buffer);
+        Ok(())
+    }
+
+    /// Render only the pixels within `region` into the matching part of `buffer`, leaving the rest of `buffer`
+    /// untouched.
+    ///
+    /// `buffer` must contain the full RGBA8 frame, with each row taking up `stride` bytes. `region` is in device
+    /// pixels and is rounded outwards to whole pixels and clipped to the frame. `draw_fn` should draw the full
+    /// scene; backends use the region to avoid rasterising content outside of it.
+    ///
+    /// The default implementation renders the full frame into a temporary buffer and copies the region out of it.
+    fn render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) {
+        let mut frame = Vec::new();
+        self.render_to_vec(draw_fn, &mut frame);
+
+        let height = buffer.len().div_ceil(stride.max(1));
+        if height == 0 {
+            return;
+        }
+        let width = frame.len() / 4 / height;
+        let Some(region) = PixelRegion::new(region, width as u32, height as u32) else {
+            return;
+        };
+        region.copy_rows(&frame, width * 4, buffer, stride);
+    }
+
+    /// Fallible version of [`render_region`](ImageRenderer::render_region).
+    ///
+    /// The default implementation calls `render_region`.
+    fn try_render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), RenderError> {
+        self.render_region(draw_fn, region, buffer, stride);
+        Ok(())
+    }
+}
+
+/// A region of whole pixels within a frame (used by [`ImageRenderer::render_region`])
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PixelRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRegion {
+    /// Round `rect` outwards to whole pixels and clip it to a frame of the specified size.
+    /// Returns `None` if the resulting region is empty.
+    pub fn new(rect: Rect, frame_width: u32, frame_height: u32) -> Option<Self> {
+        let rect =
+            rect.expand()
+                .intersect(Rect::new(0.0, 0.0, frame_width as f64, frame_height as f64));
+        if rect.is_zero_area() {
+            return None;
+        }
+        Some(Self {
+            x: rect.x0 as u32,
+            y: rect.y0 as u32,
+            width: rect.width() as u32,
+            height: rect.height() as u32,
+        })
+    }
+
+    /// Copy the region's rows from `src` (a full RGBA8 frame) to `dst` (another full RGBA8 frame)
+    pub fn copy_rows(&self, src: &[u8], src_stride: usize, dst: &mut [u8], dst_stride: usize) {
+        let x = self.x as usize * 4;
+        let row_len = self.width as usize * 4;
+        for row in self.y as usize..(self.y + self.height) as usize {
+            let src_start = row * src_stride + x;
+            let dst_start = row * dst_stride + x;
+            dst[dst_start..dst_start + row_len]
+                .copy_from_slice(&src[src_start..src_start + row_len]);
+        }
+    }
 }
 
 /// Draw a scene to a buffer using an `ImageRenderer`
@@ -82,6 +256,19 @@ pub fn render_to_buffer<R: ImageRenderer, F: FnOnce(&mut R::ScenePainter<'_>)>(
     buf
 }
 
+/// Fallible version of [`render_to_buffer`]
+pub fn try_render_to_buffer<R: ImageRenderer, F: FnOnce(&mut R::ScenePainter<'_>)>(
+    draw_fn: F,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, RenderError> {
+    let mut buf = Vec::with_capacity((width * height * 4) as usize);
+    let mut renderer = R::try_new(width, height)?;
+    renderer.try_render_to_vec(draw_fn, &mut buf)?;
+
+    Ok(buf)
+}
+
 /// Abstraction for drawing a 2D scene
 pub trait PaintScene {
     /// Removes all content from the scene
@@ -156,37 +343,34 @@ pub trait PaintScene {
 
     /// Append a recorded Scene Fragment to the current scene
     fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
-        for cmd in scene.commands {
+        self.append_fragment(&SceneFragment::new(scene), scene_transform);
+    }
+
+    /// Append a shared, immutable Scene Fragment to the current scene.
+    ///
+    /// Unlike [`append_scene`](PaintScene::append_scene) this borrows the fragment, so a cached fragment
+    /// can be appended every frame without cloning its contents.
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        for cmd in fragment.commands() {
             match cmd {
-                RenderCommand::PushLayer(cmd) => self.push_layer(
-                    cmd.blend,
-                    cmd.alpha,
-                    scene_transform * cmd.transform,
-                    &cmd.clip,
-                ),
+                RenderCommand::PushLayer(cmd) => {
+                    self.push_layer(cmd.blend, cmd.alpha, transform * cmd.transform, &cmd.clip)
+                }
                 RenderCommand::PushClipLayer(cmd) => {
-                    self.push_clip_layer(scene_transform * cmd.transform, &cmd.clip)
+                    self.push_clip_layer(transform * cmd.transform, &cmd.clip)
                 }
                 RenderCommand::PopLayer => self.pop_layer(),
                 RenderCommand::Stroke(cmd) => self.stroke(
                     &cmd.style,
-                    scene_transform * cmd.transform,
-                    match cmd.brush {
-                        Brush::Solid(alpha_color) => Brush::Solid(alpha_color),
-                        Brush::Gradient(ref gradient) => Brush::Gradient(gradient),
-                        Brush::Image(ref image) => Brush::Image(image.as_ref()),
-                    },
+                    transform * cmd.transform,
+                    cmd.brush.as_ref(),
                     cmd.brush_transform,
                     &cmd.shape,
                 ),
                 RenderCommand::Fill(cmd) => self.fill(
                     cmd.fill,
-                    scene_transform * cmd.transform,
-                    match cmd.brush {
-                        Brush::Solid(alpha_color) => Brush::Solid(alpha_color),
-                        Brush::Gradient(ref gradient) => Brush::Gradient(gradient),
-                        Brush::Image(ref image) => Brush::Image(image.as_ref()),
-                    },
+                    transform * cmd.transform,
+                    cmd.brush.as_ref(),
                     cmd.brush_transform,
                     &cmd.shape,
                 ),
@@ -196,23 +380,22 @@ pub trait PaintScene {
                     cmd.hint,
                     &cmd.normalized_coords,
                     &cmd.style,
-                    match cmd.brush {
-                        Brush::Solid(alpha_color) => Brush::Solid(alpha_color),
-                        Brush::Gradient(ref gradient) => Brush::Gradient(gradient),
-                        Brush::Image(ref image) => Brush::Image(image.as_ref()),
-                    },
+                    cmd.brush.as_ref(),
                     cmd.brush_alpha,
-                    scene_transform * cmd.transform,
+                    transform * cmd.transform,
                     cmd.glyph_transform,
-                    cmd.glyphs.into_iter(),
+                    cmd.glyphs.iter().copied(),
                 ),
                 RenderCommand::BoxShadow(cmd) => self.draw_box_shadow(
-                    scene_transform * cmd.transform,
+                    transform * cmd.transform,
                     cmd.rect,
                     cmd.brush,
                     cmd.radius,
                     cmd.std_dev,
                 ),
+                RenderCommand::Fragment(cmd) => {
+                    self.append_fragment(&cmd.fragment, transform * cmd.transform)
+                }
             }
         }
     }
@@ -233,3 +416,99 @@ pub trait PaintScene {
         );
     }
 }
+
+impl<S: PaintScene + ?Sized> PaintScene for &mut S {
+    fn reset(&mut self) {
+        (**self).reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        (**self).push_layer(blend, alpha, transform, clip);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        (**self).push_clip_layer(transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        (**self).pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        (**self).stroke(style, transform, brush, brush_transform, shape);
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        (**self).fill(style, transform, brush, brush_transform, shape);
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        (**self).draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs,
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        (**self).draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+
+    fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
+        (**self).append_scene(scene, scene_transform);
+    }
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        (**self).append_fragment(fragment, transform);
+    }
+
+    fn draw_image(&mut self, image: ImageBrushRef, transform: Affine) {
+        (**self).draw_image(image, transform);
+    }
+}
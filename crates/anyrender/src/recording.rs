@@ -1,6 +1,10 @@
-use crate::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene};
-use kurbo::{Affine, BezPath, Rect, Shape, Stroke};
-use peniko::{BlendMode, Brush, Color, Fill, FontData, ImageBrush, ImageData, Style, StyleRef};
+use crate::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene, bounds};
+use kurbo::{
+    Affine, BezPath, Circle, CirclePathIter, Line, LinePathIter, PathEl, Point, Rect, RectPathIter,
+    RoundedRect, RoundedRectPathIter, Shape, Stroke,
+};
+use peniko::{BlendMode, Color, Fill, FontData, ImageBrush, ImageData, Style, StyleRef};
+use std::sync::Arc;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -28,11 +32,18 @@ pub enum RenderCommand<Font = FontData, Image = ImageData> {
     GlyphRun(GlyphRunCommand<Font, Image>),
     /// Draw a rounded rectangle blurred with a gaussian filter.
     BoxShadow(BoxShadowCommand),
+    /// Draws a shared scene fragment. Fragments can't be serialized directly: they should be
+    /// flattened into their commands first (see [`Scene::flatten`]).
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_fragment", skip_deserializing)
+    )]
+    Fragment(FragmentCommand),
 }
 
-impl RenderCommand {
+impl<Font, Image> RenderCommand<Font, Image> {
     /// Apply the specific transform to the command
-    fn apply_transform(mut self, transform: Affine) -> Self {
+    pub fn apply_transform(mut self, transform: Affine) -> Self {
         match &mut self {
             RenderCommand::PushLayer(cmd) => cmd.transform = transform * cmd.transform,
             RenderCommand::PushClipLayer(cmd) => cmd.transform = transform * cmd.transform,
@@ -41,12 +52,44 @@ impl RenderCommand {
             RenderCommand::Fill(cmd) => cmd.transform = transform * cmd.transform,
             RenderCommand::GlyphRun(cmd) => cmd.transform = transform * cmd.transform,
             RenderCommand::BoxShadow(cmd) => cmd.transform = transform * cmd.transform,
+            RenderCommand::Fragment(cmd) => cmd.transform = transform * cmd.transform,
         };
 
         self
     }
 }
 
+/// Report a clear error (rather than serde's generic "cannot be serialized") for fragments which weren't flattened
+#[cfg(feature = "serde")]
+fn serialize_fragment<S: serde::Serializer>(
+    _fragment: &FragmentCommand,
+    _serializer: S,
+) -> Result<S::Ok, S::Error> {
+    Err(serde::ser::Error::custom(
+        "scene fragments must be flattened with `Scene::flatten` before they are serialized",
+    ))
+}
+
+impl RenderCommand {
+    /// The device-space bounds of the command, ignoring any clipping by enclosing layers.
+    ///
+    /// For [`PushLayer`](RenderCommand::PushLayer) and [`PushClipLayer`](RenderCommand::PushClipLayer) this
+    /// is the bounds of the clip shape. Returns `None` for [`PopLayer`](RenderCommand::PopLayer) and for
+    /// fragments which don't draw anything.
+    pub fn bounds(&self) -> Option<Rect> {
+        match self {
+            RenderCommand::PushLayer(cmd) => Some(cmd.bounds()),
+            RenderCommand::PushClipLayer(cmd) => Some(cmd.bounds()),
+            RenderCommand::PopLayer => None,
+            RenderCommand::Stroke(cmd) => Some(cmd.bounds()),
+            RenderCommand::Fill(cmd) => Some(cmd.bounds()),
+            RenderCommand::GlyphRun(cmd) => Some(cmd.bounds()),
+            RenderCommand::BoxShadow(cmd) => Some(cmd.bounds()),
+            RenderCommand::Fragment(cmd) => cmd.bounds(),
+        }
+    }
+}
+
 /// Pushes a new layer clipped by the specified shape and composed with previous layers using the specified blend mode.
 /// Every drawing command after this call will be clipped by the shape until the layer is popped.
 /// However, the transforms are not saved or modified by the layer stack.
@@ -56,8 +99,7 @@ pub struct LayerCommand {
     pub blend: BlendMode,
     pub alpha: f32,
     pub transform: Affine,
-    #[cfg_attr(feature = "serde", serde(with = "svg_path"))]
-    pub clip: BezPath, // TODO: more shape options
+    pub clip: RecordedShape,
 }
 
 /// Pushes a new clip layer clipped by the specified shape.
@@ -67,44 +109,65 @@ pub struct LayerCommand {
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ClipCommand {
     pub transform: Affine,
-    #[cfg_attr(feature = "serde", serde(with = "svg_path"))]
-    pub clip: BezPath, // TODO: more shape options
+    pub clip: RecordedShape,
 }
 
 /// Strokes a shape using the specified style and brush.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Image: Serialize",
+        deserialize = "Image: Deserialize<'de>"
+    ))
+)]
 pub struct StrokeCommand<Image> {
     pub style: Stroke,
     pub transform: Affine,
-    pub brush: Brush<ImageBrush<Image>>, // TODO: review ownership to avoid cloning. Should brushes be a "resource"?
+    #[cfg_attr(feature = "serde", serde(with = "paint_brush"))]
+    pub brush: Paint<ImageBrush<Image>>, // TODO: review ownership to avoid cloning. Should brushes be a "resource"?
     pub brush_transform: Option<Affine>,
-    #[cfg_attr(feature = "serde", serde(with = "svg_path"))]
-    pub shape: BezPath, // TODO: more shape options
+    pub shape: RecordedShape,
 }
 
 /// Fills a shape using the specified style and brush.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Image: Serialize",
+        deserialize = "Image: Deserialize<'de>"
+    ))
+)]
 pub struct FillCommand<Image> {
     pub fill: Fill,
     pub transform: Affine,
-    pub brush: Brush<ImageBrush<Image>>, // TODO: review ownership to avoid cloning. Should brushes be a "resource"?
+    #[cfg_attr(feature = "serde", serde(with = "paint_brush"))]
+    pub brush: Paint<ImageBrush<Image>>, // TODO: review ownership to avoid cloning. Should brushes be a "resource"?
     pub brush_transform: Option<Affine>,
-    #[cfg_attr(feature = "serde", serde(with = "svg_path"))]
-    pub shape: BezPath, // TODO: more shape options
+    pub shape: RecordedShape,
 }
 
 /// Draws a run of glyphs
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Font: Serialize, Image: Serialize",
+        deserialize = "Font: Deserialize<'de>, Image: Deserialize<'de>"
+    ))
+)]
 pub struct GlyphRunCommand<Font = FontData, Image = ImageData> {
     pub font_data: Font,
     pub font_size: f32,
     pub hint: bool,
     pub normalized_coords: Vec<NormalizedCoord>,
     pub style: Style,
-    pub brush: Brush<ImageBrush<Image>>,
+    #[cfg_attr(feature = "serde", serde(with = "paint_brush"))]
+    pub brush: Paint<ImageBrush<Image>>,
     pub brush_alpha: f32,
     pub transform: Affine,
     pub glyph_transform: Option<Affine>,
@@ -122,6 +185,273 @@ pub struct BoxShadowCommand {
     pub std_dev: f64,
 }
 
+/// Draws a shared scene fragment
+#[derive(Clone, Debug, PartialEq)]
+pub struct FragmentCommand {
+    pub fragment: SceneFragment,
+    pub transform: Affine,
+}
+
+/// A shape stored in a recorded [`Scene`].
+///
+/// Primitive shapes are kept intact so that backends can still take their fast paths for them when the
+/// scene is replayed. All other shapes are flattened into a [`BezPath`].
+///
+/// When deserializing, a bare SVG path string (the representation used for every shape before primitive shapes
+/// were preserved) is also accepted as a [`RecordedShape::Path`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum RecordedShape {
+    Rect(Rect),
+    RoundedRect(RoundedRect),
+    Circle(Circle),
+    Line(Line),
+    Path(#[cfg_attr(feature = "serde", serde(with = "svg_path"))] BezPath),
+}
+
+impl RecordedShape {
+    /// Record a shape, flattening it into a path with the specified tolerance if it is not a primitive
+    pub fn from_shape(shape: &impl Shape, tolerance: f64) -> Self {
+        if let Some(rect) = shape.as_rect() {
+            RecordedShape::Rect(rect)
+        } else if let Some(rrect) = shape.as_rounded_rect() {
+            RecordedShape::RoundedRect(rrect)
+        } else if let Some(circle) = shape.as_circle() {
+            RecordedShape::Circle(circle)
+        } else if let Some(line) = shape.as_line() {
+            RecordedShape::Line(line)
+        } else {
+            RecordedShape::Path(shape.into_path(tolerance))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RecordedShape {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        /// The representation written by [`RecordedShape`]'s `Serialize` impl
+        #[derive(Deserialize)]
+        #[serde(remote = "RecordedShape")]
+        enum Tagged {
+            Rect(Rect),
+            RoundedRect(RoundedRect),
+            Circle(Circle),
+            Line(Line),
+            Path(#[serde(with = "svg_path")] BezPath),
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Tagged(#[serde(with = "Tagged")] RecordedShape),
+            Legacy(#[serde(with = "svg_path")] BezPath),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Tagged(shape) => shape,
+            Repr::Legacy(path) => RecordedShape::Path(path),
+        })
+    }
+}
+
+impl From<BezPath> for RecordedShape {
+    fn from(path: BezPath) -> Self {
+        RecordedShape::Path(path)
+    }
+}
+
+/// The [`Shape::PathElementsIter`] for [`RecordedShape`]
+#[allow(clippy::large_enum_variant)]
+pub enum RecordedShapePathIter<'a> {
+    Rect(RectPathIter),
+    RoundedRect(RoundedRectPathIter),
+    Circle(CirclePathIter),
+    Line(LinePathIter),
+    Path(std::iter::Copied<std::slice::Iter<'a, PathEl>>),
+}
+
+impl Iterator for RecordedShapePathIter<'_> {
+    type Item = PathEl;
+
+    fn next(&mut self) -> Option<PathEl> {
+        match self {
+            RecordedShapePathIter::Rect(iter) => iter.next(),
+            RecordedShapePathIter::RoundedRect(iter) => iter.next(),
+            RecordedShapePathIter::Circle(iter) => iter.next(),
+            RecordedShapePathIter::Line(iter) => iter.next(),
+            RecordedShapePathIter::Path(iter) => iter.next(),
+        }
+    }
+}
+
+impl Shape for RecordedShape {
+    type PathElementsIter<'iter>
+        = RecordedShapePathIter<'iter>
+    where
+        Self: 'iter;
+
+    fn path_elements(&self, tolerance: f64) -> Self::PathElementsIter<'_> {
+        match self {
+            RecordedShape::Rect(rect) => RecordedShapePathIter::Rect(rect.path_elements(tolerance)),
+            RecordedShape::RoundedRect(rrect) => {
+                RecordedShapePathIter::RoundedRect(rrect.path_elements(tolerance))
+            }
+            RecordedShape::Circle(circle) => {
+                RecordedShapePathIter::Circle(circle.path_elements(tolerance))
+            }
+            RecordedShape::Line(line) => RecordedShapePathIter::Line(line.path_elements(tolerance)),
+            RecordedShape::Path(path) => {
+                RecordedShapePathIter::Path(path.elements().iter().copied())
+            }
+        }
+    }
+
+    fn area(&self) -> f64 {
+        match self {
+            RecordedShape::Rect(rect) => rect.area(),
+            RecordedShape::RoundedRect(rrect) => rrect.area(),
+            RecordedShape::Circle(circle) => circle.area(),
+            RecordedShape::Line(line) => Shape::area(line),
+            RecordedShape::Path(path) => path.area(),
+        }
+    }
+
+    fn perimeter(&self, accuracy: f64) -> f64 {
+        match self {
+            RecordedShape::Rect(rect) => rect.perimeter(accuracy),
+            RecordedShape::RoundedRect(rrect) => rrect.perimeter(accuracy),
+            RecordedShape::Circle(circle) => circle.perimeter(accuracy),
+            RecordedShape::Line(line) => line.perimeter(accuracy),
+            RecordedShape::Path(path) => path.perimeter(accuracy),
+        }
+    }
+
+    fn winding(&self, pt: Point) -> i32 {
+        match self {
+            RecordedShape::Rect(rect) => rect.winding(pt),
+            RecordedShape::RoundedRect(rrect) => rrect.winding(pt),
+            RecordedShape::Circle(circle) => circle.winding(pt),
+            RecordedShape::Line(line) => line.winding(pt),
+            RecordedShape::Path(path) => path.winding(pt),
+        }
+    }
+
+    fn bounding_box(&self) -> Rect {
+        match self {
+            RecordedShape::Rect(rect) => rect.bounding_box(),
+            RecordedShape::RoundedRect(rrect) => rrect.bounding_box(),
+            RecordedShape::Circle(circle) => circle.bounding_box(),
+            RecordedShape::Line(line) => line.bounding_box(),
+            RecordedShape::Path(path) => path.bounding_box(),
+        }
+    }
+
+    fn as_line(&self) -> Option<Line> {
+        match self {
+            RecordedShape::Line(line) => Some(*line),
+            _ => None,
+        }
+    }
+
+    fn as_rect(&self) -> Option<Rect> {
+        match self {
+            RecordedShape::Rect(rect) => Some(*rect),
+            _ => None,
+        }
+    }
+
+    fn as_rounded_rect(&self) -> Option<RoundedRect> {
+        match self {
+            RecordedShape::RoundedRect(rrect) => Some(*rrect),
+            _ => None,
+        }
+    }
+
+    fn as_circle(&self) -> Option<Circle> {
+        match self {
+            RecordedShape::Circle(circle) => Some(*circle),
+            _ => None,
+        }
+    }
+
+    fn as_path_slice(&self) -> Option<&[PathEl]> {
+        match self {
+            RecordedShape::Path(path) => Some(path.elements()),
+            _ => None,
+        }
+    }
+}
+
+impl LayerCommand {
+    /// The device-space bounds of the clip shape
+    pub fn bounds(&self) -> Rect {
+        bounds::transformed_shape_bounds(&self.clip, self.transform)
+    }
+}
+
+impl ClipCommand {
+    /// The device-space bounds of the clip shape
+    pub fn bounds(&self) -> Rect {
+        bounds::transformed_shape_bounds(&self.clip, self.transform)
+    }
+}
+
+impl<Image> StrokeCommand<Image> {
+    /// The device-space bounds of the stroked shape (including the stroke width, joins and caps)
+    pub fn bounds(&self) -> Rect {
+        bounds::stroke_bounds(&self.style, self.transform, &self.shape)
+    }
+}
+
+impl<Image> FillCommand<Image> {
+    /// The device-space bounds of the filled shape
+    pub fn bounds(&self) -> Rect {
+        bounds::transformed_shape_bounds(&self.shape, self.transform)
+    }
+}
+
+impl<Image> GlyphRunCommand<FontData, Image> {
+    /// The device-space bounds of the glyph run.
+    ///
+    /// Each glyph is bounded horizontally by its side bearing and advance and vertically by the font's bounding
+    /// box. If the font cannot be parsed then each glyph is assumed to occupy a `font_size` square above the
+    /// baseline.
+    pub fn bounds(&self) -> Rect {
+        let stroke = match &self.style {
+            Style::Fill(_) => None,
+            Style::Stroke(stroke) => Some(stroke),
+        };
+        bounds::glyph_run_bounds(
+            &self.font_data,
+            self.font_size,
+            stroke,
+            self.transform,
+            self.glyph_transform,
+            self.glyphs.iter(),
+        )
+    }
+}
+
+impl BoxShadowCommand {
+    /// The device-space bounds of the blurred box (the gaussian blur is treated as extending 3 standard
+    /// deviations beyond the box)
+    pub fn bounds(&self) -> Rect {
+        bounds::box_shadow_bounds(self.transform, self.rect, self.std_dev)
+    }
+}
+
+impl FragmentCommand {
+    /// The device-space bounds of everything drawn by the fragment (see [`Scene::bounds`])
+    pub fn bounds(&self) -> Option<Rect> {
+        self.fragment
+            .bounds()
+            .map(|bounds| self.transform.transform_rect_bbox(bounds))
+    }
+}
+
 /// A recording of a Scene or Scene Fragment stored as plain data types that can be stored
 /// and passed around.
 #[derive(Clone, Debug, PartialEq)]
@@ -130,6 +460,54 @@ pub struct Scene {
     pub commands: Vec<RenderCommand>,
 }
 
+/// An immutable, reference-counted recording which can be cheaply cloned, shared across threads and
+/// appended to a [`PaintScene`] by reference using [`PaintScene::append_fragment`].
+///
+/// This is intended for content which is drawn repeatedly without changing (icons, static parts of a UI,
+/// etc). Appending a fragment to a [`Scene`] stores a reference to it rather than copying its commands.
+#[derive(Clone, Debug)]
+pub struct SceneFragment {
+    scene: Arc<Scene>,
+}
+
+impl SceneFragment {
+    pub fn new(scene: Scene) -> Self {
+        Self {
+            scene: Arc::new(scene),
+        }
+    }
+
+    pub fn commands(&self) -> &[RenderCommand] {
+        &self.scene.commands
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// Whether both fragments are references to the same recording
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.scene, &other.scene)
+    }
+
+    /// The device-space bounds of everything drawn by the fragment (see [`Scene::bounds`])
+    pub fn bounds(&self) -> Option<Rect> {
+        self.scene.bounds()
+    }
+}
+
+impl From<Scene> for SceneFragment {
+    fn from(scene: Scene) -> Self {
+        Self::new(scene)
+    }
+}
+
+impl PartialEq for SceneFragment {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.scene.commands == other.scene.commands
+    }
+}
+
 impl Default for Scene {
     fn default() -> Self {
         Self {
@@ -152,13 +530,76 @@ impl Scene {
         }
     }
 
-    fn convert_paintref(&mut self, paint_ref: PaintRef<'_>) -> Brush {
+    /// The device-space bounds of everything drawn by the scene, taking into account clipping by layers.
+    ///
+    /// Returns `None` if the scene doesn't draw anything (or everything it draws is clipped out).
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut clip_stack: Vec<Option<Rect>> = Vec::new();
+        let mut bounds: Option<Rect> = None;
+
+        for command in &self.commands {
+            let clip = clip_stack.last().copied().flatten();
+            match command {
+                RenderCommand::PushLayer(_) | RenderCommand::PushClipLayer(_) => {
+                    let layer_bounds = command.bounds().unwrap_or(Rect::ZERO);
+                    let layer_clip = match clip {
+                        Some(clip) => clip.intersect(layer_bounds),
+                        None => layer_bounds,
+                    };
+                    clip_stack.push(Some(layer_clip));
+                }
+                RenderCommand::PopLayer => {
+                    clip_stack.pop();
+                }
+                _ => {
+                    let Some(mut command_bounds) = command.bounds() else {
+                        continue;
+                    };
+                    if let Some(clip) = clip {
+                        if !command_bounds.overlaps(clip) {
+                            continue;
+                        }
+                        command_bounds = command_bounds.intersect(clip);
+                    }
+                    bounds = Some(match bounds {
+                        Some(bounds) => bounds.union(command_bounds),
+                        None => command_bounds,
+                    });
+                }
+            }
+        }
+
+        bounds
+    }
+
+    /// A copy of the scene where every appended fragment (including fragments nested in other fragments) is
+    /// replaced by its commands, with the fragment's transform applied to them.
+    pub fn flatten(&self) -> Scene {
+        let mut commands = Vec::with_capacity(self.commands.len());
+        flatten_commands(&self.commands, Affine::IDENTITY, &mut commands);
+        Scene {
+            tolerance: self.tolerance,
+            commands,
+        }
+    }
+
+    fn convert_paintref(&mut self, paint_ref: PaintRef<'_>) -> Paint {
         match paint_ref {
-            Paint::Solid(color) => Brush::Solid(color),
-            Paint::Gradient(gradient) => Brush::Gradient(gradient.clone()),
-            Paint::Image(image) => Brush::Image(image.to_owned()),
-            // TODO: handle this somehow
-            Paint::Custom(_) => Brush::Solid(Color::TRANSPARENT),
+            Paint::Solid(color) => Paint::Solid(color),
+            Paint::Gradient(gradient) => Paint::Gradient(gradient.clone()),
+            Paint::Image(image) => Paint::Image(image.to_owned()),
+            Paint::Custom(custom) => Paint::Custom(Arc::clone(custom)),
+        }
+    }
+}
+
+fn flatten_commands(commands: &[RenderCommand], transform: Affine, out: &mut Vec<RenderCommand>) {
+    for command in commands {
+        match command {
+            RenderCommand::Fragment(cmd) => {
+                flatten_commands(cmd.fragment.commands(), transform * cmd.transform, out)
+            }
+            command => out.push(command.clone().apply_transform(transform)),
         }
     }
 }
@@ -176,7 +617,7 @@ impl PaintScene for Scene {
         clip: &impl Shape,
     ) {
         let blend = blend.into();
-        let clip = clip.into_path(self.tolerance);
+        let clip = RecordedShape::from_shape(clip, self.tolerance);
         let layer = LayerCommand {
             blend,
             alpha,
@@ -187,7 +628,7 @@ impl PaintScene for Scene {
     }
 
     fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
-        let clip = clip.into_path(self.tolerance);
+        let clip = RecordedShape::from_shape(clip, self.tolerance);
         let layer = ClipCommand { transform, clip };
         self.commands.push(RenderCommand::PushClipLayer(layer));
     }
@@ -204,7 +645,7 @@ impl PaintScene for Scene {
         brush_transform: Option<Affine>,
         shape: &impl Shape,
     ) {
-        let shape = shape.into_path(self.tolerance);
+        let shape = RecordedShape::from_shape(shape, self.tolerance);
         let brush = self.convert_paintref(paint_ref.into());
         let stroke = StrokeCommand {
             style: style.clone(),
@@ -224,7 +665,7 @@ impl PaintScene for Scene {
         brush_transform: Option<Affine>,
         shape: &impl Shape,
     ) {
-        let shape = shape.into_path(self.tolerance);
+        let shape = RecordedShape::from_shape(shape, self.tolerance);
         let brush = self.convert_paintref(paint.into());
         let fill = FillCommand {
             fill: style,
@@ -284,12 +725,14 @@ impl PaintScene for Scene {
     }
 
     fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
-        self.commands.extend(
-            scene
-                .commands
-                .into_iter()
-                .map(|cmd| cmd.apply_transform(scene_transform)),
-        );
+        self.append_fragment(&SceneFragment::new(scene), scene_transform);
+    }
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        self.commands.push(RenderCommand::Fragment(FragmentCommand {
+            fragment: fragment.clone(),
+            transform,
+        }));
     }
 }
 
@@ -314,3 +757,39 @@ mod svg_path {
         BezPath::from_svg(&s).map_err(serde::de::Error::custom)
     }
 }
+
+/// Serde helper for serializing a [`Paint`] in the same format as a [`peniko::Brush`].
+///
+/// Custom paints are type-erased and so cannot be serialized.
+#[cfg(feature = "serde")]
+mod paint_brush {
+    use crate::Paint;
+    use peniko::{Brush, ImageBrush};
+    use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<I, S>(paint: &Paint<ImageBrush<I>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        I: Serialize,
+        S: Serializer,
+    {
+        let brush: Brush<&ImageBrush<I>, _> = match paint {
+            Paint::Solid(color) => Brush::Solid(*color),
+            Paint::Gradient(gradient) => Brush::Gradient(gradient),
+            Paint::Image(image) => Brush::Image(image),
+            Paint::Custom(_) => {
+                return Err(serde::ser::Error::custom(
+                    "custom paints cannot be serialized",
+                ));
+            }
+        };
+        brush.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, I, D>(deserializer: D) -> Result<Paint<ImageBrush<I>>, D::Error>
+    where
+        I: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Brush::<ImageBrush<I>>::deserialize(deserializer).map(Paint::from)
+    }
+}
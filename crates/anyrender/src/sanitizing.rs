@@ -0,0 +1,512 @@
+//! A [`PaintScene`] adapter which fixes up (or drops) invalid and dangerous drawing commands
+
+use crate::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene};
+use kurbo::{Affine, BezPath, PathEl, Rect, Shape, Stroke};
+use peniko::{BlendMode, Blob, Color, Fill, FontData, ImageBrush, ImageData, StyleRef};
+use std::collections::HashMap;
+
+/// The tolerance used when flattening shapes to check them
+const TOLERANCE: f64 = 0.1;
+
+/// Limits enforced by a [`SanitizingPaintScene`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SanitizeLimits {
+    /// The maximum number of nested layers. Deeper layers are dropped along with everything drawn into them.
+    pub max_layer_depth: usize,
+    /// The maximum standard deviation of box shadow blurs. Larger values are clamped.
+    pub max_std_dev: f64,
+    /// The maximum number of path elements in a shape. Longer paths are truncated, and strokes whose dash
+    /// pattern would generate more dashes than this are drawn undashed.
+    pub max_path_segments: usize,
+    /// The maximum width and height of images. Larger images are downscaled to fit.
+    pub max_image_size: u32,
+}
+
+impl Default for SanitizeLimits {
+    fn default() -> Self {
+        Self {
+            max_layer_depth: 64,
+            max_std_dev: 256.0,
+            max_path_segments: 100_000,
+            max_image_size: 8192,
+        }
+    }
+}
+
+/// Whether stroking a path of the specified length with `style`'s dash pattern generates at most `max_segments`
+/// dashes. Patterns with negative entries, or which don't advance, are never within the limit.
+fn dashes_within_limit(style: &Stroke, length: f64, max_segments: usize) -> bool {
+    let period: f64 = style.dash_pattern.iter().sum();
+    if period <= 0.0 || style.dash_pattern.iter().any(|&dash| dash < 0.0) {
+        return false;
+    }
+    length / period * style.dash_pattern.len() as f64 <= max_segments as f64
+}
+
+/// The result of checking a shape against the limits
+enum Geometry {
+    Valid,
+    /// The shape contains NaN or infinite coordinates
+    NonFinite,
+    /// The shape has too many path elements, and has been truncated
+    Truncated(BezPath),
+}
+
+/// Downscaled copies of oversized images, kept for as long as they are used in consecutive frames
+#[derive(Default)]
+struct DownscaledImages {
+    current: HashMap<(u64, u32, u32), ImageData>,
+    previous: HashMap<(u64, u32, u32), ImageData>,
+}
+
+impl DownscaledImages {
+    fn next_frame(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Sanitize a brush, returning `None` if it can't be drawn. Oversized images are replaced with a
+    /// downscaled copy, and the brush transform is adjusted to compensate.
+    fn sanitize<'a>(
+        &'a mut self,
+        brush: PaintRef<'a>,
+        brush_transform: Option<Affine>,
+        max_image_size: u32,
+    ) -> Option<(PaintRef<'a>, Option<Affine>)> {
+        if brush_transform.is_some_and(|transform| !transform.is_finite()) {
+            return None;
+        }
+        let Paint::Image(brush) = brush else {
+            return Some((brush, brush_transform));
+        };
+
+        let image = brush.image;
+        let required_len = image.format.size_in_bytes(image.width, image.height)?;
+        if image.width == 0 || image.height == 0 || image.data.len() < required_len {
+            return None;
+        }
+        let sampler = brush
+            .sampler
+            .with_alpha(sanitize_alpha(brush.sampler.alpha));
+        if image.width <= max_image_size && image.height <= max_image_size {
+            return Some((Paint::Image(ImageBrush { image, sampler }), brush_transform));
+        }
+
+        let key = (image.data.id(), image.width, image.height);
+        let scaled = match self.previous.remove(&key) {
+            Some(scaled) => scaled,
+            None => downscale(image, max_image_size),
+        };
+        let scale = Affine::scale_non_uniform(
+            image.width as f64 / scaled.width as f64,
+            image.height as f64 / scaled.height as f64,
+        );
+        let scaled = self.current.entry(key).or_insert(scaled);
+        Some((
+            Paint::Image(ImageBrush {
+                image: scaled,
+                sampler,
+            }),
+            Some(brush_transform.unwrap_or_default() * scale),
+        ))
+    }
+}
+
+/// Downscale an image so that neither side is larger than `max_size`, averaging the source pixels
+/// covered by each output pixel
+fn downscale(image: &ImageData, max_size: u32) -> ImageData {
+    let scale = max_size as f64 / image.width.max(image.height) as f64;
+    let width = ((image.width as f64 * scale) as u32).clamp(1, max_size);
+    let height = ((image.height as f64 * scale) as u32).clamp(1, max_size);
+    let (src_width, src_height) = (image.width as usize, image.height as usize);
+    let src = image.data.data();
+
+    // The range of source pixels covered by output pixel `i` of `len`
+    let span = |i: usize, len: usize, src_len: usize| {
+        let start = i * src_len / len;
+        let end = ((i + 1) * src_len / len).max(start + 1);
+        start..end
+    };
+
+    let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height as usize {
+        let rows = span(y, height as usize, src_height);
+        for x in 0..width as usize {
+            let columns = span(x, width as usize, src_width);
+            let mut sum = [0u64; 4];
+            for row in rows.clone() {
+                let start = (row * src_width + columns.start) * 4;
+                let end = (row * src_width + columns.end) * 4;
+                for pixel in src[start..end].chunks_exact(4) {
+                    for (sum, channel) in sum.iter_mut().zip(pixel) {
+                        *sum += *channel as u64;
+                    }
+                }
+            }
+            let count = (rows.len() * columns.len()) as u64;
+            data.extend(sum.map(|sum| (sum / count) as u8));
+        }
+    }
+
+    ImageData {
+        data: Blob::from(data),
+        format: image.format,
+        alpha_type: image.alpha_type,
+        width,
+        height,
+    }
+}
+
+/// Clamp an alpha value to `0.0..=1.0`, treating NaN as fully transparent
+fn sanitize_alpha(alpha: f32) -> f32 {
+    if alpha.is_nan() {
+        0.0
+    } else {
+        alpha.clamp(0.0, 1.0)
+    }
+}
+
+/// A [`PaintScene`] that wraps another [`PaintScene`] and fixes up invalid or dangerous commands so that they
+/// can't crash the inner scene or make it allocate huge amounts of memory. This is intended for rendering
+/// untrusted content.
+///
+/// - Commands with non-finite transforms or geometry, zero-sized or truncated images are dropped
+/// - Layers nested deeper than [`SanitizeLimits::max_layer_depth`] are dropped along with their content
+/// - Unbalanced `pop_layer` calls are dropped, and layers left open are popped when the scene is dropped
+///   (or when [`close_layers`](Self::close_layers) is called)
+/// - Box shadow blurs, path lengths and image sizes are clamped to the [`SanitizeLimits`]
+/// - Alpha values are clamped to `0.0..=1.0`
+///
+/// Use a [`ValidatingPaintScene`](crate::ValidatingPaintScene) to find out why commands are invalid.
+pub struct SanitizingPaintScene<S: PaintScene> {
+    /// Only `None` once the scene has been unwrapped with [`into_inner`](Self::into_inner)
+    inner: Option<S>,
+    limits: SanitizeLimits,
+    /// Whether each pushed layer was forwarded to the inner scene
+    layers: Vec<bool>,
+    images: DownscaledImages,
+    dropped_count: usize,
+}
+
+impl<S: PaintScene> SanitizingPaintScene<S> {
+    /// Wrap `inner`, using the default [`SanitizeLimits`]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner: Some(inner),
+            limits: SanitizeLimits::default(),
+            layers: Vec::new(),
+            images: DownscaledImages::default(),
+            dropped_count: 0,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: SanitizeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &SanitizeLimits {
+        &self.limits
+    }
+
+    /// The number of commands which have been dropped since the scene was created or reset
+    pub fn dropped_count(&self) -> usize {
+        self.dropped_count
+    }
+
+    /// Pop every layer which is still open
+    pub fn close_layers(&mut self) {
+        let inner = self.inner.as_mut().unwrap();
+        for forwarded in self.layers.drain(..).rev() {
+            if forwarded {
+                inner.pop_layer();
+            }
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        self.inner.as_ref().unwrap()
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        self.inner.as_mut().unwrap()
+    }
+
+    /// Close any open layers and unwrap the inner scene
+    pub fn into_inner(mut self) -> S {
+        self.close_layers();
+        self.inner.take().unwrap()
+    }
+
+    /// Whether commands are currently being forwarded (they aren't inside of dropped layers)
+    fn is_forwarding(&self) -> bool {
+        self.layers.last().is_none_or(|forwarded| *forwarded)
+    }
+
+    fn check_geometry(&self, shape: &impl Shape) -> Geometry {
+        if let Some(rect) = shape.as_rect() {
+            return match rect.is_finite() {
+                true => Geometry::Valid,
+                false => Geometry::NonFinite,
+            };
+        }
+
+        let mut count = 0;
+        for el in shape.path_elements(TOLERANCE) {
+            let is_finite = match el {
+                PathEl::MoveTo(p) | PathEl::LineTo(p) => p.is_finite(),
+                PathEl::QuadTo(p1, p2) => p1.is_finite() && p2.is_finite(),
+                PathEl::CurveTo(p1, p2, p3) => p1.is_finite() && p2.is_finite() && p3.is_finite(),
+                PathEl::ClosePath => true,
+            };
+            if !is_finite {
+                return Geometry::NonFinite;
+            }
+            count += 1;
+        }
+
+        if count > self.limits.max_path_segments {
+            let path = shape
+                .path_elements(TOLERANCE)
+                .take(self.limits.max_path_segments)
+                .collect();
+            Geometry::Truncated(path)
+        } else {
+            Geometry::Valid
+        }
+    }
+
+    /// Push a layer onto the layer stack, returning whether it should be forwarded to the inner scene
+    fn push(&mut self, transform: Affine, clip: &impl Shape) -> Option<Geometry> {
+        let geometry = self.check_geometry(clip);
+        let forwarded = self.is_forwarding()
+            && transform.is_finite()
+            && !matches!(geometry, Geometry::NonFinite)
+            && self.layers.len() < self.limits.max_layer_depth;
+        self.layers.push(forwarded);
+        if forwarded {
+            Some(geometry)
+        } else {
+            self.dropped_count += 1;
+            None
+        }
+    }
+
+    /// Whether a drawing command with the specified transform should be forwarded. Records the command as
+    /// dropped if not.
+    fn should_draw(&mut self, transform: Affine) -> bool {
+        let draw = self.is_forwarding() && transform.is_finite();
+        if !draw {
+            self.dropped_count += 1;
+        }
+        draw
+    }
+}
+
+impl<S: PaintScene> Drop for SanitizingPaintScene<S> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            self.close_layers();
+        }
+    }
+}
+
+impl<S: PaintScene> PaintScene for SanitizingPaintScene<S> {
+    fn reset(&mut self) {
+        self.layers.clear();
+        self.images.next_frame();
+        self.dropped_count = 0;
+        self.inner_mut().reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        let alpha = sanitize_alpha(alpha);
+        match self.push(transform, clip) {
+            Some(Geometry::Truncated(path)) => {
+                self.inner_mut().push_layer(blend, alpha, transform, &path)
+            }
+            Some(_) => self.inner_mut().push_layer(blend, alpha, transform, clip),
+            None => {}
+        }
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        match self.push(transform, clip) {
+            Some(Geometry::Truncated(path)) => self.inner_mut().push_clip_layer(transform, &path),
+            Some(_) => self.inner_mut().push_clip_layer(transform, clip),
+            None => {}
+        }
+    }
+
+    fn pop_layer(&mut self) {
+        match self.layers.pop() {
+            Some(true) => self.inner_mut().pop_layer(),
+            Some(false) => {}
+            None => self.dropped_count += 1,
+        }
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        if !self.should_draw(transform) {
+            return;
+        }
+        let dashes_valid =
+            style.dash_pattern.iter().all(|dash| dash.is_finite()) && style.dash_offset.is_finite();
+        if !style.width.is_finite() || !style.miter_limit.is_finite() || !dashes_valid {
+            self.dropped_count += 1;
+            return;
+        }
+        let geometry = self.check_geometry(shape);
+        // Dash patterns which don't advance (or advance very little along a long path) would make the backend
+        // generate an unbounded number of dashes, so they are removed
+        let max_segments = self.limits.max_path_segments;
+        let dashes_allowed = style.dash_pattern.is_empty()
+            || match &geometry {
+                Geometry::Valid => {
+                    dashes_within_limit(style, shape.perimeter(TOLERANCE), max_segments)
+                }
+                Geometry::Truncated(path) => {
+                    dashes_within_limit(style, path.perimeter(TOLERANCE), max_segments)
+                }
+                Geometry::NonFinite => true,
+            };
+        let mut undashed;
+        let style = if dashes_allowed {
+            style
+        } else {
+            undashed = style.clone();
+            undashed.dash_pattern.clear();
+            &undashed
+        };
+
+        let inner = self.inner.as_mut().unwrap();
+        let Some((brush, brush_transform)) =
+            self.images
+                .sanitize(brush.into(), brush_transform, self.limits.max_image_size)
+        else {
+            self.dropped_count += 1;
+            return;
+        };
+        match geometry {
+            Geometry::Valid => inner.stroke(style, transform, brush, brush_transform, shape),
+            Geometry::Truncated(path) => {
+                inner.stroke(style, transform, brush, brush_transform, &path)
+            }
+            Geometry::NonFinite => self.dropped_count += 1,
+        }
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        if !self.should_draw(transform) {
+            return;
+        }
+        let geometry = self.check_geometry(shape);
+        let inner = self.inner.as_mut().unwrap();
+        let Some((brush, brush_transform)) =
+            self.images
+                .sanitize(brush.into(), brush_transform, self.limits.max_image_size)
+        else {
+            self.dropped_count += 1;
+            return;
+        };
+        match geometry {
+            Geometry::Valid => inner.fill(style, transform, brush, brush_transform, shape),
+            Geometry::Truncated(path) => {
+                inner.fill(style, transform, brush, brush_transform, &path)
+            }
+            Geometry::NonFinite => self.dropped_count += 1,
+        }
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        if !self.should_draw(transform) {
+            return;
+        }
+        if !font_size.is_finite() || glyph_transform.is_some_and(|t| !t.is_finite()) {
+            self.dropped_count += 1;
+            return;
+        }
+        let inner = self.inner.as_mut().unwrap();
+        // Glyph runs don't have a brush transform, so images which need to be downscaled (and therefore need
+        // their brush transform adjusted) can't be drawn
+        let Some((brush, None)) =
+            self.images
+                .sanitize(brush.into(), None, self.limits.max_image_size)
+        else {
+            self.dropped_count += 1;
+            return;
+        };
+        inner.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            sanitize_alpha(brush_alpha),
+            transform,
+            glyph_transform,
+            glyphs.filter(|glyph| glyph.x.is_finite() && glyph.y.is_finite()),
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        if !self.should_draw(transform) {
+            return;
+        }
+        if !rect.is_finite() {
+            self.dropped_count += 1;
+            return;
+        }
+        let radius = if radius.is_finite() {
+            radius.max(0.0)
+        } else {
+            0.0
+        };
+        let std_dev = if std_dev.is_nan() {
+            0.0
+        } else {
+            std_dev.clamp(0.0, self.limits.max_std_dev)
+        };
+        self.inner_mut()
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+}
@@ -0,0 +1,329 @@
+//! [`PaintScene`] adapters which forward every command to multiple scenes
+
+use crate::{Glyph, NormalizedCoord, PaintRef, PaintScene, Scene, SceneFragment};
+use kurbo::{Affine, Rect, Shape, Stroke};
+use peniko::{BlendMode, Color, Fill, FontData, ImageBrushRef, StyleRef};
+
+/// A [`PaintScene`] that forwards every command to two scenes, such as a renderer's scene painter and a
+/// [`Scene`] recording.
+///
+/// Glyph iterators can only be consumed once, so glyph runs are collected before being forwarded. Scenes
+/// appended with [`append_scene`](PaintScene::append_scene) are converted into a [`SceneFragment`] so that
+/// they can be shared by both scenes without being cloned.
+///
+/// See [`FanOutPaintScene`] to forward commands to any number of scenes.
+pub struct TeePaintScene<A: PaintScene, B: PaintScene> {
+    first: A,
+    second: B,
+}
+
+impl<A: PaintScene, B: PaintScene> TeePaintScene<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    pub fn first_mut(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    pub fn second_mut(&mut self) -> &mut B {
+        &mut self.second
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: PaintScene, B: PaintScene> PaintScene for TeePaintScene<A, B> {
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        let blend = blend.into();
+        self.first.push_layer(blend, alpha, transform, clip);
+        self.second.push_layer(blend, alpha, transform, clip);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        self.first.push_clip_layer(transform, clip);
+        self.second.push_clip_layer(transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        self.first.pop_layer();
+        self.second.pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush = brush.into();
+        self.first
+            .stroke(style, transform, brush.clone(), brush_transform, shape);
+        self.second
+            .stroke(style, transform, brush, brush_transform, shape);
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush = brush.into();
+        self.first
+            .fill(style, transform, brush.clone(), brush_transform, shape);
+        self.second
+            .fill(style, transform, brush, brush_transform, shape);
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let style = style.into();
+        let brush = brush.into();
+        let glyphs: Vec<Glyph> = glyphs.collect();
+        self.first.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush.clone(),
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs.iter().copied(),
+        );
+        self.second.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs.into_iter(),
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.first
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+        self.second
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+
+    fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
+        self.append_fragment(&SceneFragment::new(scene), scene_transform);
+    }
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        self.first.append_fragment(fragment, transform);
+        self.second.append_fragment(fragment, transform);
+    }
+
+    fn draw_image(&mut self, image: ImageBrushRef, transform: Affine) {
+        self.first.draw_image(image, transform);
+        self.second.draw_image(image, transform);
+    }
+}
+
+/// A [`PaintScene`] that forwards every command to any number of scenes.
+///
+/// Scenes of different types can be combined by using `&mut dyn DynPaintScene` (or a nested
+/// [`TeePaintScene`]) as the scene type. As with [`TeePaintScene`], glyph runs are collected before being
+/// forwarded.
+pub struct FanOutPaintScene<S: PaintScene> {
+    scenes: Vec<S>,
+}
+
+impl<S: PaintScene> FanOutPaintScene<S> {
+    pub fn new(scenes: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            scenes: scenes.into_iter().collect(),
+        }
+    }
+
+    /// Add a scene, which will receive all subsequent commands
+    pub fn push(&mut self, scene: S) {
+        self.scenes.push(scene);
+    }
+
+    pub fn scenes(&self) -> &[S] {
+        &self.scenes
+    }
+
+    pub fn scenes_mut(&mut self) -> &mut [S] {
+        &mut self.scenes
+    }
+
+    pub fn into_inner(self) -> Vec<S> {
+        self.scenes
+    }
+}
+
+impl<S: PaintScene> PaintScene for FanOutPaintScene<S> {
+    fn reset(&mut self) {
+        for scene in &mut self.scenes {
+            scene.reset();
+        }
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        let blend = blend.into();
+        for scene in &mut self.scenes {
+            scene.push_layer(blend, alpha, transform, clip);
+        }
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        for scene in &mut self.scenes {
+            scene.push_clip_layer(transform, clip);
+        }
+    }
+
+    fn pop_layer(&mut self) {
+        for scene in &mut self.scenes {
+            scene.pop_layer();
+        }
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush = brush.into();
+        for scene in &mut self.scenes {
+            scene.stroke(style, transform, brush.clone(), brush_transform, shape);
+        }
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush = brush.into();
+        for scene in &mut self.scenes {
+            scene.fill(style, transform, brush.clone(), brush_transform, shape);
+        }
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let style = style.into();
+        let brush = brush.into();
+        let glyphs: Vec<Glyph> = glyphs.collect();
+        for scene in &mut self.scenes {
+            scene.draw_glyphs(
+                font,
+                font_size,
+                hint,
+                normalized_coords,
+                style,
+                brush.clone(),
+                brush_alpha,
+                transform,
+                glyph_transform,
+                glyphs.iter().copied(),
+            );
+        }
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        for scene in &mut self.scenes {
+            scene.draw_box_shadow(transform, rect, brush, radius, std_dev);
+        }
+    }
+
+    fn append_scene(&mut self, scene: Scene, scene_transform: Affine) {
+        self.append_fragment(&SceneFragment::new(scene), scene_transform);
+    }
+
+    fn append_fragment(&mut self, fragment: &SceneFragment, transform: Affine) {
+        for scene in &mut self.scenes {
+            scene.append_fragment(fragment, transform);
+        }
+    }
+
+    fn draw_image(&mut self, image: ImageBrushRef, transform: Affine) {
+        for scene in &mut self.scenes {
+            scene.draw_image(image, transform);
+        }
+    }
+}
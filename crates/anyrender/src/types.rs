@@ -39,7 +39,11 @@ pub enum Paint<I = ImageBrush, G = Gradient, C = Arc<dyn Any + Send + Sync>> {
     Custom(C),
 }
 
-pub type PaintRef<'a> = Paint<ImageBrushRef<'a>, &'a Gradient, &'a (dyn Any + Send + Sync)>;
+/// A borrowed [`Paint`].
+///
+/// Custom paints are borrowed as the [`Arc`] (rather than the `dyn Any` inside it) so that
+/// they can be retained by recordings such as [`Scene`](crate::Scene).
+pub type PaintRef<'a> = Paint<ImageBrushRef<'a>, &'a Gradient, &'a Arc<dyn Any + Send + Sync>>;
 
 impl Paint {
     pub fn as_ref(&self) -> PaintRef<'_> {
@@ -47,9 +51,30 @@ impl Paint {
             Paint::Solid(color) => Paint::Solid(*color),
             Paint::Gradient(gradient) => Paint::Gradient(gradient),
             Paint::Image(image) => Paint::Image(image.as_ref()),
+            Paint::Custom(custom) => Paint::Custom(custom),
+        }
+    }
+}
 
-            // Custom paints are translated into "invisible" where they are not supported
-            Paint::Custom(custom) => Paint::Custom(custom.as_ref()),
+/// Custom paints are compared by identity, as the type-erased values can't be compared directly
+impl<I: PartialEq, G: PartialEq> PartialEq for Paint<I, G, Arc<dyn Any + Send + Sync>> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Paint::Solid(a), Paint::Solid(b)) => a == b,
+            (Paint::Gradient(a), Paint::Gradient(b)) => a == b,
+            (Paint::Image(a), Paint::Image(b)) => a == b,
+            (Paint::Custom(a), Paint::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl<I, G> From<Brush<I, G>> for Paint<I, G> {
+    fn from(brush: Brush<I, G>) -> Self {
+        match brush {
+            Brush::Solid(color) => Paint::Solid(color),
+            Brush::Gradient(gradient) => Paint::Gradient(gradient),
+            Brush::Image(image) => Paint::Image(image),
         }
     }
 }
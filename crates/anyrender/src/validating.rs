@@ -0,0 +1,427 @@
+//! A [`PaintScene`] adapter which checks drawing commands for invalid input
+
+use crate::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene};
+use kurbo::{Affine, PathEl, Rect, Shape, Stroke};
+use peniko::{BlendMode, Color, Fill, FontData, StyleRef};
+use skrifa::raw::{FontRef, TableProvider};
+use std::fmt;
+
+/// The tolerance used when flattening shapes to check their coordinates
+const TOLERANCE: f64 = 0.1;
+
+/// How serious a [`ValidationIssue`] is
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The command is suspicious, but backends will generally render something reasonable
+    Warning,
+    /// The command is invalid, and backends may panic or produce garbage output
+    Error,
+}
+
+/// The kind of problem found by a [`ValidatingPaintScene`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationIssueKind {
+    /// `pop_layer` was called without a matching `push_layer` or `push_clip_layer`
+    UnbalancedPopLayer,
+    /// Layers were still open at the end of the frame
+    UnclosedLayers { count: usize },
+    /// A transform (including brush and glyph transforms) contains NaN or infinite values
+    NonFiniteTransform,
+    /// A shape, rect, glyph position or stroke contains NaN or infinite values
+    NonFiniteGeometry,
+    /// An alpha value is outside of `0.0..=1.0`
+    AlphaOutOfRange(f32),
+    /// The standard deviation of a box shadow is negative (or NaN)
+    NegativeStdDev(f64),
+    /// The corner radius of a box shadow is negative (or NaN)
+    NegativeRadius(f64),
+    /// An image brush has zero width or height
+    ZeroSizedImage { width: u32, height: u32 },
+    /// The font data couldn't be parsed
+    InvalidFont,
+    /// A glyph ID isn't present in the font
+    GlyphIdOutOfRange { glyph_id: u32, glyph_count: u32 },
+}
+
+impl ValidationIssueKind {
+    /// The default severity of this kind of issue
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::AlphaOutOfRange(_) | Self::NegativeRadius(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnbalancedPopLayer => write!(f, "pop_layer called with no layers pushed"),
+            Self::UnclosedLayers { count } => {
+                write!(f, "{count} layer(s) left open at the end of the frame")
+            }
+            Self::NonFiniteTransform => write!(f, "transform contains NaN or infinite values"),
+            Self::NonFiniteGeometry => write!(f, "geometry contains NaN or infinite values"),
+            Self::AlphaOutOfRange(alpha) => write!(f, "alpha {alpha} is outside of 0..=1"),
+            Self::NegativeStdDev(std_dev) => write!(f, "box shadow std_dev {std_dev} is negative"),
+            Self::NegativeRadius(radius) => write!(f, "box shadow radius {radius} is negative"),
+            Self::ZeroSizedImage { width, height } => {
+                write!(f, "image brush is zero-sized ({width}x{height})")
+            }
+            Self::InvalidFont => write!(f, "font data couldn't be parsed"),
+            Self::GlyphIdOutOfRange {
+                glyph_id,
+                glyph_count,
+            } => write!(
+                f,
+                "glyph ID {glyph_id} is out of range for a font with {glyph_count} glyphs"
+            ),
+        }
+    }
+}
+
+/// A problem with a command passed to a [`ValidatingPaintScene`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// The index of the offending command (counting every [`PaintScene`] call since the scene was created or reset)
+    pub command_index: usize,
+    /// The name of the offending [`PaintScene`] method
+    pub command: &'static str,
+    pub kind: ValidationIssueKind,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{severity}: command {} ({}): {}",
+            self.command_index, self.command, self.kind
+        )
+    }
+}
+
+/// A [`PaintScene`] that wraps another [`PaintScene`] and checks every command for invalid input before
+/// forwarding it.
+///
+/// Problems are collected as [`ValidationIssue`]s rather than being fixed up, so the inner scene sees exactly the
+/// same commands it would without validation. The one exception is unbalanced `pop_layer` calls, which are
+/// dropped as many backends panic on them. Call [`finish_frame`](Self::finish_frame) once a frame has been
+/// painted to check that every layer was popped.
+///
+/// Enabling [`with_panic_on_error`](Self::with_panic_on_error) makes errors panic at the offending call, which
+/// makes it easy to find the code responsible for them with a backtrace.
+pub struct ValidatingPaintScene<S: PaintScene> {
+    inner: S,
+    issues: Vec<ValidationIssue>,
+    command_index: usize,
+    layer_depth: usize,
+    panic_on_error: bool,
+}
+
+impl<S: PaintScene> ValidatingPaintScene<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            issues: Vec::new(),
+            command_index: 0,
+            layer_depth: 0,
+            panic_on_error: false,
+        }
+    }
+
+    /// Panic as soon as an issue with [`Severity::Error`] is found
+    pub fn with_panic_on_error(mut self, panic_on_error: bool) -> Self {
+        self.panic_on_error = panic_on_error;
+        self
+    }
+
+    /// The issues found since the scene was created or reset
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Take the issues found so far, leaving the list empty
+    pub fn take_issues(&mut self) -> Vec<ValidationIssue> {
+        std::mem::take(&mut self.issues)
+    }
+
+    /// Whether any issue with [`Severity::Error`] has been found
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+
+    /// The number of layers which are currently pushed
+    pub fn layer_depth(&self) -> usize {
+        self.layer_depth
+    }
+
+    /// Check the state of the scene at the end of a frame, reporting any layers which are still open
+    pub fn finish_frame(&mut self) -> &[ValidationIssue] {
+        if self.layer_depth > 0 {
+            self.report(
+                "finish_frame",
+                ValidationIssueKind::UnclosedLayers {
+                    count: self.layer_depth,
+                },
+            );
+        }
+        &self.issues
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn report(&mut self, command: &'static str, kind: ValidationIssueKind) {
+        let issue = ValidationIssue {
+            severity: kind.severity(),
+            command_index: self.command_index,
+            command,
+            kind,
+        };
+        if self.panic_on_error && issue.severity == Severity::Error {
+            panic!("invalid PaintScene command: {issue}");
+        }
+        self.issues.push(issue);
+    }
+
+    fn check_transform(&mut self, command: &'static str, transform: Affine) {
+        if !transform.is_finite() {
+            self.report(command, ValidationIssueKind::NonFiniteTransform);
+        }
+    }
+
+    fn check_shape(&mut self, command: &'static str, shape: &impl Shape) {
+        let is_finite = match shape.as_rect() {
+            Some(rect) => rect.is_finite(),
+            None => shape.path_elements(TOLERANCE).all(|el| match el {
+                PathEl::MoveTo(p) | PathEl::LineTo(p) => p.is_finite(),
+                PathEl::QuadTo(p1, p2) => p1.is_finite() && p2.is_finite(),
+                PathEl::CurveTo(p1, p2, p3) => p1.is_finite() && p2.is_finite() && p3.is_finite(),
+                PathEl::ClosePath => true,
+            }),
+        };
+        if !is_finite {
+            self.report(command, ValidationIssueKind::NonFiniteGeometry);
+        }
+    }
+
+    fn check_alpha(&mut self, command: &'static str, alpha: f32) {
+        if !(0.0..=1.0).contains(&alpha) {
+            self.report(command, ValidationIssueKind::AlphaOutOfRange(alpha));
+        }
+    }
+
+    fn check_brush(
+        &mut self,
+        command: &'static str,
+        brush: &PaintRef<'_>,
+        brush_transform: Option<Affine>,
+    ) {
+        if let Some(brush_transform) = brush_transform {
+            self.check_transform(command, brush_transform);
+        }
+        if let Paint::Image(image) = brush {
+            let (width, height) = (image.image.width, image.image.height);
+            if width == 0 || height == 0 {
+                self.report(
+                    command,
+                    ValidationIssueKind::ZeroSizedImage { width, height },
+                );
+            }
+            self.check_alpha(command, image.sampler.alpha);
+        }
+    }
+
+    fn check_glyphs(&mut self, font: &FontData, glyphs: &[Glyph]) {
+        const COMMAND: &str = "draw_glyphs";
+        let font = FontRef::from_index(font.data.data(), font.index).ok();
+        let Some(glyph_count) = font
+            .and_then(|font| font.maxp().ok())
+            .map(|maxp| maxp.num_glyphs() as u32)
+        else {
+            self.report(COMMAND, ValidationIssueKind::InvalidFont);
+            return;
+        };
+
+        if glyphs
+            .iter()
+            .any(|glyph| !glyph.x.is_finite() || !glyph.y.is_finite())
+        {
+            self.report(COMMAND, ValidationIssueKind::NonFiniteGeometry);
+        }
+        // Only report the first out of range glyph to avoid flooding the report with long runs
+        if let Some(glyph) = glyphs.iter().find(|glyph| glyph.id >= glyph_count) {
+            self.report(
+                COMMAND,
+                ValidationIssueKind::GlyphIdOutOfRange {
+                    glyph_id: glyph.id,
+                    glyph_count,
+                },
+            );
+        }
+    }
+}
+
+impl<S: PaintScene> PaintScene for ValidatingPaintScene<S> {
+    fn reset(&mut self) {
+        self.issues.clear();
+        self.command_index = 0;
+        self.layer_depth = 0;
+        self.inner.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.check_alpha("push_layer", alpha);
+        self.check_transform("push_layer", transform);
+        self.check_shape("push_layer", clip);
+        self.command_index += 1;
+        self.layer_depth += 1;
+        self.inner.push_layer(blend, alpha, transform, clip);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        self.check_transform("push_clip_layer", transform);
+        self.check_shape("push_clip_layer", clip);
+        self.command_index += 1;
+        self.layer_depth += 1;
+        self.inner.push_clip_layer(transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        if self.layer_depth == 0 {
+            self.report("pop_layer", ValidationIssueKind::UnbalancedPopLayer);
+        } else {
+            self.layer_depth -= 1;
+            self.inner.pop_layer();
+        }
+        self.command_index += 1;
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush = brush.into();
+        if !style.width.is_finite() || !style.miter_limit.is_finite() {
+            self.report("stroke", ValidationIssueKind::NonFiniteGeometry);
+        }
+        self.check_transform("stroke", transform);
+        self.check_brush("stroke", &brush, brush_transform);
+        self.check_shape("stroke", shape);
+        self.command_index += 1;
+        self.inner
+            .stroke(style, transform, brush, brush_transform, shape);
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let brush = brush.into();
+        self.check_transform("fill", transform);
+        self.check_brush("fill", &brush, brush_transform);
+        self.check_shape("fill", shape);
+        self.command_index += 1;
+        self.inner
+            .fill(style, transform, brush, brush_transform, shape);
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let brush = brush.into();
+        let glyphs: Vec<Glyph> = glyphs.collect();
+        if !font_size.is_finite() {
+            self.report("draw_glyphs", ValidationIssueKind::NonFiniteGeometry);
+        }
+        self.check_alpha("draw_glyphs", brush_alpha);
+        self.check_transform("draw_glyphs", transform);
+        if let Some(glyph_transform) = glyph_transform {
+            self.check_transform("draw_glyphs", glyph_transform);
+        }
+        self.check_brush("draw_glyphs", &brush, None);
+        self.check_glyphs(font, &glyphs);
+        self.command_index += 1;
+        self.inner.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            brush,
+            brush_alpha,
+            transform,
+            glyph_transform,
+            glyphs.into_iter(),
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.check_transform("draw_box_shadow", transform);
+        if !rect.is_finite() || radius.is_infinite() || std_dev.is_infinite() {
+            self.report("draw_box_shadow", ValidationIssueKind::NonFiniteGeometry);
+        }
+        if std_dev.is_nan() || std_dev < 0.0 {
+            self.report(
+                "draw_box_shadow",
+                ValidationIssueKind::NegativeStdDev(std_dev),
+            );
+        }
+        if radius.is_nan() || radius < 0.0 {
+            self.report(
+                "draw_box_shadow",
+                ValidationIssueKind::NegativeRadius(radius),
+            );
+        }
+        self.command_index += 1;
+        self.inner
+            .draw_box_shadow(transform, rect, brush, radius, std_dev);
+    }
+}
@@ -0,0 +1,198 @@
+use anyrender::recording::Scene;
+use anyrender::{Glyph, PaintScene};
+use kurbo::{Affine, Circle, Join, Rect, Stroke};
+use peniko::{Blob, Color, Fill, FontData};
+
+fn assert_rect_eq(actual: Rect, expected: Rect) {
+    let eps = 1e-6;
+    assert!(
+        (actual.x0 - expected.x0).abs() < eps
+            && (actual.y0 - expected.y0).abs() < eps
+            && (actual.x1 - expected.x1).abs() < eps
+            && (actual.y1 - expected.y1).abs() < eps,
+        "expected {expected:?}, got {actual:?}"
+    );
+}
+
+#[test]
+fn empty_scene_has_no_bounds() {
+    assert_eq!(Scene::new().bounds(), None);
+}
+
+#[test]
+fn fill_bounds_are_transformed() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((100.0, 50.0)) * Affine::scale(2.0),
+        Color::BLACK,
+        None,
+        &Rect::new(0.0, 0.0, 10.0, 20.0),
+    );
+
+    assert_rect_eq(
+        scene.commands[0].bounds().unwrap(),
+        Rect::new(100.0, 50.0, 120.0, 90.0),
+    );
+    assert_rect_eq(scene.bounds().unwrap(), Rect::new(100.0, 50.0, 120.0, 90.0));
+}
+
+#[test]
+fn stroke_bounds_include_stroke_width() {
+    let mut scene = Scene::new();
+    scene.stroke(
+        &Stroke::new(4.0).with_join(Join::Round),
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(10.0, 10.0, 20.0, 20.0),
+    );
+    assert_rect_eq(scene.bounds().unwrap(), Rect::new(8.0, 8.0, 22.0, 22.0));
+
+    // Miter joins can extend up to `miter_limit * width / 2` beyond the shape
+    scene.reset();
+    scene.stroke(
+        &Stroke::new(4.0)
+            .with_join(Join::Miter)
+            .with_miter_limit(3.0),
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(10.0, 10.0, 20.0, 20.0),
+    );
+    assert_rect_eq(scene.bounds().unwrap(), Rect::new(4.0, 4.0, 26.0, 26.0));
+}
+
+#[test]
+fn box_shadow_bounds_include_blur() {
+    let mut scene = Scene::new();
+    scene.draw_box_shadow(
+        Affine::IDENTITY,
+        Rect::new(0.0, 0.0, 10.0, 10.0),
+        Color::BLACK,
+        2.0,
+        1.0,
+    );
+    assert_rect_eq(scene.bounds().unwrap(), Rect::new(-3.0, -3.0, 13.0, 13.0));
+}
+
+#[test]
+fn clip_layers_clip_bounds() {
+    let mut scene = Scene::new();
+    scene.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 50.0, 50.0));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Circle::new((50.0, 50.0), 20.0),
+    );
+    scene.pop_layer();
+    assert_rect_eq(scene.bounds().unwrap(), Rect::new(30.0, 30.0, 50.0, 50.0));
+
+    // Content that is entirely clipped out doesn't contribute to the bounds
+    scene.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 10.0, 10.0));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(200.0, 200.0, 300.0, 300.0),
+    );
+    scene.pop_layer();
+    assert_rect_eq(scene.bounds().unwrap(), Rect::new(30.0, 30.0, 50.0, 50.0));
+}
+
+#[test]
+fn glyph_run_bounds_use_font_metrics() {
+    static ROBOTO_BYTES: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+    let font = FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0);
+
+    let mut scene = Scene::new();
+    let glyphs = [
+        Glyph {
+            id: 44,
+            x: 0.0,
+            y: 100.0,
+        },
+        Glyph {
+            id: 45,
+            x: 20.0,
+            y: 100.0,
+        },
+    ];
+    scene.draw_glyphs(
+        &font,
+        32.0,
+        false,
+        &[],
+        Fill::NonZero,
+        Color::BLACK,
+        1.0,
+        Affine::IDENTITY,
+        None,
+        glyphs.into_iter(),
+    );
+
+    let bounds = scene.bounds().unwrap();
+    // The glyphs sit on a baseline at y = 100, with ascenders above and descenders below it
+    assert!(bounds.y0 < 100.0 - 20.0 && bounds.y1 > 100.0);
+    // The run starts at the first glyph and extends past the origin of the last glyph
+    assert!(bounds.x0 <= 0.0 && bounds.x1 > 20.0);
+    // But not by more than a font size
+    assert!(bounds.x1 < 20.0 + 32.0 && bounds.y0 > 100.0 - 64.0);
+}
+
+#[test]
+fn glyph_run_bounds_include_ink_beyond_the_advance() {
+    use skrifa::raw::{FontRef, TableProvider, types::GlyphId};
+
+    static ROBOTO_BYTES: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+    let font = FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0);
+
+    // Find glyphs whose outline extends past their advance
+    let font_ref = FontRef::new(ROBOTO_BYTES).unwrap();
+    let (hmtx, loca, glyf) = (
+        font_ref.hmtx().unwrap(),
+        font_ref.loca(None).unwrap(),
+        font_ref.glyf().unwrap(),
+    );
+    let units_per_em = font_ref.head().unwrap().units_per_em() as f64;
+    let overhanging: Vec<(u32, f64, f64)> = (0..font_ref.maxp().unwrap().num_glyphs() as u32)
+        .filter_map(|id| {
+            let glyph = loca.get_glyf(GlyphId::new(id), &glyf).ok()??;
+            let advance = hmtx.advance(GlyphId::new(id))? as i32;
+            (glyph.x_max() as i32 > advance).then_some((
+                id,
+                glyph.x_min() as f64,
+                glyph.x_max() as f64,
+            ))
+        })
+        .collect();
+    assert!(!overhanging.is_empty());
+
+    let font_size = 64.0;
+    let scale = font_size / units_per_em;
+    for (id, x_min, x_max) in overhanging {
+        let mut scene = Scene::new();
+        scene.draw_glyphs(
+            &font,
+            font_size as f32,
+            false,
+            &[],
+            Fill::NonZero,
+            Color::BLACK,
+            1.0,
+            Affine::IDENTITY,
+            None,
+            std::iter::once(Glyph { id, x: 0.0, y: 0.0 }),
+        );
+        let bounds = scene.bounds().unwrap();
+        assert!(
+            bounds.x0 <= x_min * scale + 1e-6 && bounds.x1 >= x_max * scale - 1e-6,
+            "glyph {id}: bounds {bounds:?} don't include x = {}..{}",
+            x_min * scale,
+            x_max * scale
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use anyrender::recording::{RenderCommand, Scene};
+use anyrender::{CanvasPainter, Paint};
+use kurbo::{Affine, Rect};
+use peniko::{Color, Mix};
+
+const RECT: Rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+#[test]
+fn save_and_restore_transform_alpha_and_clips() {
+    let mut canvas = CanvasPainter::new(Scene::new());
+    canvas.translate((10.0, 0.0));
+    canvas.save();
+    canvas.scale(2.0);
+    canvas.set_global_alpha(0.5);
+    canvas.clip(&RECT);
+    canvas.push_layer(Mix::Multiply, 1.0, &RECT);
+    canvas.fill(&RECT, Color::BLACK);
+    canvas.restore();
+    canvas.fill(&RECT, Color::BLACK);
+    // Restoring without a saved state does nothing
+    canvas.restore();
+    assert_eq!(canvas.transform(), Affine::translate((10.0, 0.0)));
+    let scene = canvas.into_inner();
+
+    let commands = &scene.commands;
+    assert_eq!(commands.len(), 6);
+    let scaled = Affine::translate((10.0, 0.0)) * Affine::scale(2.0);
+    let RenderCommand::PushClipLayer(clip) = &commands[0] else {
+        panic!("expected a clip layer, found {:?}", commands[0]);
+    };
+    assert_eq!(clip.transform, scaled);
+    assert!(matches!(commands[1], RenderCommand::PushLayer(_)));
+    let RenderCommand::Fill(fill) = &commands[2] else {
+        panic!("expected a fill, found {:?}", commands[2]);
+    };
+    assert_eq!(fill.transform, scaled);
+    assert_eq!(fill.brush, Paint::Solid(Color::BLACK.multiply_alpha(0.5)));
+    assert!(matches!(commands[3], RenderCommand::PopLayer));
+    assert!(matches!(commands[4], RenderCommand::PopLayer));
+    let RenderCommand::Fill(fill) = &commands[5] else {
+        panic!("expected a fill, found {:?}", commands[5]);
+    };
+    assert_eq!(fill.transform, Affine::translate((10.0, 0.0)));
+    assert_eq!(fill.brush, Paint::Solid(Color::BLACK));
+}
+
+#[test]
+fn into_inner_pops_remaining_layers() {
+    let mut canvas = CanvasPainter::new(Scene::new());
+    canvas.clip(&RECT);
+    canvas.save();
+    canvas.clip(&RECT);
+    canvas.save();
+    let scene = canvas.into_inner();
+
+    let pops = scene
+        .commands
+        .iter()
+        .filter(|cmd| matches!(cmd, RenderCommand::PopLayer))
+        .count();
+    assert_eq!(pops, 2);
+}
@@ -0,0 +1,80 @@
+use anyrender::recording::{RenderCommand, Scene};
+use anyrender::{CullingPaintScene, PaintScene};
+use kurbo::{Affine, Rect, Stroke};
+use peniko::{Color, Fill};
+
+const VIEWPORT: Rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+
+fn fill_rect(scene: &mut impl PaintScene, rect: Rect) {
+    scene.fill(Fill::NonZero, Affine::IDENTITY, Color::BLACK, None, &rect);
+}
+
+#[test]
+fn culls_commands_outside_viewport() {
+    let mut scene = CullingPaintScene::new(Scene::new(), VIEWPORT);
+    fill_rect(&mut scene, Rect::new(10.0, 10.0, 20.0, 20.0));
+    fill_rect(&mut scene, Rect::new(200.0, 10.0, 220.0, 20.0));
+    // Translated into the viewport
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((-150.0, 0.0)),
+        Color::BLACK,
+        None,
+        &Rect::new(200.0, 10.0, 220.0, 20.0),
+    );
+    // The stroke width extends the shape into the viewport
+    scene.stroke(
+        &Stroke::new(10.0),
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(102.0, 10.0, 120.0, 20.0),
+    );
+    scene.draw_box_shadow(
+        Affine::IDENTITY,
+        Rect::new(-50.0, -50.0, -10.0, -10.0),
+        Color::BLACK,
+        0.0,
+        1.0,
+    );
+
+    assert_eq!(scene.culled_count(), 2);
+    assert_eq!(scene.inner().commands.len(), 3);
+}
+
+#[test]
+fn culls_commands_outside_clip() {
+    let mut scene = CullingPaintScene::new(Scene::new(), VIEWPORT);
+    scene.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 50.0, 50.0));
+    fill_rect(&mut scene, Rect::new(60.0, 60.0, 70.0, 70.0));
+    fill_rect(&mut scene, Rect::new(40.0, 40.0, 70.0, 70.0));
+    scene.pop_layer();
+    fill_rect(&mut scene, Rect::new(60.0, 60.0, 70.0, 70.0));
+
+    assert_eq!(scene.culled_count(), 1);
+    let commands = &scene.inner().commands;
+    assert_eq!(commands.len(), 4);
+    assert!(matches!(commands[0], RenderCommand::PushClipLayer(_)));
+    assert!(matches!(commands[2], RenderCommand::PopLayer));
+}
+
+#[test]
+fn culled_layers_stay_balanced() {
+    let mut scene = CullingPaintScene::new(Scene::new(), VIEWPORT);
+    scene.push_layer(
+        peniko::Mix::Normal,
+        0.5,
+        Affine::IDENTITY,
+        &Rect::new(500.0, 500.0, 600.0, 600.0),
+    );
+    // Nested layers and content inside a culled layer are dropped, even if they would otherwise be visible
+    scene.push_clip_layer(Affine::IDENTITY, &VIEWPORT);
+    fill_rect(&mut scene, Rect::new(10.0, 10.0, 20.0, 20.0));
+    scene.pop_layer();
+    scene.pop_layer();
+    fill_rect(&mut scene, Rect::new(10.0, 10.0, 20.0, 20.0));
+
+    let commands = &scene.inner().commands;
+    assert_eq!(commands.len(), 1);
+    assert!(matches!(commands[0], RenderCommand::Fill(_)));
+}
@@ -0,0 +1,103 @@
+use anyrender::PaintScene;
+use anyrender::recording::Scene;
+use kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+
+fn fill_rect(scene: &mut Scene, rect: Rect, color: Color) {
+    scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, &rect);
+}
+
+fn background(scene: &mut Scene) {
+    fill_rect(scene, Rect::new(0.0, 0.0, 100.0, 100.0), Color::WHITE);
+}
+
+#[test]
+fn identical_scenes_have_no_damage() {
+    let mut a = Scene::new();
+    background(&mut a);
+    fill_rect(&mut a, Rect::new(10.0, 10.0, 20.0, 20.0), Color::BLACK);
+
+    assert!(a.damage(&a.clone()).is_empty());
+}
+
+#[test]
+fn changed_command_damages_old_and_new_bounds() {
+    let mut a = Scene::new();
+    background(&mut a);
+    fill_rect(&mut a, Rect::new(10.0, 10.0, 20.0, 20.0), Color::BLACK);
+    fill_rect(&mut a, Rect::new(50.0, 50.0, 60.0, 60.0), Color::BLACK);
+
+    // Move the first rect and leave the second unchanged
+    let mut b = Scene::new();
+    background(&mut b);
+    fill_rect(&mut b, Rect::new(80.0, 10.0, 90.0, 20.0), Color::BLACK);
+    fill_rect(&mut b, Rect::new(50.0, 50.0, 60.0, 60.0), Color::BLACK);
+
+    let mut damage = b.damage(&a);
+    damage.sort_by(|a, b| a.x0.total_cmp(&b.x0));
+    assert_eq!(
+        damage,
+        vec![
+            Rect::new(10.0, 10.0, 20.0, 20.0),
+            Rect::new(80.0, 10.0, 90.0, 20.0)
+        ]
+    );
+}
+
+#[test]
+fn inserted_command_only_damages_its_bounds() {
+    let mut a = Scene::new();
+    background(&mut a);
+    fill_rect(&mut a, Rect::new(10.0, 10.0, 20.0, 20.0), Color::BLACK);
+    fill_rect(&mut a, Rect::new(50.0, 50.0, 60.0, 60.0), Color::BLACK);
+
+    let mut b = Scene::new();
+    background(&mut b);
+    fill_rect(&mut b, Rect::new(10.0, 10.0, 20.0, 20.0), Color::BLACK);
+    fill_rect(&mut b, Rect::new(30.0, 30.0, 32.0, 40.0), Color::BLACK);
+    fill_rect(&mut b, Rect::new(50.0, 50.0, 60.0, 60.0), Color::BLACK);
+
+    assert_eq!(b.damage(&a), vec![Rect::new(30.0, 30.0, 32.0, 40.0)]);
+}
+
+#[test]
+fn changed_layer_damages_layer_clip() {
+    let mut a = Scene::new();
+    background(&mut a);
+    a.push_layer(
+        peniko::Mix::Normal,
+        1.0,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, 50.0, 50.0),
+    );
+    fill_rect(&mut a, Rect::new(10.0, 10.0, 20.0, 20.0), Color::BLACK);
+    a.pop_layer();
+
+    let mut b = Scene::new();
+    background(&mut b);
+    b.push_layer(
+        peniko::Mix::Normal,
+        0.5,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, 50.0, 50.0),
+    );
+    fill_rect(&mut b, Rect::new(10.0, 10.0, 20.0, 20.0), Color::BLACK);
+    b.pop_layer();
+
+    assert_eq!(b.damage(&a), vec![Rect::new(0.0, 0.0, 50.0, 50.0)]);
+}
+
+#[test]
+fn damage_is_clipped() {
+    let mut a = Scene::new();
+    a.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 50.0, 50.0));
+    fill_rect(&mut a, Rect::new(40.0, 40.0, 80.0, 80.0), Color::BLACK);
+    a.pop_layer();
+
+    let mut b = Scene::new();
+    b.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 50.0, 50.0));
+    fill_rect(&mut b, Rect::new(40.0, 40.0, 80.0, 80.0), Color::WHITE);
+    b.pop_layer();
+
+    assert_eq!(b.damage(&a), vec![Rect::new(40.0, 40.0, 50.0, 50.0)]);
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyrender::{
+    FallbackWindowRenderer, NullScenePainter, PaintScene, RenderError, WindowHandle, WindowRenderer,
+};
+use kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+use raw_window_handle::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle as RawWindowHandle,
+};
+
+/// A window which doesn't have a native handle (the mock renderers never ask for one)
+struct NoWindow;
+
+impl HasWindowHandle for NoWindow {
+    fn window_handle(&self) -> Result<RawWindowHandle<'_>, HandleError> {
+        Err(HandleError::Unavailable)
+    }
+}
+
+impl HasDisplayHandle for NoWindow {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        Err(HandleError::Unavailable)
+    }
+}
+
+fn window() -> Arc<dyn WindowHandle> {
+    Arc::new(NoWindow)
+}
+
+/// A window renderer which either always or never resumes successfully, counting the frames it renders
+struct MockWindowRenderer {
+    fails: bool,
+    active: bool,
+    frames: Arc<AtomicUsize>,
+}
+
+impl WindowRenderer for MockWindowRenderer {
+    type ScenePainter<'a>
+        = NullScenePainter
+    where
+        Self: 'a;
+
+    fn resume(&mut self, window: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        self.try_resume(window, width, height).unwrap();
+    }
+
+    fn try_resume(
+        &mut self,
+        _window: Arc<dyn WindowHandle>,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), RenderError> {
+        if self.fails {
+            return Err(RenderError::resume("no compatible device"));
+        }
+        self.active = true;
+        Ok(())
+    }
+
+    fn suspend(&mut self) {
+        self.active = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn set_size(&mut self, _width: u32, _height: u32) {}
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        self.frames.fetch_add(1, Ordering::SeqCst);
+        draw_fn(&mut NullScenePainter);
+    }
+}
+
+/// Counts how many times a backend was constructed and how many frames it rendered
+#[derive(Clone, Default)]
+struct Counters {
+    constructed: Arc<AtomicUsize>,
+    frames: Arc<AtomicUsize>,
+}
+
+impl Counters {
+    fn constructed(&self) -> usize {
+        self.constructed.load(Ordering::SeqCst)
+    }
+
+    fn frames(&self) -> usize {
+        self.frames.load(Ordering::SeqCst)
+    }
+}
+
+fn with_mock(renderer: FallbackWindowRenderer, fails: bool) -> (FallbackWindowRenderer, Counters) {
+    let counters = Counters::default();
+    let constructor_counters = counters.clone();
+    let renderer = renderer.with_backend(move || {
+        constructor_counters
+            .constructed
+            .fetch_add(1, Ordering::SeqCst);
+        MockWindowRenderer {
+            fails,
+            active: false,
+            frames: constructor_counters.frames.clone(),
+        }
+    });
+    (renderer, counters)
+}
+
+fn render(renderer: &mut FallbackWindowRenderer) {
+    renderer.render(|painter| {
+        painter.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            Color::BLACK,
+            None,
+            &Rect::new(0.0, 0.0, 10.0, 10.0),
+        );
+    });
+}
+
+#[test]
+fn falls_back_to_the_next_backend() {
+    let (renderer, failing) = with_mock(FallbackWindowRenderer::new(), true);
+    let (mut renderer, working) = with_mock(renderer, false);
+
+    renderer.try_resume(window(), 100, 100).unwrap();
+    assert_eq!(renderer.active_backend(), Some(1));
+    assert_eq!(renderer.failures().len(), 1);
+    assert_eq!(renderer.failures()[0].0, 0);
+    assert!(matches!(renderer.failures()[0].1, RenderError::Resume(_)));
+
+    render(&mut renderer);
+    assert_eq!(failing.frames(), 0);
+    assert_eq!(working.frames(), 1);
+
+    // The failed backend isn't retried when resuming again
+    renderer.suspend();
+    renderer.try_resume(window(), 100, 100).unwrap();
+    assert_eq!(renderer.active_backend(), Some(1));
+    assert_eq!(failing.constructed(), 1);
+    assert_eq!(working.constructed(), 1);
+}
+
+#[test]
+fn fails_when_every_backend_fails() {
+    let (renderer, first) = with_mock(FallbackWindowRenderer::new(), true);
+    let (mut renderer, second) = with_mock(renderer, true);
+
+    assert!(matches!(
+        renderer.try_resume(window(), 100, 100),
+        Err(RenderError::Resume(_))
+    ));
+    assert!(!renderer.is_active());
+    assert_eq!(renderer.active_backend(), None);
+    let failed: Vec<usize> = renderer
+        .failures()
+        .iter()
+        .map(|(index, _)| *index)
+        .collect();
+    assert_eq!(failed, [0, 1]);
+
+    // Rendering without an active backend does nothing
+    render(&mut renderer);
+    renderer.try_render(|_| {}).unwrap();
+    assert_eq!(first.frames() + second.frames(), 0);
+}
+
+#[test]
+fn resuming_after_every_backend_failed_does_not_retry_them() {
+    let (renderer, first) = with_mock(FallbackWindowRenderer::new(), true);
+    let (mut renderer, second) = with_mock(renderer, true);
+
+    assert!(renderer.try_resume(window(), 100, 100).is_err());
+    assert!(renderer.try_resume(window(), 100, 100).is_err());
+    assert_eq!(first.constructed(), 1);
+    assert_eq!(second.constructed(), 1);
+    assert_eq!(renderer.failures().len(), 2);
+    assert!(!renderer.is_active());
+}
@@ -0,0 +1,135 @@
+use anyrender::recording::Scene;
+use anyrender::{CommandIndex, Glyph, PaintScene, SceneFragment};
+use kurbo::{Affine, BezPath, Circle, Point, Rect, Shape, Stroke};
+use peniko::{Blob, Color, Fill, FontData};
+
+static ROBOTO_BYTES: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+
+fn indices(hits: Vec<CommandIndex>) -> Vec<usize> {
+    hits.into_iter().map(|hit| hit.index).collect()
+}
+
+/// Two nested squares drawn as a single path
+fn nested_squares() -> BezPath {
+    let mut path = Rect::new(0.0, 0.0, 30.0, 30.0).to_path(0.1);
+    path.extend(Rect::new(10.0, 10.0, 20.0, 20.0).to_path(0.1));
+    path
+}
+
+#[test]
+fn hit_test_respects_fill_rules_and_strokes() {
+    let mut scene = Scene::new();
+    // 0: even-odd squares have a hole in the middle
+    scene.fill(
+        Fill::EvenOdd,
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &nested_squares(),
+    );
+    // 1: non-zero squares don't
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((100.0, 0.0)),
+        Color::BLACK,
+        None,
+        &nested_squares(),
+    );
+    // 2: a stroked circle, only hit near its outline
+    scene.stroke(
+        &Stroke::new(4.0),
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Circle::new((15.0, 15.0), 10.0),
+    );
+
+    assert_eq!(indices(scene.hit_test(Point::new(5.0, 15.0))), [2, 0]);
+    assert_eq!(
+        indices(scene.hit_test(Point::new(15.0, 15.0))),
+        Vec::<usize>::new()
+    );
+    assert_eq!(indices(scene.hit_test(Point::new(115.0, 15.0))), [1]);
+    assert_eq!(
+        indices(scene.hit_test(Point::new(50.0, 15.0))),
+        Vec::<usize>::new()
+    );
+}
+
+#[test]
+fn hit_test_respects_clips_and_fragments() {
+    let mut fragment = Scene::new();
+    fragment.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    let fragment = SceneFragment::new(fragment);
+
+    let mut scene = Scene::new();
+    scene.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 50.0, 50.0));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(0.0, 0.0, 100.0, 100.0),
+    );
+    scene.pop_layer();
+    scene.append_fragment(&fragment, Affine::translate((60.0, 60.0)));
+
+    assert_eq!(indices(scene.hit_test(Point::new(25.0, 25.0))), [1]);
+    // The fill is clipped out here, but the fragment is hit
+    assert_eq!(
+        scene.hit_test(Point::new(65.0, 65.0)),
+        [CommandIndex {
+            index: 3,
+            fragment_path: vec![0],
+        }]
+    );
+    assert_eq!(
+        indices(scene.hit_test_rect(Rect::new(45.0, 45.0, 62.0, 62.0))),
+        [3, 1]
+    );
+    assert_eq!(
+        indices(scene.hit_test_rect(Rect::new(52.0, 0.0, 58.0, 100.0))),
+        Vec::<usize>::new()
+    );
+}
+
+#[test]
+fn hit_test_uses_glyph_outlines() {
+    let font = FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0);
+    let mut scene = Scene::new();
+    // Glyph 44 is 'H' in Roboto
+    scene.draw_glyphs(
+        &font,
+        100.0,
+        false,
+        &[],
+        Fill::NonZero,
+        Color::BLACK,
+        1.0,
+        Affine::IDENTITY,
+        None,
+        [Glyph {
+            id: 44,
+            x: 0.0,
+            y: 100.0,
+        }]
+        .into_iter(),
+    );
+
+    let bounds = scene.bounds().unwrap();
+    let hits_in_row = (0..bounds.width() as usize)
+        .filter(|x| {
+            !scene
+                .hit_test(Point::new(bounds.x0 + *x as f64, 70.0))
+                .is_empty()
+        })
+        .count();
+    // The row crosses both stems of the 'H' but not the gap between them
+    assert!(hits_in_row > 0 && hits_in_row < bounds.width() as usize / 2);
+}
@@ -0,0 +1,196 @@
+use anyrender::recording::{RenderCommand, Scene};
+use anyrender::{CustomPaint, Glyph, NormalizedCoord, Paint, PaintRef, PaintScene, SceneFragment};
+use kurbo::{Affine, Rect, Shape, Stroke};
+use peniko::{BlendMode, Blob, Color, Fill, FontData, StyleRef};
+use std::{any::Any, sync::Arc};
+
+fn fill_rect(scene: &mut impl PaintScene, paint: &Paint, rect: Rect) {
+    scene.fill(Fill::NonZero, Affine::IDENTITY, paint, None, &rect);
+}
+
+#[test]
+fn custom_paints_survive_recording_and_replay() {
+    let custom: Arc<dyn Any + Send + Sync> = Arc::new(CustomPaint {
+        source_id: 1,
+        width: 10,
+        height: 10,
+        scale: 1.0,
+    });
+
+    let mut fragment = Scene::new();
+    fill_rect(
+        &mut fragment,
+        &Paint::Custom(custom.clone()),
+        Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+
+    let mut scene = Scene::new();
+    scene.append_scene(fragment, Affine::translate((5.0, 5.0)));
+    let scene = scene.flatten();
+
+    let RenderCommand::Fill(fill) = &scene.commands[0] else {
+        panic!("Expected Fill command, got {:?}", scene.commands[0]);
+    };
+    match &fill.brush {
+        Paint::Custom(recorded) => assert!(Arc::ptr_eq(recorded, &custom)),
+        other => panic!("Expected custom paint, got {other:?}"),
+    }
+}
+
+/// A painter which only records the custom paints that it is asked to draw with
+#[derive(Default)]
+struct CustomPaintCapture {
+    paints: Vec<Arc<dyn Any + Send + Sync>>,
+}
+
+impl CustomPaintCapture {
+    fn capture(&mut self, paint: PaintRef<'_>) {
+        if let Paint::Custom(custom) = paint {
+            self.paints.push(custom.clone());
+        }
+    }
+}
+
+impl PaintScene for CustomPaintCapture {
+    fn reset(&mut self) {
+        self.paints.clear();
+    }
+
+    fn push_layer(
+        &mut self,
+        _blend: impl Into<BlendMode>,
+        _alpha: f32,
+        _transform: Affine,
+        _clip: &impl Shape,
+    ) {
+    }
+
+    fn push_clip_layer(&mut self, _transform: Affine, _clip: &impl Shape) {}
+
+    fn pop_layer(&mut self) {}
+
+    fn stroke<'a>(
+        &mut self,
+        _style: &Stroke,
+        _transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        _brush_transform: Option<Affine>,
+        _shape: &impl Shape,
+    ) {
+        self.capture(brush.into());
+    }
+
+    fn fill<'a>(
+        &mut self,
+        _style: Fill,
+        _transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        _brush_transform: Option<Affine>,
+        _shape: &impl Shape,
+    ) {
+        self.capture(brush.into());
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        _font: &'a FontData,
+        _font_size: f32,
+        _hint: bool,
+        _normalized_coords: &'a [NormalizedCoord],
+        _style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        _brush_alpha: f32,
+        _transform: Affine,
+        _glyph_transform: Option<Affine>,
+        _glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        self.capture(brush.into());
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        _transform: Affine,
+        _rect: Rect,
+        _brush: Color,
+        _radius: f64,
+        _std_dev: f64,
+    ) {
+    }
+}
+
+#[test]
+fn custom_paints_are_forwarded_when_appending_to_a_backend() {
+    let custom: Arc<dyn Any + Send + Sync> = Arc::new(CustomPaint {
+        source_id: 1,
+        width: 10,
+        height: 10,
+        scale: 1.0,
+    });
+    let paint = Paint::Custom(custom.clone());
+    let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+    let mut fragment = Scene::new();
+    fill_rect(&mut fragment, &paint, rect);
+    fragment.stroke(&Stroke::new(1.0), Affine::IDENTITY, &paint, None, &rect);
+    // The font is never parsed, as the glyphs are only recorded
+    fragment.draw_glyphs(
+        &FontData::new(Blob::from(Vec::new()), 0),
+        16.0,
+        false,
+        &[],
+        Fill::NonZero,
+        &paint,
+        1.0,
+        Affine::IDENTITY,
+        None,
+        [Glyph {
+            id: 1,
+            x: 0.0,
+            y: 0.0,
+        }]
+        .into_iter(),
+    );
+
+    // Nest the fragment, so that the default `append_fragment` also has to forward nested fragments
+    let mut scene = Scene::new();
+    scene.append_scene(fragment, Affine::translate((5.0, 5.0)));
+
+    let mut painter = CustomPaintCapture::default();
+    painter.append_scene(scene, Affine::IDENTITY);
+    assert_eq!(painter.paints.len(), 3);
+    for forwarded in &painter.paints {
+        assert!(Arc::ptr_eq(forwarded, &custom));
+    }
+}
+
+#[test]
+fn fragments_are_appended_by_reference() {
+    let mut fragment = Scene::new();
+    fill_rect(
+        &mut fragment,
+        &Paint::Solid(Color::BLACK),
+        Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    let fragment = SceneFragment::new(fragment);
+
+    let mut scene = Scene::new();
+    scene.append_fragment(&fragment, Affine::translate((5.0, 5.0)));
+    scene.append_fragment(&fragment, Affine::translate((20.0, 5.0)));
+
+    assert_eq!(scene.commands.len(), 2);
+    let RenderCommand::Fragment(cmd) = &scene.commands[0] else {
+        panic!("Expected Fragment command, got {:?}", scene.commands[0]);
+    };
+    assert!(cmd.fragment.ptr_eq(&fragment));
+    assert_eq!(scene.bounds(), Some(Rect::new(5.0, 5.0, 30.0, 15.0)));
+
+    // Flattening the scene replaces fragments (including nested fragments) with their commands
+    let mut outer = Scene::new();
+    outer.append_scene(scene, Affine::IDENTITY);
+    let replayed = outer.flatten();
+    assert_eq!(replayed.commands.len(), 2);
+    let RenderCommand::Fill(fill) = &replayed.commands[1] else {
+        panic!("Expected Fill command, got {:?}", replayed.commands[1]);
+    };
+    assert_eq!(fill.transform, Affine::translate((20.0, 5.0)));
+}
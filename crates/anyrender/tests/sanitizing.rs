@@ -0,0 +1,153 @@
+use anyrender::recording::{RecordedShape, RenderCommand, Scene};
+use anyrender::{Paint, PaintScene, SanitizeLimits, SanitizingPaintScene};
+use kurbo::{Affine, BezPath, Line, Rect, Stroke};
+use peniko::{Blob, Color, Fill, ImageAlphaType, ImageBrush, ImageData, ImageFormat, Mix};
+
+const RECT: Rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+fn fill_rect(scene: &mut impl PaintScene, transform: Affine) {
+    scene.fill(Fill::NonZero, transform, Color::BLACK, None, &RECT);
+}
+
+#[test]
+fn drops_non_finite_commands_and_unbalanced_pops() {
+    let mut recording = Scene::new();
+    let mut scene = SanitizingPaintScene::new(&mut recording);
+    scene.pop_layer();
+    fill_rect(&mut scene, Affine::scale(f64::NAN));
+    fill_rect(&mut scene, Affine::IDENTITY);
+    // Everything inside a dropped layer is dropped too
+    scene.push_clip_layer(Affine::translate((f64::INFINITY, 0.0)), &RECT);
+    fill_rect(&mut scene, Affine::IDENTITY);
+    scene.pop_layer();
+    assert_eq!(scene.dropped_count(), 4);
+    drop(scene);
+
+    assert_eq!(recording.commands.len(), 1);
+}
+
+#[test]
+fn caps_layer_depth_and_closes_layers_on_drop() {
+    let mut recording = Scene::new();
+    let mut scene = SanitizingPaintScene::new(&mut recording).with_limits(SanitizeLimits {
+        max_layer_depth: 2,
+        ..Default::default()
+    });
+    for _ in 0..4 {
+        scene.push_layer(Mix::Normal, 2.0, Affine::IDENTITY, &RECT);
+    }
+    fill_rect(&mut scene, Affine::IDENTITY);
+    scene.pop_layer();
+    scene.pop_layer();
+    drop(scene);
+
+    let commands = &recording.commands;
+    assert_eq!(commands.len(), 4);
+    let RenderCommand::PushLayer(layer) = &commands[0] else {
+        panic!("expected a layer, found {:?}", commands[0]);
+    };
+    assert_eq!(layer.alpha, 1.0);
+    assert!(matches!(commands[1], RenderCommand::PushLayer(_)));
+    assert!(matches!(commands[2], RenderCommand::PopLayer));
+    assert!(matches!(commands[3], RenderCommand::PopLayer));
+}
+
+#[test]
+fn clamps_blurs_and_path_lengths() {
+    let mut scene = SanitizingPaintScene::new(Scene::new()).with_limits(SanitizeLimits {
+        max_std_dev: 10.0,
+        max_path_segments: 3,
+        ..Default::default()
+    });
+    scene.draw_box_shadow(Affine::IDENTITY, RECT, Color::BLACK, -1.0, 1e12);
+    let mut path = BezPath::new();
+    path.move_to((0.0, 0.0));
+    for i in 0..10 {
+        path.line_to((i as f64, 10.0));
+    }
+    scene.fill(Fill::NonZero, Affine::IDENTITY, Color::BLACK, None, &path);
+
+    let commands = &scene.inner().commands;
+    let RenderCommand::BoxShadow(shadow) = &commands[0] else {
+        panic!("expected a box shadow, found {:?}", commands[0]);
+    };
+    assert_eq!((shadow.radius, shadow.std_dev), (0.0, 10.0));
+    let RenderCommand::Fill(fill) = &commands[1] else {
+        panic!("expected a fill, found {:?}", commands[1]);
+    };
+    let RecordedShape::Path(path) = &fill.shape else {
+        panic!("expected a path, found {:?}", fill.shape);
+    };
+    assert_eq!(path.elements().len(), 3);
+}
+
+#[test]
+fn removes_dash_patterns_which_generate_too_many_dashes() {
+    let mut scene = SanitizingPaintScene::new(Scene::new());
+    let line = Line::new((0.0, 0.0), (1e6, 0.0));
+    for pattern in [[1e-9, 1e-9], [-1.0, 2.0], [0.0, 0.0], [100.0, 100.0]] {
+        let style = Stroke::new(1.0).with_dashes(0.0, pattern);
+        scene.stroke(&style, Affine::IDENTITY, Color::BLACK, None, &line);
+    }
+
+    let patterns: Vec<_> = scene
+        .inner()
+        .commands
+        .iter()
+        .map(|command| match command {
+            RenderCommand::Stroke(stroke) => stroke.style.dash_pattern.to_vec(),
+            _ => panic!("expected a stroke, found {command:?}"),
+        })
+        .collect();
+    // 1e6 / 200 * 2 = 10,000 dashes is within the default limit
+    assert_eq!(patterns, [vec![], vec![], vec![], vec![100.0, 100.0]]);
+}
+
+#[test]
+fn downscales_oversized_images_and_drops_invalid_ones() {
+    let image = |width: u32, height: u32, len: usize| {
+        ImageBrush::new(ImageData {
+            data: Blob::from(vec![255u8; len]),
+            format: ImageFormat::Rgba8,
+            alpha_type: ImageAlphaType::Alpha,
+            width,
+            height,
+        })
+    };
+    let mut scene = SanitizingPaintScene::new(Scene::new()).with_limits(SanitizeLimits {
+        max_image_size: 16,
+        ..Default::default()
+    });
+    let large = image(64, 32, 64 * 32 * 4);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        large.as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 64.0, 32.0),
+    );
+    let truncated = image(64, 32, 16);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        truncated.as_ref(),
+        None,
+        &RECT,
+    );
+    let empty = image(0, 32, 0);
+    scene.fill(Fill::NonZero, Affine::IDENTITY, empty.as_ref(), None, &RECT);
+    assert_eq!(scene.dropped_count(), 2);
+
+    let commands = &scene.inner().commands;
+    assert_eq!(commands.len(), 1);
+    let RenderCommand::Fill(fill) = &commands[0] else {
+        panic!("expected a fill, found {:?}", commands[0]);
+    };
+    let Paint::Image(brush) = &fill.brush else {
+        panic!("expected an image brush");
+    };
+    assert_eq!((brush.image.width, brush.image.height), (16, 8));
+    assert_eq!(brush.image.data.data(), &[255u8; 16 * 8 * 4][..]);
+    // The brush is scaled back up to cover the same area as the original image
+    assert_eq!(fill.brush_transform, Some(Affine::scale(4.0)));
+}
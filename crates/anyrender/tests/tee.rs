@@ -0,0 +1,61 @@
+use anyrender::recording::Scene;
+use anyrender::{DynPaintScene, FanOutPaintScene, Glyph, PaintScene, TeePaintScene};
+use kurbo::{Affine, Rect};
+use peniko::{Blob, Color, Fill, FontData, Mix};
+
+static ROBOTO_BYTES: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+
+fn draw(scene: &mut impl PaintScene, font: &FontData) {
+    let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+    scene.push_layer(Mix::Multiply, 0.5, Affine::IDENTITY, &rect);
+    scene.fill(Fill::NonZero, Affine::IDENTITY, Color::BLACK, None, &rect);
+    scene.draw_glyphs(
+        font,
+        16.0,
+        false,
+        &[],
+        Fill::NonZero,
+        Color::BLACK,
+        1.0,
+        Affine::IDENTITY,
+        None,
+        (44..48).map(|id| Glyph {
+            id,
+            x: id as f32,
+            y: 0.0,
+        }),
+    );
+    scene.draw_box_shadow(Affine::IDENTITY, rect, Color::BLACK, 2.0, 3.0);
+    scene.pop_layer();
+}
+
+#[test]
+fn tee_forwards_every_command_to_both_scenes() {
+    let font = FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0);
+    let mut expected = Scene::new();
+    draw(&mut expected, &font);
+
+    let mut tee = TeePaintScene::new(Scene::new(), Scene::new());
+    draw(&mut tee, &font);
+    let (first, second) = tee.into_inner();
+    assert_eq!(first, expected);
+    assert_eq!(second, expected);
+}
+
+#[test]
+fn fan_out_forwards_to_scenes_of_different_types() {
+    let font = FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0);
+    let mut expected = Scene::new();
+    draw(&mut expected, &font);
+
+    let mut recording = Scene::new();
+    let mut tee = TeePaintScene::new(Scene::new(), Scene::new());
+    {
+        let scenes: [&mut dyn DynPaintScene; 2] = [&mut recording, &mut tee];
+        let mut fan_out = FanOutPaintScene::new(scenes);
+        draw(&mut fan_out, &font);
+    }
+    assert_eq!(recording, expected);
+    assert_eq!(tee.first(), &expected);
+    assert_eq!(tee.second(), &expected);
+}
@@ -0,0 +1,130 @@
+use anyrender::recording::{RenderCommand, Scene};
+use anyrender::{Glyph, PaintScene, Severity, ValidatingPaintScene, ValidationIssueKind};
+use kurbo::{Affine, Rect};
+use peniko::{
+    Blob, Color, Fill, FontData, ImageAlphaType, ImageBrush, ImageData, ImageFormat, Mix,
+};
+
+static ROBOTO_BYTES: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+
+const RECT: Rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+fn issue_kinds(scene: &ValidatingPaintScene<Scene>) -> Vec<(usize, ValidationIssueKind)> {
+    scene
+        .issues()
+        .iter()
+        .map(|issue| (issue.command_index, issue.kind.clone()))
+        .collect()
+}
+
+#[test]
+fn valid_commands_are_forwarded_without_issues() {
+    let mut scene = ValidatingPaintScene::new(Scene::new());
+    scene.push_layer(Mix::Multiply, 0.5, Affine::IDENTITY, &RECT);
+    scene.fill(Fill::NonZero, Affine::IDENTITY, Color::BLACK, None, &RECT);
+    scene.draw_box_shadow(Affine::IDENTITY, RECT, Color::BLACK, 2.0, 3.0);
+    scene.pop_layer();
+
+    assert!(scene.finish_frame().is_empty());
+    assert_eq!(scene.inner().commands.len(), 4);
+}
+
+#[test]
+fn reports_invalid_commands_with_their_index() {
+    let mut scene = ValidatingPaintScene::new(Scene::new());
+    scene.pop_layer();
+    scene.fill(
+        Fill::NonZero,
+        Affine::scale(f64::NAN),
+        Color::BLACK,
+        None,
+        &Rect::new(0.0, 0.0, f64::INFINITY, 10.0),
+    );
+    scene.push_layer(Mix::Normal, 1.5, Affine::IDENTITY, &RECT);
+    scene.draw_box_shadow(Affine::IDENTITY, RECT, Color::BLACK, -1.0, -2.0);
+    let image = ImageBrush::new(ImageData {
+        data: Blob::from(Vec::new()),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 0,
+        height: 4,
+    });
+    scene.fill(Fill::NonZero, Affine::IDENTITY, image.as_ref(), None, &RECT);
+    scene.finish_frame();
+
+    assert_eq!(
+        issue_kinds(&scene),
+        vec![
+            (0, ValidationIssueKind::UnbalancedPopLayer),
+            (1, ValidationIssueKind::NonFiniteTransform),
+            (1, ValidationIssueKind::NonFiniteGeometry),
+            (2, ValidationIssueKind::AlphaOutOfRange(1.5)),
+            (3, ValidationIssueKind::NegativeStdDev(-2.0)),
+            (3, ValidationIssueKind::NegativeRadius(-1.0)),
+            (
+                4,
+                ValidationIssueKind::ZeroSizedImage {
+                    width: 0,
+                    height: 4
+                }
+            ),
+            (5, ValidationIssueKind::UnclosedLayers { count: 1 }),
+        ]
+    );
+    assert_eq!(scene.issues()[3].severity, Severity::Warning);
+    assert!(scene.has_errors());
+
+    // The unbalanced pop isn't forwarded, but everything else is
+    let commands = &scene.inner().commands;
+    assert_eq!(commands.len(), 4);
+    assert!(
+        !commands
+            .iter()
+            .any(|cmd| matches!(cmd, RenderCommand::PopLayer))
+    );
+
+    scene.reset();
+    assert!(scene.issues().is_empty());
+    assert_eq!(scene.layer_depth(), 0);
+}
+
+#[test]
+fn reports_out_of_range_glyph_ids() {
+    let font = FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0);
+    let mut scene = ValidatingPaintScene::new(Scene::new());
+    scene.draw_glyphs(
+        &font,
+        16.0,
+        false,
+        &[],
+        Fill::NonZero,
+        Color::BLACK,
+        1.0,
+        Affine::IDENTITY,
+        None,
+        [44, 100_000]
+            .into_iter()
+            .map(|id| Glyph { id, x: 0.0, y: 0.0 }),
+    );
+
+    let [issue] = scene.issues() else {
+        panic!("expected one issue, found {:?}", scene.issues());
+    };
+    assert!(matches!(
+        issue.kind,
+        ValidationIssueKind::GlyphIdOutOfRange {
+            glyph_id: 100_000,
+            ..
+        }
+    ));
+    assert_eq!(issue.command, "draw_glyphs");
+}
+
+#[test]
+#[should_panic(expected = "pop_layer called with no layers pushed")]
+fn panics_on_error_when_enabled() {
+    let mut scene = ValidatingPaintScene::new(Scene::new()).with_panic_on_error(true);
+    scene.push_clip_layer(Affine::IDENTITY, &RECT);
+    scene.pop_layer();
+    scene.pop_layer();
+}
@@ -0,0 +1,82 @@
+//! Opening single-scene and sequence archives behind one interface
+
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyrender::recording::Scene;
+use anyrender_serialize::{
+    ArchiveError, FrameMetadata, ResourceId, ResourceManifest, SceneArchive, SceneSequenceReader,
+    SerializableRenderCommand,
+};
+use peniko::Blob;
+use zip::ZipArchive;
+
+pub enum Archive {
+    Scene(SceneArchive),
+    Sequence(SceneSequenceReader<BufReader<File>>),
+}
+
+impl Archive {
+    /// Open an archive, detecting whether it contains a single scene or a sequence of frames
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let is_sequence = ZipArchive::new(BufReader::new(File::open(path)?))?
+            .index_for_name("frames.json")
+            .is_some();
+        let reader = BufReader::new(File::open(path)?);
+        Ok(if is_sequence {
+            Archive::Sequence(SceneSequenceReader::open(reader)?)
+        } else {
+            Archive::Scene(SceneArchive::deserialize(reader)?)
+        })
+    }
+
+    pub fn manifest(&self) -> &ResourceManifest {
+        match self {
+            Archive::Scene(archive) => &archive.manifest,
+            Archive::Sequence(reader) => reader.manifest(),
+        }
+    }
+
+    /// The metadata of each frame, or `None` for single-scene archives
+    pub fn frames(&self) -> Option<&[FrameMetadata]> {
+        match self {
+            Archive::Scene(_) => None,
+            Archive::Sequence(reader) => Some(reader.frames()),
+        }
+    }
+
+    /// The number of frames (1 for single-scene archives)
+    pub fn frame_count(&self) -> usize {
+        self.frames().map_or(1, <[_]>::len)
+    }
+
+    pub fn commands(
+        &mut self,
+        frame: usize,
+    ) -> Result<Vec<SerializableRenderCommand>, ArchiveError> {
+        match self {
+            Archive::Scene(_) if frame != 0 => Err(ArchiveError::FrameNotFound(frame)),
+            Archive::Scene(archive) => Ok(archive.commands.clone()),
+            Archive::Sequence(reader) => reader.read_commands(frame),
+        }
+    }
+
+    pub fn scene(&mut self, frame: usize) -> Result<Scene, ArchiveError> {
+        match self {
+            Archive::Scene(_) if frame != 0 => Err(ArchiveError::FrameNotFound(frame)),
+            Archive::Scene(archive) => archive.to_scene(),
+            Archive::Sequence(reader) => reader.read_frame(frame),
+        }
+    }
+
+    /// The original encoded bytes of an image, if they are stored in the archive. For sequence archives, this is
+    /// only known once a frame using the image has been read.
+    pub fn encoded_image(&self, id: ResourceId) -> Option<Blob<u8>> {
+        match self {
+            Archive::Scene(archive) => archive.encoded_images.get(id.0)?.clone(),
+            Archive::Sequence(reader) => reader.encoded_image(id).cloned(),
+        }
+    }
+}
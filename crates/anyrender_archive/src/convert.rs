@@ -0,0 +1,101 @@
+//! The `convert` subcommand
+
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyrender::Paint;
+use anyrender::recording::{RenderCommand, Scene};
+use anyrender_serialize::{
+    CommandEncoding, SceneArchive, SceneSequenceWriter, SerializableRenderCommand, SerializeConfig,
+};
+
+use crate::Encoding;
+use crate::archive::Archive;
+
+pub fn run(
+    input: &Path,
+    output: &Path,
+    subset_fonts: bool,
+    woff2_fonts: bool,
+    encoding: Encoding,
+) -> Result<(), Box<dyn Error>> {
+    let mut config = SerializeConfig::new()
+        .with_subset_fonts(subset_fonts)
+        .with_woff2_fonts(woff2_fonts)
+        .with_command_encoding(match encoding {
+            Encoding::Json => CommandEncoding::Json,
+            Encoding::Binary => CommandEncoding::Binary,
+        });
+
+    let mut archive = Archive::open(input)?;
+    let writer = BufWriter::new(File::create(output)?);
+    match archive.frames().map(<[_]>::to_vec) {
+        None => {
+            let commands = archive.commands(0)?;
+            let scene = archive.scene(0)?;
+            config = preserve_encoded_images(config, &archive, &commands, &scene);
+            SceneArchive::from_scene(&scene, &config)?.serialize(writer)?;
+        }
+        Some(frames) => {
+            // The reader keeps the images it has loaded, so later reads of each frame share these images' blobs
+            for index in 0..frames.len() {
+                let commands = archive.commands(index)?;
+                let scene = archive.scene(index)?;
+                config = preserve_encoded_images(config, &archive, &commands, &scene);
+            }
+            let mut sequence = SceneSequenceWriter::new(writer, &config);
+            for (index, frame) in frames.iter().enumerate() {
+                let scene = archive.scene(index)?;
+                sequence.append_frame(&scene, frame.timestamp, frame.width, frame.height)?;
+            }
+            sequence.finish()?;
+        }
+    }
+
+    let input_size = input.metadata()?.len();
+    let output_size = output.metadata()?.len();
+    println!(
+        "Wrote {} ({input_size} -> {output_size} bytes)",
+        output.display()
+    );
+    Ok(())
+}
+
+/// Keep the original encoded bytes of the images drawn by `scene` (read from `commands`), rather than re-encoding
+/// them as PNGs
+fn preserve_encoded_images(
+    mut config: SerializeConfig,
+    archive: &Archive,
+    commands: &[SerializableRenderCommand],
+    scene: &Scene,
+) -> SerializeConfig {
+    for (command, restored) in commands.iter().zip(&scene.commands) {
+        if let (Some(&id), Some(image)) = (brush_image(command), brush_image(restored))
+            && let Some(encoding) = archive
+                .manifest()
+                .images
+                .get(id.0)
+                .and_then(|meta| meta.original_encoding)
+            && let Some(data) = archive.encoded_image(id)
+        {
+            config = config.with_encoded_image(image, encoding, data);
+        }
+    }
+    config
+}
+
+/// The image drawn by a command's brush, if it has one
+fn brush_image<Font, Image>(command: &RenderCommand<Font, Image>) -> Option<&Image> {
+    let brush = match command {
+        RenderCommand::Stroke(stroke) => &stroke.brush,
+        RenderCommand::Fill(fill) => &fill.brush,
+        RenderCommand::GlyphRun(glyph_run) => &glyph_run.brush,
+        _ => return None,
+    };
+    match brush {
+        Paint::Image(image_brush) => Some(&image_brush.image),
+        _ => None,
+    }
+}
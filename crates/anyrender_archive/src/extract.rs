@@ -0,0 +1,35 @@
+//! The `extract` subcommand
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+/// Copy the `fonts/` and `images/` entries of an archive into `output`, as they are stored (fonts may be
+/// WOFF2-compressed, and images are PNGs unless their original JPEG or WebP bytes were preserved)
+pub fn run(path: &Path, output: &Path) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        if !file.is_file() {
+            continue;
+        }
+        // `enclosed_name` rejects paths which would escape the output directory
+        let Some(name) = file.enclosed_name() else {
+            continue;
+        };
+        if !(name.starts_with("fonts") || name.starts_with("images")) {
+            continue;
+        }
+
+        let destination = output.join(&name);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut file, &mut File::create(&destination)?)?;
+        println!("{}", destination.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! The `info` subcommand
+
+use std::error::Error;
+use std::path::Path;
+
+use anyrender_serialize::SerializableRenderCommand;
+
+use crate::archive::Archive;
+
+const COMMAND_NAMES: [&str; 8] = [
+    "push_layer",
+    "push_clip_layer",
+    "pop_layer",
+    "stroke",
+    "fill",
+    "glyph_run",
+    "box_shadow",
+    "fragment",
+];
+
+fn command_index(command: &SerializableRenderCommand) -> usize {
+    match command {
+        SerializableRenderCommand::PushLayer(_) => 0,
+        SerializableRenderCommand::PushClipLayer(_) => 1,
+        SerializableRenderCommand::PopLayer => 2,
+        SerializableRenderCommand::Stroke(_) => 3,
+        SerializableRenderCommand::Fill(_) => 4,
+        SerializableRenderCommand::GlyphRun(_) => 5,
+        SerializableRenderCommand::BoxShadow(_) => 6,
+        SerializableRenderCommand::Fragment(_) => 7,
+    }
+}
+
+/// The deepest nesting of layers in a list of commands
+fn max_layer_depth(commands: &[SerializableRenderCommand]) -> usize {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+    for command in commands {
+        match command {
+            SerializableRenderCommand::PushLayer(_)
+            | SerializableRenderCommand::PushClipLayer(_) => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            SerializableRenderCommand::PopLayer => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    max_depth
+}
+
+pub fn run(path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut archive = Archive::open(path)?;
+
+    let manifest = archive.manifest();
+    println!("Archive: {}", path.display());
+    println!("Format version: {}", manifest.version);
+    println!("Command encoding: {:?}", manifest.command_encoding);
+    println!("Tolerance: {}", manifest.tolerance);
+    if let Some(frames) = archive.frames() {
+        let duration = frames
+            .last()
+            .map(|frame| frame.timestamp)
+            .unwrap_or_default();
+        println!(
+            "Frames: {} over {:.3}s",
+            frames.len(),
+            duration.as_secs_f64()
+        );
+    }
+
+    let mut counts = [0usize; COMMAND_NAMES.len()];
+    let mut max_depth = 0;
+    for frame in 0..archive.frame_count() {
+        let commands = archive.commands(frame)?;
+        for command in &commands {
+            counts[command_index(command)] += 1;
+        }
+        max_depth = max_depth.max(max_layer_depth(&commands));
+    }
+    println!();
+    println!("Commands: {}", counts.iter().sum::<usize>());
+    for (name, count) in COMMAND_NAMES.iter().zip(counts) {
+        if count > 0 {
+            println!("  {name:<16} {count}");
+        }
+    }
+    println!("Max layer depth: {max_depth}");
+
+    let manifest = archive.manifest();
+    println!();
+    println!("Fonts: {}", manifest.fonts.len());
+    for font in &manifest.fonts {
+        println!("  {}  {} bytes", font.entry.path, font.entry.size);
+    }
+    println!("Images: {}", manifest.images.len());
+    for image in &manifest.images {
+        println!(
+            "  {}  {}x{} {:?}, {} bytes",
+            image.entry.path, image.width, image.height, image.format, image.entry.size
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+//! `anyrender-archive`: a command-line tool for triaging scene archives written by `anyrender_serialize`.
+//!
+//! Both single-scene archives ([`SceneArchive`](anyrender_serialize::SceneArchive)) and frame sequence archives
+//! ([`SceneSequenceWriter`](anyrender_serialize::SceneSequenceWriter)) are supported.
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+mod archive;
+mod convert;
+mod extract;
+mod info;
+mod render;
+mod validate;
+
+#[derive(Parser)]
+#[command(name = "anyrender-archive", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the manifest, command counts, resource sizes and maximum layer depth of an archive
+    Info { archive: PathBuf },
+    /// Rasterise a scene (or a frame of a sequence) to a PNG
+    Render {
+        archive: PathBuf,
+        /// Path of the PNG to write
+        #[arg(short, long)]
+        output: PathBuf,
+        /// The frame to render, for sequence archives
+        #[arg(long, default_value_t = 0)]
+        frame: usize,
+        /// Width of the output before scaling. Defaults to the frame's viewport width for sequence archives, or
+        /// the right edge of the scene's bounds otherwise.
+        #[arg(long)]
+        width: Option<u32>,
+        /// Height of the output before scaling. Defaults to the frame's viewport height for sequence archives,
+        /// or the bottom edge of the scene's bounds otherwise.
+        #[arg(long)]
+        height: Option<u32>,
+        /// Scale factor applied to the scene and the output size
+        #[arg(long, default_value_t = 1.0)]
+        scale: f64,
+        #[arg(long, value_enum, default_value_t = Backend::VelloCpu)]
+        backend: Backend,
+    },
+    /// Write the fonts and images stored in an archive to a directory
+    Extract {
+        archive: PathBuf,
+        /// Directory to write the `fonts/` and `images/` directories into
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+    /// Check resource hashes, resource references and layer balance
+    Validate { archive: PathBuf },
+    /// Re-encode an archive with different options
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+        /// Subset fonts to only include the glyphs used
+        #[arg(long)]
+        subset_fonts: bool,
+        /// WOFF2-compress fonts
+        #[arg(long)]
+        woff2_fonts: bool,
+        #[arg(long, value_enum, default_value_t = Encoding::Json)]
+        encoding: Encoding,
+    },
+}
+
+/// The renderer used by the `render` subcommand
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum Backend {
+    VelloCpu,
+    TinySkia,
+}
+
+/// How draw commands are encoded by the `convert` subcommand
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum Encoding {
+    Json,
+    Binary,
+}
+
+fn run(cli: Cli) -> Result<ExitCode, Box<dyn Error>> {
+    match cli.command {
+        Command::Info { archive } => info::run(&archive)?,
+        Command::Render {
+            archive,
+            output,
+            frame,
+            width,
+            height,
+            scale,
+            backend,
+        } => render::run(&archive, &output, frame, width, height, scale, backend)?,
+        Command::Extract { archive, output } => extract::run(&archive, &output)?,
+        Command::Validate { archive } => return validate::run(&archive),
+        Command::Convert {
+            input,
+            output,
+            subset_fonts,
+            woff2_fonts,
+            encoding,
+        } => convert::run(&input, &output, subset_fonts, woff2_fonts, encoding)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
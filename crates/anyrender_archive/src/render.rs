@@ -0,0 +1,53 @@
+//! The `render` subcommand
+
+use std::error::Error;
+use std::path::Path;
+
+use anyrender::{ImageRenderer, PaintScene, recording::Scene, render_to_buffer};
+use anyrender_tiny_skia::TinySkiaImageRenderer;
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+use image::RgbaImage;
+use kurbo::Affine;
+
+use crate::Backend;
+use crate::archive::Archive;
+
+fn render_with<R: ImageRenderer>(scene: Scene, scale: f64, width: u32, height: u32) -> Vec<u8> {
+    render_to_buffer::<R, _>(
+        |painter| painter.append_scene(scene, Affine::scale(scale)),
+        width,
+        height,
+    )
+}
+
+pub fn run(
+    path: &Path,
+    output: &Path,
+    frame: usize,
+    width: Option<u32>,
+    height: Option<u32>,
+    scale: f64,
+    backend: Backend,
+) -> Result<(), Box<dyn Error>> {
+    let mut archive = Archive::open(path)?;
+    let scene = archive.scene(frame)?;
+
+    let (default_width, default_height) = match archive.frames() {
+        Some(frames) => (frames[frame].width, frames[frame].height),
+        None => scene.bounds().map_or((1, 1), |bounds| {
+            (bounds.x1.ceil() as u32, bounds.y1.ceil() as u32)
+        }),
+    };
+    let width = ((width.unwrap_or(default_width) as f64 * scale).ceil() as u32).max(1);
+    let height = ((height.unwrap_or(default_height) as f64 * scale).ceil() as u32).max(1);
+
+    let pixels = match backend {
+        Backend::VelloCpu => render_with::<VelloCpuImageRenderer>(scene, scale, width, height),
+        Backend::TinySkia => render_with::<TinySkiaImageRenderer>(scene, scale, width, height),
+    };
+    let image = RgbaImage::from_raw(width, height, pixels)
+        .ok_or("renderer returned a buffer of the wrong size")?;
+    image.save(output)?;
+    println!("Wrote {width}x{height} image to {}", output.display());
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! The `validate` subcommand
+
+use std::error::Error;
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyrender::Paint;
+use anyrender_serialize::{ResourceManifest, SerializableBrush, SerializableRenderCommand};
+
+use crate::archive::Archive;
+
+/// Check the resource references and layer balance of a frame's commands
+fn check_commands(
+    manifest: &ResourceManifest,
+    commands: &[SerializableRenderCommand],
+    problems: &mut Vec<String>,
+) {
+    let check_brush = |index: usize, brush: &SerializableBrush, problems: &mut Vec<String>| {
+        if let Paint::Image(image) = brush
+            && image.image.0 >= manifest.images.len()
+        {
+            problems.push(format!(
+                "command {index} references missing image {}",
+                image.image.0
+            ));
+        }
+    };
+
+    let mut depth = 0usize;
+    for (index, command) in commands.iter().enumerate() {
+        match command {
+            SerializableRenderCommand::PushLayer(_)
+            | SerializableRenderCommand::PushClipLayer(_) => depth += 1,
+            SerializableRenderCommand::PopLayer => match depth.checked_sub(1) {
+                Some(new_depth) => depth = new_depth,
+                None => problems.push(format!("command {index} pops a layer which wasn't pushed")),
+            },
+            SerializableRenderCommand::Stroke(stroke) => {
+                check_brush(index, &stroke.brush, problems)
+            }
+            SerializableRenderCommand::Fill(fill) => check_brush(index, &fill.brush, problems),
+            SerializableRenderCommand::GlyphRun(glyph_run) => {
+                check_brush(index, &glyph_run.brush, problems);
+                let font = glyph_run.font_data.resource_id.0;
+                if font >= manifest.fonts.len() {
+                    problems.push(format!("command {index} references missing font {font}"));
+                }
+            }
+            SerializableRenderCommand::BoxShadow(_) | SerializableRenderCommand::Fragment(_) => {}
+        }
+    }
+    if depth > 0 {
+        problems.push(format!("{depth} layers are never popped"));
+    }
+}
+
+/// Validate an archive, printing any problems found.
+///
+/// Resource hashes are checked as resources are loaded: every resource for single-scene archives, and the
+/// resources referenced by any frame for sequence archives.
+pub fn run(path: &Path) -> Result<ExitCode, Box<dyn Error>> {
+    // Single-scene archives check every resource hash when they are opened
+    let mut archive = match Archive::open(path) {
+        Ok(archive) => archive,
+        Err(err) => {
+            println!("{}: {err}", path.display());
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    let frame_count = archive.frame_count();
+    let mut problem_count = 0;
+    for frame in 0..frame_count {
+        let mut problems = Vec::new();
+        match archive.commands(frame) {
+            Ok(commands) => {
+                check_commands(archive.manifest(), &commands, &mut problems);
+                // Load the frame's resources, which checks their hashes
+                if problems.is_empty()
+                    && let Err(err) = archive.scene(frame)
+                {
+                    problems.push(err.to_string());
+                }
+            }
+            Err(err) => problems.push(err.to_string()),
+        }
+
+        for problem in &problems {
+            match archive.frames() {
+                Some(_) => println!("frame {frame}: {problem}"),
+                None => println!("{problem}"),
+            }
+        }
+        problem_count += problems.len();
+    }
+
+    if problem_count == 0 {
+        println!("{}: OK ({frame_count} frames)", path.display());
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("{}: {problem_count} problems found", path.display());
+        Ok(ExitCode::FAILURE)
+    }
+}
@@ -0,0 +1,305 @@
+//! Integration tests which run the `anyrender-archive` binary.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use anyrender::recording::Scene;
+use anyrender::{Paint, PaintScene};
+use anyrender_serialize::{
+    ImageEncoding, ResourceId, SceneArchive, SceneSequenceReader, SceneSequenceWriter,
+    SerializableRenderCommand, SerializeConfig,
+};
+use image::ImageEncoder;
+use kurbo::{Affine, Rect};
+use peniko::{Blob, Color, Fill, ImageAlphaType, ImageBrush, ImageData, ImageFormat, Mix};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("anyrender_archive_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_archive(path: &Path) {
+    write_scene_archive(path, &layer_scene());
+}
+
+/// A red rectangle drawn in a layer
+fn layer_scene() -> Scene {
+    let mut scene = Scene::new();
+    scene.push_layer(
+        Mix::Normal,
+        1.0,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, 40.0, 30.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &Rect::new(0.0, 0.0, 40.0, 30.0),
+    );
+    scene.pop_layer();
+    scene
+}
+
+/// A 2x2 image drawn in a layer
+fn image_scene() -> Scene {
+    let image = ImageBrush::new(ImageData {
+        data: Blob::from([0u8, 0, 255, 255].repeat(4)),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 2,
+        height: 2,
+    });
+    let mut scene = layer_scene();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        image.as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 2.0, 2.0),
+    );
+    scene
+}
+
+fn write_scene_archive(path: &Path, scene: &Scene) {
+    write_modified_archive(path, scene, |_| {});
+}
+
+/// Write an archive of `scene`, after modifying it with `modify` (to create a broken archive)
+fn write_modified_archive(path: &Path, scene: &Scene, modify: impl FnOnce(&mut SceneArchive)) {
+    let mut archive = SceneArchive::from_scene(scene, &SerializeConfig::new()).unwrap();
+    modify(&mut archive);
+    archive.serialize(File::create(path).unwrap()).unwrap();
+}
+
+/// Run `validate`, asserting that it fails, and return its output
+fn validate_failure(path: &Path) -> String {
+    let output = run(&["validate", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_anyrender-archive"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn info_validate_render_and_convert() {
+    let dir = temp_dir("cli");
+    let archive = dir.join("scene.zip");
+    write_archive(&archive);
+    let archive = archive.to_str().unwrap();
+
+    let info = stdout(&run(&["info", archive]));
+    assert!(info.contains("Commands: 3"), "{info}");
+    assert!(info.contains("fill             1"), "{info}");
+    assert!(info.contains("Max layer depth: 1"), "{info}");
+
+    let validate = stdout(&run(&["validate", archive]));
+    assert!(validate.contains("OK"), "{validate}");
+
+    let png = dir.join("scene.png");
+    stdout(&run(&[
+        "render",
+        archive,
+        "--output",
+        png.to_str().unwrap(),
+        "--scale",
+        "2",
+    ]));
+    let image = image::open(&png).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (80, 60));
+    assert_eq!(image.get_pixel(40, 30).0, [255, 0, 0, 255]);
+
+    let converted = dir.join("converted.zip");
+    stdout(&run(&[
+        "convert",
+        archive,
+        converted.to_str().unwrap(),
+        "--encoding",
+        "binary",
+    ]));
+    let info = stdout(&run(&["info", converted.to_str().unwrap()]));
+    assert!(info.contains("Command encoding: Binary"), "{info}");
+}
+
+#[test]
+fn convert_keeps_original_jpeg_bytes() {
+    let dir = temp_dir("convert_jpeg");
+    let image = ImageData {
+        data: Blob::from([0u8, 0, 255, 255].repeat(16 * 16)),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 16,
+        height: 16,
+    };
+    let rgb: Vec<u8> = [0u8, 0, 255].repeat(16 * 16);
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new(&mut jpeg)
+        .write_image(&rgb, 16, 16, image::ExtendedColorType::Rgb8)
+        .unwrap();
+    let config =
+        SerializeConfig::new().with_encoded_image(&image, ImageEncoding::Jpeg, jpeg.clone());
+
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        ImageBrush::new(image).as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 16.0, 16.0),
+    );
+
+    let archive = dir.join("scene.zip");
+    SceneArchive::from_scene(&scene, &config)
+        .unwrap()
+        .serialize(File::create(&archive).unwrap())
+        .unwrap();
+    let sequence = dir.join("sequence.zip");
+    let mut writer = SceneSequenceWriter::new(File::create(&sequence).unwrap(), &config);
+    for frame in 0..2 {
+        writer
+            .append_frame(&scene, Duration::from_millis(frame * 16), 16, 16)
+            .unwrap();
+    }
+    writer.finish().unwrap();
+
+    let converted = dir.join("converted.zip");
+    stdout(&run(&[
+        "convert",
+        archive.to_str().unwrap(),
+        converted.to_str().unwrap(),
+    ]));
+    let converted = SceneArchive::deserialize(File::open(&converted).unwrap()).unwrap();
+    let meta = &converted.manifest.images[0];
+    assert_eq!(meta.original_encoding, Some(ImageEncoding::Jpeg));
+    assert!(meta.entry.path.ends_with(".jpg"), "{}", meta.entry.path);
+    assert_eq!(
+        converted.encoded_images[0].as_ref().map(|blob| blob.data()),
+        Some(jpeg.as_slice())
+    );
+
+    let converted = dir.join("converted_sequence.zip");
+    stdout(&run(&[
+        "convert",
+        sequence.to_str().unwrap(),
+        converted.to_str().unwrap(),
+    ]));
+    let mut reader = SceneSequenceReader::open(File::open(&converted).unwrap()).unwrap();
+    assert_eq!(reader.frame_count(), 2);
+    assert_eq!(
+        reader.manifest().images[0].original_encoding,
+        Some(ImageEncoding::Jpeg)
+    );
+    reader.read_frame(1).unwrap();
+    assert_eq!(
+        reader.encoded_image(ResourceId(0)).map(|blob| blob.data()),
+        Some(jpeg.as_slice())
+    );
+}
+
+#[test]
+fn validate_reports_corrupt_archives() {
+    let dir = temp_dir("corrupt");
+    let archive = dir.join("corrupt.zip");
+    fs::write(&archive, b"not a zip file").unwrap();
+
+    let output = run(&["validate", archive.to_str().unwrap()]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn validate_reports_hash_mismatches() {
+    let dir = temp_dir("hash_mismatch");
+    let archive = dir.join("archive.zip");
+    write_modified_archive(&archive, &image_scene(), |archive| {
+        archive.manifest.images[0].entry.sha256_hash = "0".repeat(64);
+    });
+
+    let output = validate_failure(&archive);
+    assert!(output.contains("Hash mismatch"), "{output}");
+}
+
+#[test]
+fn validate_reports_missing_resources() {
+    let dir = temp_dir("missing_resource");
+    let archive = dir.join("archive.zip");
+    write_modified_archive(&archive, &image_scene(), |archive| {
+        let Some(SerializableRenderCommand::Fill(fill)) = archive.commands.last_mut() else {
+            panic!("expected the image fill to be the last command");
+        };
+        let Paint::Image(image) = &mut fill.brush else {
+            panic!("expected an image brush");
+        };
+        image.image = ResourceId(5);
+    });
+
+    let output = validate_failure(&archive);
+    assert!(
+        output.contains("command 3 references missing image 5"),
+        "{output}"
+    );
+}
+
+#[test]
+fn validate_reports_unbalanced_layers() {
+    let dir = temp_dir("unbalanced");
+    let extra_pop = dir.join("extra_pop.zip");
+    write_modified_archive(&extra_pop, &layer_scene(), |archive| {
+        archive.commands.push(SerializableRenderCommand::PopLayer);
+    });
+    let output = validate_failure(&extra_pop);
+    assert!(
+        output.contains("command 3 pops a layer which wasn't pushed"),
+        "{output}"
+    );
+
+    let missing_pop = dir.join("missing_pop.zip");
+    write_modified_archive(&missing_pop, &layer_scene(), |archive| {
+        archive.commands.pop();
+    });
+    let output = validate_failure(&missing_pop);
+    assert!(output.contains("1 layers are never popped"), "{output}");
+}
+
+#[test]
+fn extract_writes_resources() {
+    let dir = temp_dir("extract");
+    let archive = dir.join("archive.zip");
+    write_scene_archive(&archive, &image_scene());
+    let manifest = SceneArchive::from_scene(&image_scene(), &SerializeConfig::new())
+        .unwrap()
+        .manifest;
+
+    let output = dir.join("resources");
+    stdout(&run(&[
+        "extract",
+        archive.to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+    ]));
+
+    let image_path = output.join(&manifest.images[0].entry.path);
+    let image = image::open(&image_path).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (2, 2));
+    assert_eq!(image.get_pixel(1, 1).0, [0, 0, 255, 255]);
+    // Only resources are extracted
+    assert!(!output.join("resources.json").exists());
+}
@@ -0,0 +1,181 @@
+//! Visual conformance tests for [`anyrender`] backends
+//!
+//! A library of canonical scenes (see [`scenes`]) is rendered through an [`ImageRenderer`], and each
+//! result is compared against a reference image checked in to the `references` directory. The reference
+//! images are rendered with `anyrender_vello_cpu`, and can be regenerated by running the tests with the
+//! `ANYRENDER_UPDATE_REFERENCES` environment variable set.
+//!
+//! Every scene is drawn over an opaque background, so backends are compared on the same backdrop whatever
+//! they clear the canvas to. Reference images store the renderer's (premultiplied) output as-is, so any
+//! translucent pixels (such as those cleared by compose modes) look darker than expected in an image viewer.
+//!
+//! The Skia backend is only checked when the `skia` feature is enabled (as it is in CI).
+
+use std::fmt;
+use std::path::PathBuf;
+
+use anyrender::{ImageRenderer, PaintScene, render_to_buffer};
+use anyrender_test_utils::{CompareOptions, ImageComparison, compare_images};
+use image::RgbaImage;
+use kurbo::Affine;
+
+mod scenes;
+
+pub use scenes::{ConformanceScene, scenes};
+
+/// If this environment variable is set then [`check_backend`] overwrites the reference images with the
+/// backend's output instead of comparing against them
+pub const UPDATE_REFERENCES_ENV_VAR: &str = "ANYRENDER_UPDATE_REFERENCES";
+
+/// How far a backend's output may differ from the reference images
+#[derive(Copy, Clone, Debug)]
+pub struct Tolerance {
+    /// Pixels are only counted as differing if one of their channels differs by more than this
+    pub max_channel_difference: u8,
+    /// The fraction of a scene's pixels which may differ
+    pub max_differing_ratio: f64,
+}
+
+impl Tolerance {
+    pub const EXACT: Self = Self {
+        max_channel_difference: 0,
+        max_differing_ratio: 0.0,
+    };
+
+    /// The options for comparing an image with `total_pixels` pixels
+    pub fn compare_options(&self, total_pixels: usize) -> CompareOptions {
+        let max_differing_pixels = (total_pixels as f64 * self.max_differing_ratio) as usize;
+        CompareOptions::new()
+            .with_channel_tolerance(self.max_channel_difference)
+            .with_max_differing_pixels(max_differing_pixels)
+    }
+}
+
+/// The outcome of checking a single scene
+#[derive(Clone, Debug)]
+pub enum SceneOutcome {
+    /// The output was compared against the reference image
+    Compared(ImageComparison),
+    /// The reference image was written
+    Updated,
+    /// The reference image is missing or couldn't be loaded
+    MissingReference(String),
+}
+
+/// The results of checking every scene against a backend
+#[derive(Clone, Debug)]
+pub struct ConformanceReport {
+    pub backend: String,
+    pub tolerance: Tolerance,
+    pub scenes: Vec<(String, SceneOutcome)>,
+}
+
+impl ConformanceReport {
+    /// Whether every scene matched its reference image within the tolerance
+    pub fn passed(&self) -> bool {
+        self.scenes.iter().all(|(_, outcome)| match outcome {
+            SceneOutcome::Compared(comparison) => comparison.matches,
+            SceneOutcome::Updated => true,
+            SceneOutcome::MissingReference(_) => false,
+        })
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "conformance results for {}:", self.backend)?;
+        for (name, outcome) in &self.scenes {
+            match outcome {
+                SceneOutcome::Compared(comparison) => {
+                    let total_pixels = comparison.width as usize * comparison.height as usize;
+                    writeln!(
+                        f,
+                        "  {name:<24} {:<4} {:>6} pixels differ ({:.2}%), max channel difference {}",
+                        if comparison.matches { "ok" } else { "FAIL" },
+                        comparison.differing_pixels,
+                        comparison.differing_pixels as f64 / total_pixels.max(1) as f64 * 100.0,
+                        comparison.max_channel_difference,
+                    )?
+                }
+                SceneOutcome::Updated => writeln!(f, "  {name:<24} updated reference")?,
+                SceneOutcome::MissingReference(err) => {
+                    writeln!(f, "  {name:<24} FAIL missing reference ({err})")?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The path of the reference image for the scene called `name`
+pub fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("references")
+        .join(format!("{name}.png"))
+}
+
+/// Render a conformance scene to an RGBA8 buffer
+pub fn render_scene<R: ImageRenderer>(scene: &ConformanceScene) -> Vec<u8> {
+    let recording = scene.scene();
+    render_to_buffer::<R, _>(
+        |painter| painter.append_scene(recording, Affine::IDENTITY),
+        scene.width,
+        scene.height,
+    )
+}
+
+/// Render every conformance scene with `R` and compare the output against the reference images.
+///
+/// If `update_references` is true then the reference images are overwritten instead.
+pub fn check_backend<R: ImageRenderer>(
+    backend: &str,
+    tolerance: Tolerance,
+    update_references: bool,
+) -> ConformanceReport {
+    let scenes = scenes()
+        .into_iter()
+        .map(|scene| {
+            let actual = render_scene::<R>(&scene);
+            let path = reference_path(&scene.name);
+
+            let outcome = if update_references {
+                RgbaImage::from_raw(scene.width, scene.height, actual)
+                    .expect("rendered image has the wrong size")
+                    .save(&path)
+                    .expect("failed to write reference image");
+                SceneOutcome::Updated
+            } else {
+                match image::open(&path) {
+                    Ok(expected) => {
+                        let expected = expected.to_rgba8();
+                        if expected.dimensions() == (scene.width, scene.height) {
+                            let options = tolerance
+                                .compare_options(scene.width as usize * scene.height as usize);
+                            SceneOutcome::Compared(compare_images(
+                                &actual,
+                                &expected,
+                                scene.width,
+                                scene.height,
+                                &options,
+                            ))
+                        } else {
+                            SceneOutcome::MissingReference(format!(
+                                "reference is {:?}, expected {:?}",
+                                expected.dimensions(),
+                                (scene.width, scene.height)
+                            ))
+                        }
+                    }
+                    Err(err) => SceneOutcome::MissingReference(err.to_string()),
+                }
+            };
+            (scene.name, outcome)
+        })
+        .collect();
+
+    ConformanceReport {
+        backend: backend.to_string(),
+        tolerance,
+        scenes,
+    }
+}
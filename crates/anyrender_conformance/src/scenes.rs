@@ -0,0 +1,479 @@
+//! The canonical scenes that every backend is checked against
+
+use std::f64::consts::{PI, TAU};
+
+use anyrender::{Glyph, PaintScene, Scene};
+use kurbo::{Affine, BezPath, Cap, Circle, Join, Point, Rect, RoundedRect, Shape, Stroke};
+use peniko::{
+    BlendMode, Blob, Color, Compose, Extend, Fill, FontData, Gradient, ImageAlphaType, ImageBrush,
+    ImageData, ImageFormat, ImageQuality, Mix,
+};
+use skrifa::{FontRef, MetadataProvider, instance::Size};
+
+const ROBOTO: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+
+/// The size of a cell in scenes which lay out variants of a feature in a grid
+const CELL: f64 = 32.0;
+
+/// A scene which is rendered by every backend and compared against a reference image
+pub struct ConformanceScene {
+    /// The name of the scene, which is also the file name of its reference image
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    draw: Box<dyn Fn(&mut Scene) + Send + Sync>,
+}
+
+impl ConformanceScene {
+    fn new(
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        draw: impl Fn(&mut Scene) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            width,
+            height,
+            draw: Box::new(draw),
+        }
+    }
+
+    /// Record the scene's drawing commands.
+    ///
+    /// Every scene is drawn over an opaque white background, so that backends which don't start from a
+    /// transparent canvas (Skia clears to white) are compared against the same backdrop.
+    pub fn scene(&self) -> Scene {
+        let mut scene = Scene::new();
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            Color::WHITE,
+            None,
+            &Rect::new(0.0, 0.0, self.width as f64, self.height as f64),
+        );
+        (self.draw)(&mut scene);
+        scene
+    }
+}
+
+/// All of the conformance scenes
+pub fn scenes() -> Vec<ConformanceScene> {
+    let mut scenes = vec![ConformanceScene::new("mix_modes", 128, 128, mix_modes)];
+    // Each compose mode is drawn in a separate scene, as some backends apply compose modes which clear the
+    // destination outside of the source (such as `Copy`) to the whole scene rather than only within the
+    // layer's clip
+    scenes.extend(COMPOSE_MODES.into_iter().map(|compose| {
+        let name = format!("compose_{compose:?}").to_lowercase();
+        ConformanceScene::new(name, 32, 32, move |scene| compose_mode(scene, compose))
+    }));
+    scenes.extend([
+        ConformanceScene::new("nested_clip_layers", 128, 128, nested_clip_layers),
+        ConformanceScene::new("fill_rules", 128, 64, fill_rules),
+        ConformanceScene::new("dashed_strokes", 128, 128, dashed_strokes),
+        ConformanceScene::new("gradient_extends", 96, 96, gradient_extends),
+        ConformanceScene::new("image_samplers", 96, 128, image_samplers),
+        ConformanceScene::new("glyph_runs", 160, 128, glyph_runs),
+        ConformanceScene::new("box_shadows", 128, 128, box_shadows),
+    ]);
+    scenes
+}
+
+const MIX_MODES: [Mix; 16] = [
+    Mix::Normal,
+    Mix::Multiply,
+    Mix::Screen,
+    Mix::Overlay,
+    Mix::Darken,
+    Mix::Lighten,
+    Mix::ColorDodge,
+    Mix::ColorBurn,
+    Mix::HardLight,
+    Mix::SoftLight,
+    Mix::Difference,
+    Mix::Exclusion,
+    Mix::Hue,
+    Mix::Saturation,
+    Mix::Color,
+    Mix::Luminosity,
+];
+
+const COMPOSE_MODES: [Compose; 14] = [
+    Compose::Clear,
+    Compose::Copy,
+    Compose::Dest,
+    Compose::SrcOver,
+    Compose::DestOver,
+    Compose::SrcIn,
+    Compose::DestIn,
+    Compose::SrcOut,
+    Compose::DestOut,
+    Compose::SrcAtop,
+    Compose::DestAtop,
+    Compose::Xor,
+    Compose::Plus,
+    Compose::PlusLighter,
+];
+
+/// The rect of the cell at `index` in a grid with `columns` columns
+fn cell(index: usize, columns: usize) -> Rect {
+    let x = (index % columns) as f64 * CELL;
+    let y = (index / columns) as f64 * CELL;
+    Rect::new(x, y, x + CELL, y + CELL)
+}
+
+fn mix_modes(scene: &mut Scene) {
+    let backdrop = Gradient::new_linear((0.0, 0.0), (CELL, 0.0))
+        .with_stops([Color::from_rgb8(230, 60, 40), Color::from_rgb8(40, 90, 220)].as_slice());
+    for (i, mix) in MIX_MODES.into_iter().enumerate() {
+        let rect = cell(i, 4);
+        let transform = Affine::translate(rect.origin().to_vec2());
+        scene.fill(
+            Fill::NonZero,
+            transform,
+            &backdrop,
+            None,
+            &Rect::new(0.0, 0.0, CELL, CELL),
+        );
+        scene.push_layer(mix, 1.0, Affine::IDENTITY, &rect);
+        scene.fill(
+            Fill::NonZero,
+            transform,
+            Color::from_rgba8(250, 210, 60, 220),
+            None,
+            &Circle::new((CELL * 0.6, CELL * 0.6), CELL * 0.35),
+        );
+        scene.pop_layer();
+    }
+}
+
+fn compose_mode(scene: &mut Scene, compose: Compose) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(220, 50, 50),
+        None,
+        &Rect::new(4.0, 4.0, 20.0, 20.0),
+    );
+    scene.push_layer(
+        BlendMode::new(Mix::Normal, compose),
+        1.0,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, CELL, CELL),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgba8(40, 80, 230, 200),
+        None,
+        &Circle::new((20.0, 20.0), 9.0),
+    );
+    scene.pop_layer();
+}
+
+fn star(center: Point, outer: f64, inner: f64, points: usize) -> BezPath {
+    let mut path = BezPath::new();
+    for i in 0..points * 2 {
+        let radius = if i % 2 == 0 { outer } else { inner };
+        let angle = i as f64 * PI / points as f64 - PI / 2.0;
+        let point = center + kurbo::Vec2::from_angle(angle) * radius;
+        if i == 0 {
+            path.move_to(point);
+        } else {
+            path.line_to(point);
+        }
+    }
+    path.close_path();
+    path
+}
+
+fn nested_clip_layers(scene: &mut Scene) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(245, 245, 245),
+        None,
+        &Rect::new(0.0, 0.0, 128.0, 128.0),
+    );
+    scene.push_clip_layer(Affine::IDENTITY, &Circle::new((64.0, 64.0), 56.0));
+    scene.push_clip_layer(
+        Affine::rotate_about(PI / 8.0, Point::new(64.0, 64.0)),
+        &Rect::new(20.0, 20.0, 108.0, 108.0),
+    );
+    for i in 0..16 {
+        let color = if i % 2 == 0 {
+            Color::from_rgb8(40, 160, 90)
+        } else {
+            Color::from_rgb8(250, 190, 60)
+        };
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            color,
+            None,
+            &Rect::new(i as f64 * 8.0, 0.0, i as f64 * 8.0 + 8.0, 128.0),
+        );
+    }
+    scene.push_layer(
+        Mix::Normal,
+        0.6,
+        Affine::IDENTITY,
+        &star(Point::new(64.0, 64.0), 50.0, 20.0, 5),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(60, 60, 200),
+        None,
+        &Rect::new(0.0, 0.0, 128.0, 128.0),
+    );
+    scene.pop_layer();
+    scene.pop_layer();
+    scene.pop_layer();
+}
+
+fn fill_rules(scene: &mut Scene) {
+    let pentagram = |center: Point| {
+        let mut path = BezPath::new();
+        for i in 0..5 {
+            let angle = (i * 2) as f64 * TAU / 5.0 - PI / 2.0;
+            let point = center + kurbo::Vec2::from_angle(angle) * 28.0;
+            if i == 0 {
+                path.move_to(point);
+            } else {
+                path.line_to(point);
+            }
+        }
+        path.close_path();
+        // A hole made of a circle with the same winding direction
+        path.extend(Circle::new(center, 8.0).path_elements(0.1));
+        path
+    };
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(200, 40, 120),
+        None,
+        &pentagram(Point::new(32.0, 34.0)),
+    );
+    scene.fill(
+        Fill::EvenOdd,
+        Affine::IDENTITY,
+        Color::from_rgb8(200, 40, 120),
+        None,
+        &pentagram(Point::new(96.0, 34.0)),
+    );
+}
+
+fn dashed_strokes(scene: &mut Scene) {
+    let color = Color::from_rgb8(30, 30, 30);
+    for (i, cap) in [Cap::Butt, Cap::Square, Cap::Round].into_iter().enumerate() {
+        let y = 12.0 + i as f64 * 14.0;
+        scene.stroke(
+            &Stroke::new(6.0)
+                .with_caps(cap)
+                .with_dashes(i as f64 * 3.0, [12.0, 8.0]),
+            Affine::IDENTITY,
+            color,
+            None,
+            &kurbo::Line::new((10.0, y), (118.0, y)),
+        );
+    }
+    for (i, join) in [Join::Bevel, Join::Miter, Join::Round]
+        .into_iter()
+        .enumerate()
+    {
+        let x = 12.0 + i as f64 * 38.0;
+        let mut zigzag = BezPath::new();
+        zigzag.move_to((x, 80.0));
+        zigzag.line_to((x + 12.0, 56.0));
+        zigzag.line_to((x + 24.0, 80.0));
+        scene.stroke(
+            &Stroke::new(6.0).with_join(join),
+            Affine::IDENTITY,
+            Color::from_rgb8(40, 100, 200),
+            None,
+            &zigzag,
+        );
+    }
+    scene.stroke(
+        &Stroke::new(3.0).with_dashes(0.0, [2.0, 4.0, 10.0, 4.0]),
+        Affine::IDENTITY,
+        Color::from_rgb8(200, 80, 40),
+        None,
+        &RoundedRect::new(12.0, 92.0, 116.0, 120.0, 8.0),
+    );
+}
+
+fn gradient_extends(scene: &mut Scene) {
+    let stops = [
+        Color::from_rgb8(240, 60, 60),
+        Color::from_rgb8(250, 220, 80),
+        Color::from_rgb8(50, 90, 220),
+    ];
+    let extends = [Extend::Pad, Extend::Repeat, Extend::Reflect];
+    for (column, extend) in extends.into_iter().enumerate() {
+        // The gradients only cover the middle of each cell, so that the extend mode is visible
+        let gradients = [
+            Gradient::new_linear((10.0, 0.0), (22.0, 0.0)),
+            Gradient::new_radial((16.0, 16.0), 6.0),
+            Gradient::new_sweep((16.0, 16.0), 0.0, (TAU / 3.0) as f32),
+        ];
+        for (row, gradient) in gradients.into_iter().enumerate() {
+            let rect = cell(row * 3 + column, 3);
+            scene.fill(
+                Fill::NonZero,
+                Affine::translate(rect.origin().to_vec2()),
+                &gradient.with_extend(extend).with_stops(stops.as_slice()),
+                None,
+                &Rect::new(1.0, 1.0, CELL - 1.0, CELL - 1.0),
+            );
+        }
+    }
+}
+
+fn image_samplers(scene: &mut Scene) {
+    // A 4x4 checkerboard with a translucent quadrant
+    let mut data = Vec::with_capacity(4 * 4 * 4);
+    for y in 0..4 {
+        for x in 0..4 {
+            let pixel = match ((x + y) % 2, x >= 2 && y >= 2) {
+                (0, false) => [20, 20, 20, 255],
+                (_, false) => [240, 240, 240, 255],
+                (0, true) => [200, 30, 30, 128],
+                (_, true) => [30, 30, 200, 128],
+            };
+            data.extend_from_slice(&pixel);
+        }
+    }
+    let image = ImageData {
+        data: Blob::from(data),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 4,
+        height: 4,
+    };
+
+    let qualities = [ImageQuality::Low, ImageQuality::Medium, ImageQuality::High];
+    let extends = [Extend::Pad, Extend::Repeat, Extend::Reflect];
+    for (column, quality) in qualities.into_iter().enumerate() {
+        for (row, extend) in extends.into_iter().enumerate() {
+            let rect = cell(row * 3 + column, 3);
+            // The image is scaled up and only covers the middle of the cell
+            scene.fill(
+                Fill::NonZero,
+                Affine::translate(rect.origin().to_vec2()),
+                ImageBrush::new(image.clone())
+                    .with_quality(quality)
+                    .with_extend(extend)
+                    .as_ref(),
+                Some(Affine::translate((8.0, 8.0)) * Affine::scale(4.0)),
+                &Rect::new(1.0, 1.0, CELL - 1.0, CELL - 1.0),
+            );
+        }
+        // Different extend modes for each axis
+        let rect = cell(9 + column, 3);
+        scene.fill(
+            Fill::NonZero,
+            Affine::translate(rect.origin().to_vec2()),
+            ImageBrush::new(image.clone())
+                .with_quality(quality)
+                .with_x_extend(Extend::Repeat)
+                .with_y_extend(Extend::Reflect)
+                .as_ref(),
+            Some(Affine::translate((8.0, 8.0)) * Affine::scale(4.0)),
+            &Rect::new(1.0, 1.0, CELL - 1.0, CELL - 1.0),
+        );
+    }
+}
+
+/// Lay out `text` on a single line using the font's advances
+fn layout(font: &FontRef<'_>, font_size: f32, text: &str) -> Vec<Glyph> {
+    let charmap = font.charmap();
+    let metrics = font.glyph_metrics(
+        Size::new(font_size),
+        skrifa::instance::LocationRef::default(),
+    );
+    let mut x = 0.0;
+    text.chars()
+        .filter_map(|c| {
+            let id = charmap.map(c)?;
+            let glyph = Glyph {
+                id: id.to_u32(),
+                x,
+                y: 0.0,
+            };
+            x += metrics.advance_width(id).unwrap_or_default();
+            Some(glyph)
+        })
+        .collect()
+}
+
+fn glyph_runs(scene: &mut Scene) {
+    let font_ref = FontRef::new(ROBOTO).expect("failed to parse Roboto");
+    let font = FontData::new(Blob::from(ROBOTO.to_vec()), 0);
+
+    let runs: [(f32, f64, Option<Affine>); 3] = [
+        (12.0, 20.0, None),
+        (20.0, 48.0, None),
+        // Synthetic italic
+        (20.0, 76.0, Some(Affine::skew(-0.25, 0.0))),
+    ];
+    for (font_size, y, glyph_transform) in runs {
+        scene.draw_glyphs(
+            &font,
+            font_size,
+            false,
+            &[],
+            Fill::NonZero,
+            Color::from_rgb8(20, 20, 20),
+            1.0,
+            Affine::translate((8.0, y)),
+            glyph_transform,
+            layout(&font_ref, font_size, "Anyrender").into_iter(),
+        );
+    }
+    scene.draw_glyphs(
+        &font,
+        32.0,
+        false,
+        &[],
+        &Stroke::new(1.0),
+        Color::from_rgb8(40, 100, 200),
+        1.0,
+        Affine::translate((8.0, 116.0)),
+        None,
+        layout(&font_ref, 32.0, "Stroke").into_iter(),
+    );
+}
+
+fn box_shadows(scene: &mut Scene) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::WHITE,
+        None,
+        &Rect::new(0.0, 0.0, 128.0, 128.0),
+    );
+    let shadows = [
+        (Rect::new(12.0, 12.0, 52.0, 52.0), 0.0, 2.0),
+        (Rect::new(76.0, 12.0, 116.0, 52.0), 8.0, 4.0),
+        (Rect::new(12.0, 76.0, 52.0, 116.0), 20.0, 6.0),
+    ];
+    for (rect, radius, std_dev) in shadows {
+        scene.draw_box_shadow(
+            Affine::IDENTITY,
+            rect,
+            Color::from_rgba8(0, 0, 0, 160),
+            radius,
+            std_dev,
+        );
+    }
+    scene.draw_box_shadow(
+        Affine::rotate_about(PI / 6.0, Point::new(96.0, 96.0)),
+        Rect::new(80.0, 86.0, 112.0, 106.0),
+        Color::from_rgb8(40, 100, 200),
+        4.0,
+        3.0,
+    );
+}
@@ -0,0 +1,33 @@
+use anyrender_conformance::{Tolerance, UPDATE_REFERENCES_ENV_VAR, check_backend};
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+
+/// The reference images are rendered with vello_cpu, so it is held to (almost) exact output
+#[test]
+fn vello_cpu() {
+    let update = std::env::var_os(UPDATE_REFERENCES_ENV_VAR).is_some();
+    let report = check_backend::<VelloCpuImageRenderer>(
+        "vello_cpu",
+        Tolerance {
+            max_channel_difference: 2,
+            max_differing_ratio: 0.0,
+        },
+        update,
+    );
+    println!("{report}");
+    assert!(report.passed(), "{report}");
+}
+
+#[cfg(feature = "skia")]
+#[test]
+fn skia() {
+    let report = check_backend::<anyrender_skia::SkiaImageRenderer>(
+        "skia",
+        Tolerance {
+            max_channel_difference: 24,
+            max_differing_ratio: 0.02,
+        },
+        false,
+    );
+    println!("{report}");
+    assert!(report.passed(), "{report}");
+}
@@ -0,0 +1,665 @@
+//! The binary encoding of draw commands (`draw_commands.bin`).
+//!
+//! # Layout
+//!
+//! All multi-byte numbers are little-endian. `varint` is an unsigned LEB128 integer, and `f32`/`f64` are IEEE 754
+//! floats. The stream starts with a header:
+//!
+//! | Field           | Type       | Notes                                  |
+//! |-----------------|------------|----------------------------------------|
+//! | magic           | `[u8; 4]`  | `b"ARDC"`                              |
+//! | version         | `u32`      | [`BINARY_VERSION`]                     |
+//! | command count   | `varint`   |                                        |
+//!
+//! followed by the commands, each of which is a `u8` tag and its fields:
+//!
+//! | Tag | Command         | Fields                                                                        |
+//! |-----|-----------------|-------------------------------------------------------------------------------|
+//! | 0   | `PushLayer`     | mix `u8`, compose `u8`, alpha `f32`, transform, shape                         |
+//! | 1   | `PushClipLayer` | transform, shape                                                              |
+//! | 2   | `PopLayer`      |                                                                               |
+//! | 3   | `Stroke`        | stroke style, transform, brush, optional brush transform, shape               |
+//! | 4   | `Fill`          | fill rule `u8`, transform, brush, optional brush transform, shape             |
+//! | 5   | `GlyphRun`      | font resource `varint`, face index `varint`, font size `f32`, hint `u8`,      |
+//! |     |                 | coord count `varint` + `i16` coords, style, brush, brush alpha `f32`,         |
+//! |     |                 | transform, optional glyph transform, glyph count `varint` + glyphs            |
+//! | 6   | `BoxShadow`     | transform, rect, color, radius `f64`, std_dev `f64`                           |
+//!
+//! - A transform is 6 `f64`s (the [`Affine`] coefficients). An optional transform is a `u8` (0 = none, 1 = some)
+//!   followed by the transform if present.
+//! - A shape is a `u8` tag followed by `f64` fields: 0 = rect (x0, y0, x1, y1), 1 = rounded rect (rect followed
+//!   by the 4 radii), 2 = circle (center x, y, radius), 3 = line (p0 x, y, p1 x, y), 4 = path.
+//! - A path is an element count `varint` followed by the elements, each of which is a verb `u8` (0 = move, 1 = line,
+//!   2 = quad, 3 = cubic, 4 = close) followed by its points as pairs of `f32`s. Path points are therefore stored
+//!   with single precision.
+//! - A stroke style is width `f64`, join `u8`, miter limit `f64`, start cap `u8`, end cap `u8`, dash count
+//!   `varint` + dash lengths `f64`, dash offset `f64`.
+//! - A glyph run style is a `u8` (0 = fill, followed by the fill rule `u8`; 1 = stroke, followed by a stroke style).
+//! - A color is 4 `f32` sRGB components (red, green, blue, alpha).
+//! - A brush is a `u8` tag: 0 = solid (color), 1 = gradient (a `varint` length followed by the gradient as JSON),
+//!   2 = image (resource `varint`, x extend `u8`, y extend `u8`, quality `u8`, alpha `f32`).
+//! - A glyph is an ID `varint` followed by x and y `f32`s.
+//!
+//! Enums are stored using their `repr(u8)` discriminants.
+
+use bytemuck::Contiguous;
+use peniko::kurbo::{
+    Affine, BezPath, Cap, Circle, Join, Line, PathEl, Point, Rect, RoundedRect, Stroke,
+};
+use peniko::{
+    BlendMode, Color, Compose, Extend, Fill, Gradient, ImageBrush, ImageQuality, ImageSampler, Mix,
+    Style,
+};
+
+use anyrender::recording::{
+    BoxShadowCommand, ClipCommand, FillCommand, GlyphRunCommand, LayerCommand, RecordedShape,
+    StrokeCommand,
+};
+use anyrender::{Glyph, Paint};
+
+use crate::{
+    ArchiveError, FontResourceId, ResourceId, SerializableBrush, SerializableRenderCommand,
+};
+
+/// The magic bytes at the start of a binary command stream
+pub const BINARY_MAGIC: [u8; 4] = *b"ARDC";
+
+/// The version of the binary command layout. Bump this when the layout changes.
+pub const BINARY_VERSION: u32 = 1;
+
+/// Encode draw commands using the binary layout
+pub(crate) fn encode_commands(
+    commands: &[SerializableRenderCommand],
+) -> Result<Vec<u8>, ArchiveError> {
+    let mut writer = Writer::default();
+    writer.bytes(&BINARY_MAGIC);
+    writer.bytes(&BINARY_VERSION.to_le_bytes());
+    writer.varint(commands.len() as u64);
+    for cmd in commands {
+        writer.command(cmd)?;
+    }
+    Ok(writer.buf)
+}
+
+/// Decode draw commands stored using the binary layout
+pub(crate) fn decode_commands(data: &[u8]) -> Result<Vec<SerializableRenderCommand>, ArchiveError> {
+    let mut reader = Reader { data, pos: 0 };
+    if reader.take(4)? != BINARY_MAGIC {
+        return Err(invalid("missing binary command stream header"));
+    }
+    let version = u32::from_le_bytes(reader.array()?);
+    if version != BINARY_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(version));
+    }
+    let count = reader.count()?;
+    let mut commands = Vec::with_capacity(count);
+    for _ in 0..count {
+        commands.push(reader.command()?);
+    }
+    if reader.pos != data.len() {
+        return Err(invalid("trailing data after draw commands"));
+    }
+    Ok(commands)
+}
+
+fn invalid(msg: &str) -> ArchiveError {
+    ArchiveError::InvalidFormat(format!("binary draw commands: {msg}"))
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.u8(byte);
+                return;
+            }
+            self.u8(byte | 0x80);
+        }
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn point_f32(&mut self, point: Point) {
+        self.f32(point.x as f32);
+        self.f32(point.y as f32);
+    }
+
+    fn transform(&mut self, transform: Affine) {
+        for coeff in transform.as_coeffs() {
+            self.f64(coeff);
+        }
+    }
+
+    fn optional_transform(&mut self, transform: Option<Affine>) {
+        self.bool(transform.is_some());
+        if let Some(transform) = transform {
+            self.transform(transform);
+        }
+    }
+
+    fn rect(&mut self, rect: Rect) {
+        for value in [rect.x0, rect.y0, rect.x1, rect.y1] {
+            self.f64(value);
+        }
+    }
+
+    fn color(&mut self, color: Color) {
+        for component in color.components {
+            self.f32(component);
+        }
+    }
+
+    fn shape(&mut self, shape: &RecordedShape) {
+        match shape {
+            RecordedShape::Rect(rect) => {
+                self.u8(0);
+                self.rect(*rect);
+            }
+            RecordedShape::RoundedRect(rrect) => {
+                self.u8(1);
+                self.rect(rrect.rect());
+                let radii = rrect.radii();
+                for radius in [
+                    radii.top_left,
+                    radii.top_right,
+                    radii.bottom_right,
+                    radii.bottom_left,
+                ] {
+                    self.f64(radius);
+                }
+            }
+            RecordedShape::Circle(circle) => {
+                self.u8(2);
+                self.f64(circle.center.x);
+                self.f64(circle.center.y);
+                self.f64(circle.radius);
+            }
+            RecordedShape::Line(line) => {
+                self.u8(3);
+                for value in [line.p0.x, line.p0.y, line.p1.x, line.p1.y] {
+                    self.f64(value);
+                }
+            }
+            RecordedShape::Path(path) => {
+                self.u8(4);
+                self.path(path);
+            }
+        }
+    }
+
+    fn path(&mut self, path: &BezPath) {
+        self.varint(path.elements().len() as u64);
+        for el in path.elements() {
+            match *el {
+                PathEl::MoveTo(p) => {
+                    self.u8(0);
+                    self.point_f32(p);
+                }
+                PathEl::LineTo(p) => {
+                    self.u8(1);
+                    self.point_f32(p);
+                }
+                PathEl::QuadTo(p1, p2) => {
+                    self.u8(2);
+                    self.point_f32(p1);
+                    self.point_f32(p2);
+                }
+                PathEl::CurveTo(p1, p2, p3) => {
+                    self.u8(3);
+                    self.point_f32(p1);
+                    self.point_f32(p2);
+                    self.point_f32(p3);
+                }
+                PathEl::ClosePath => self.u8(4),
+            }
+        }
+    }
+
+    fn stroke_style(&mut self, style: &Stroke) {
+        self.f64(style.width);
+        self.u8(match style.join {
+            Join::Bevel => 0,
+            Join::Miter => 1,
+            Join::Round => 2,
+        });
+        self.f64(style.miter_limit);
+        for cap in [style.start_cap, style.end_cap] {
+            self.u8(match cap {
+                Cap::Butt => 0,
+                Cap::Square => 1,
+                Cap::Round => 2,
+            });
+        }
+        self.varint(style.dash_pattern.len() as u64);
+        for dash in &style.dash_pattern {
+            self.f64(*dash);
+        }
+        self.f64(style.dash_offset);
+    }
+
+    fn brush(&mut self, brush: &SerializableBrush) -> Result<(), ArchiveError> {
+        match brush {
+            Paint::Solid(color) => {
+                self.u8(0);
+                self.color(*color);
+            }
+            Paint::Gradient(gradient) => {
+                self.u8(1);
+                let json = serde_json::to_vec(gradient)?;
+                self.varint(json.len() as u64);
+                self.bytes(&json);
+            }
+            Paint::Image(image) => {
+                self.u8(2);
+                self.varint(image.image.0 as u64);
+                self.u8(image.sampler.x_extend.into_integer());
+                self.u8(image.sampler.y_extend.into_integer());
+                self.u8(image.sampler.quality.into_integer());
+                self.f32(image.sampler.alpha);
+            }
+            // Custom paints are replaced when the archive is created, so this is unreachable in practice
+            Paint::Custom(_) => {
+                self.u8(0);
+                self.color(Color::TRANSPARENT);
+            }
+        }
+        Ok(())
+    }
+
+    fn command(&mut self, cmd: &SerializableRenderCommand) -> Result<(), ArchiveError> {
+        match cmd {
+            SerializableRenderCommand::PushLayer(layer) => {
+                self.u8(0);
+                self.u8(layer.blend.mix.into_integer());
+                self.u8(layer.blend.compose.into_integer());
+                self.f32(layer.alpha);
+                self.transform(layer.transform);
+                self.shape(&layer.clip);
+            }
+            SerializableRenderCommand::PushClipLayer(clip) => {
+                self.u8(1);
+                self.transform(clip.transform);
+                self.shape(&clip.clip);
+            }
+            SerializableRenderCommand::PopLayer => self.u8(2),
+            SerializableRenderCommand::Stroke(stroke) => {
+                self.u8(3);
+                self.stroke_style(&stroke.style);
+                self.transform(stroke.transform);
+                self.brush(&stroke.brush)?;
+                self.optional_transform(stroke.brush_transform);
+                self.shape(&stroke.shape);
+            }
+            SerializableRenderCommand::Fill(fill) => {
+                self.u8(4);
+                self.u8(fill.fill.into_integer());
+                self.transform(fill.transform);
+                self.brush(&fill.brush)?;
+                self.optional_transform(fill.brush_transform);
+                self.shape(&fill.shape);
+            }
+            SerializableRenderCommand::GlyphRun(run) => {
+                self.u8(5);
+                self.varint(run.font_data.resource_id.0 as u64);
+                self.varint(run.font_data.index as u64);
+                self.f32(run.font_size);
+                self.bool(run.hint);
+                self.varint(run.normalized_coords.len() as u64);
+                for coord in &run.normalized_coords {
+                    self.bytes(&coord.to_le_bytes());
+                }
+                match &run.style {
+                    Style::Fill(fill) => {
+                        self.u8(0);
+                        self.u8(fill.into_integer());
+                    }
+                    Style::Stroke(stroke) => {
+                        self.u8(1);
+                        self.stroke_style(stroke);
+                    }
+                }
+                self.brush(&run.brush)?;
+                self.f32(run.brush_alpha);
+                self.transform(run.transform);
+                self.optional_transform(run.glyph_transform);
+                self.varint(run.glyphs.len() as u64);
+                for glyph in &run.glyphs {
+                    self.varint(glyph.id as u64);
+                    self.f32(glyph.x);
+                    self.f32(glyph.y);
+                }
+            }
+            SerializableRenderCommand::BoxShadow(shadow) => {
+                self.u8(6);
+                self.transform(shadow.transform);
+                self.rect(shadow.rect);
+                self.color(shadow.brush);
+                self.f64(shadow.radius);
+                self.f64(shadow.std_dev);
+            }
+            SerializableRenderCommand::Fragment(_) => {
+                return Err(ArchiveError::InvalidFormat(
+                    "scene fragments must be flattened before they are encoded".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ArchiveError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ArchiveError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, ArchiveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, ArchiveError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid("invalid boolean")),
+        }
+    }
+
+    fn varint(&mut self) -> Result<u64, ArchiveError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint is too long"))
+    }
+
+    fn varint_u32(&mut self) -> Result<u32, ArchiveError> {
+        u32::try_from(self.varint()?).map_err(|_| invalid("value out of range"))
+    }
+
+    /// Read a count of items. Counts are capped at the number of remaining bytes (every item takes at least one
+    /// byte) so that corrupt data can't cause huge allocations.
+    fn count(&mut self) -> Result<usize, ArchiveError> {
+        let count = self.varint()?;
+        if count > (self.data.len() - self.pos) as u64 {
+            return Err(invalid("count exceeds the remaining data"));
+        }
+        Ok(count as usize)
+    }
+
+    fn f32(&mut self) -> Result<f32, ArchiveError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64, ArchiveError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn enum_u8<T: Contiguous<Int = u8>>(&mut self) -> Result<T, ArchiveError> {
+        T::from_integer(self.u8()?).ok_or_else(|| invalid("invalid enum value"))
+    }
+
+    fn point_f32(&mut self) -> Result<Point, ArchiveError> {
+        Ok(Point::new(self.f32()? as f64, self.f32()? as f64))
+    }
+
+    fn transform(&mut self) -> Result<Affine, ArchiveError> {
+        let mut coeffs = [0.0; 6];
+        for coeff in &mut coeffs {
+            *coeff = self.f64()?;
+        }
+        Ok(Affine::new(coeffs))
+    }
+
+    fn optional_transform(&mut self) -> Result<Option<Affine>, ArchiveError> {
+        Ok(match self.bool()? {
+            true => Some(self.transform()?),
+            false => None,
+        })
+    }
+
+    fn rect(&mut self) -> Result<Rect, ArchiveError> {
+        Ok(Rect::new(
+            self.f64()?,
+            self.f64()?,
+            self.f64()?,
+            self.f64()?,
+        ))
+    }
+
+    fn color(&mut self) -> Result<Color, ArchiveError> {
+        Ok(Color::new([
+            self.f32()?,
+            self.f32()?,
+            self.f32()?,
+            self.f32()?,
+        ]))
+    }
+
+    fn shape(&mut self) -> Result<RecordedShape, ArchiveError> {
+        Ok(match self.u8()? {
+            0 => RecordedShape::Rect(self.rect()?),
+            1 => {
+                let rect = self.rect()?;
+                let radii = (self.f64()?, self.f64()?, self.f64()?, self.f64()?);
+                RecordedShape::RoundedRect(RoundedRect::from_rect(rect, radii))
+            }
+            2 => {
+                let center = Point::new(self.f64()?, self.f64()?);
+                RecordedShape::Circle(Circle::new(center, self.f64()?))
+            }
+            3 => RecordedShape::Line(Line::new(
+                (self.f64()?, self.f64()?),
+                (self.f64()?, self.f64()?),
+            )),
+            4 => RecordedShape::Path(self.path()?),
+            _ => return Err(invalid("invalid shape")),
+        })
+    }
+
+    fn path(&mut self) -> Result<BezPath, ArchiveError> {
+        let count = self.count()?;
+        let mut path = BezPath::new();
+        for _ in 0..count {
+            let el = match self.u8()? {
+                0 => PathEl::MoveTo(self.point_f32()?),
+                1 => PathEl::LineTo(self.point_f32()?),
+                2 => PathEl::QuadTo(self.point_f32()?, self.point_f32()?),
+                3 => PathEl::CurveTo(self.point_f32()?, self.point_f32()?, self.point_f32()?),
+                4 => PathEl::ClosePath,
+                _ => return Err(invalid("invalid path verb")),
+            };
+            path.push(el);
+        }
+        Ok(path)
+    }
+
+    fn stroke_style(&mut self) -> Result<Stroke, ArchiveError> {
+        let width = self.f64()?;
+        let join = match self.u8()? {
+            0 => Join::Bevel,
+            1 => Join::Miter,
+            2 => Join::Round,
+            _ => return Err(invalid("invalid stroke join")),
+        };
+        let miter_limit = self.f64()?;
+        let mut caps = [Cap::Butt; 2];
+        for cap in &mut caps {
+            *cap = match self.u8()? {
+                0 => Cap::Butt,
+                1 => Cap::Square,
+                2 => Cap::Round,
+                _ => return Err(invalid("invalid stroke cap")),
+            };
+        }
+        let dash_count = self.count()?;
+        let mut dashes = Vec::with_capacity(dash_count);
+        for _ in 0..dash_count {
+            dashes.push(self.f64()?);
+        }
+        let dash_offset = self.f64()?;
+        Ok(Stroke::new(width)
+            .with_join(join)
+            .with_miter_limit(miter_limit)
+            .with_start_cap(caps[0])
+            .with_end_cap(caps[1])
+            .with_dashes(dash_offset, dashes))
+    }
+
+    fn brush(&mut self) -> Result<SerializableBrush, ArchiveError> {
+        Ok(match self.u8()? {
+            0 => Paint::Solid(self.color()?),
+            1 => {
+                let len = self.count()?;
+                let gradient: Gradient = serde_json::from_slice(self.take(len)?)?;
+                Paint::Gradient(gradient)
+            }
+            2 => {
+                let image = ResourceId(self.varint()? as usize);
+                let x_extend: Extend = self.enum_u8()?;
+                let y_extend: Extend = self.enum_u8()?;
+                let quality: ImageQuality = self.enum_u8()?;
+                let alpha = self.f32()?;
+                Paint::Image(ImageBrush {
+                    image,
+                    sampler: ImageSampler {
+                        x_extend,
+                        y_extend,
+                        quality,
+                        alpha,
+                    },
+                })
+            }
+            _ => return Err(invalid("invalid brush")),
+        })
+    }
+
+    fn command(&mut self) -> Result<SerializableRenderCommand, ArchiveError> {
+        Ok(match self.u8()? {
+            0 => {
+                let mix: Mix = self.enum_u8()?;
+                let compose: Compose = self.enum_u8()?;
+                SerializableRenderCommand::PushLayer(LayerCommand {
+                    blend: BlendMode { mix, compose },
+                    alpha: self.f32()?,
+                    transform: self.transform()?,
+                    clip: self.shape()?,
+                })
+            }
+            1 => SerializableRenderCommand::PushClipLayer(ClipCommand {
+                transform: self.transform()?,
+                clip: self.shape()?,
+            }),
+            2 => SerializableRenderCommand::PopLayer,
+            3 => SerializableRenderCommand::Stroke(StrokeCommand {
+                style: self.stroke_style()?,
+                transform: self.transform()?,
+                brush: self.brush()?,
+                brush_transform: self.optional_transform()?,
+                shape: self.shape()?,
+            }),
+            4 => SerializableRenderCommand::Fill(FillCommand {
+                fill: self.enum_u8::<Fill>()?,
+                transform: self.transform()?,
+                brush: self.brush()?,
+                brush_transform: self.optional_transform()?,
+                shape: self.shape()?,
+            }),
+            5 => {
+                let font_data = FontResourceId {
+                    resource_id: ResourceId(self.varint()? as usize),
+                    index: self.varint_u32()?,
+                };
+                let font_size = self.f32()?;
+                let hint = self.bool()?;
+                let coord_count = self.count()?;
+                let mut normalized_coords = Vec::with_capacity(coord_count);
+                for _ in 0..coord_count {
+                    normalized_coords.push(i16::from_le_bytes(self.array()?));
+                }
+                let style = match self.u8()? {
+                    0 => Style::Fill(self.enum_u8()?),
+                    1 => Style::Stroke(self.stroke_style()?),
+                    _ => return Err(invalid("invalid glyph run style")),
+                };
+                let brush = self.brush()?;
+                let brush_alpha = self.f32()?;
+                let transform = self.transform()?;
+                let glyph_transform = self.optional_transform()?;
+                let glyph_count = self.count()?;
+                let mut glyphs = Vec::with_capacity(glyph_count);
+                for _ in 0..glyph_count {
+                    glyphs.push(Glyph {
+                        id: self.varint_u32()?,
+                        x: self.f32()?,
+                        y: self.f32()?,
+                    });
+                }
+                SerializableRenderCommand::GlyphRun(GlyphRunCommand {
+                    font_data,
+                    font_size,
+                    hint,
+                    normalized_coords,
+                    style,
+                    brush,
+                    brush_alpha,
+                    transform,
+                    glyph_transform,
+                    glyphs,
+                })
+            }
+            6 => SerializableRenderCommand::BoxShadow(BoxShadowCommand {
+                transform: self.transform()?,
+                rect: self.rect()?,
+                brush: self.color()?,
+                radius: self.f64()?,
+                std_dev: self.f64()?,
+            }),
+            _ => return Err(invalid("invalid command")),
+        })
+    }
+}
@@ -5,32 +5,50 @@
 //! The serialized scene is a zip archive containing:
 //!
 //! - `resources.json` - Metadata mapping resource files to IDs
-//! - `draw_commands.json` - Serialized draw commands referencing resources by ID
-//! - `images/<sha256_hash>.png` - Image files (PNG format)
+//! - `draw_commands.json` or `draw_commands.bin` - Serialized draw commands referencing resources by ID
+//! - `images/<content_hash>.{png,jpg,webp}` - Image files (PNG re-encoded from the image's pixels, or the original
+//!   encoded bytes when they are provided via [`SerializeConfig::with_encoded_image`]), named by a hash of the
+//!   image's pixels, format and dimensions
 //! - `fonts/<sha256_hash>.{woff2,ttf}` - Font data files (optionally WOFF2-compressed and subsetted)
+//!
+//! Draw commands are stored as JSON by default. Large scenes can instead use a compact binary encoding (see
+//! [`CommandEncoding`] and the [`binary`] module for its layout), which is detected automatically when
+//! deserializing.
+//!
+//! Sequences of frames which share resources (such as a captured interaction) can be stored in a single archive
+//! using [`SceneSequenceWriter`] and read back one frame at a time using [`SceneSequenceReader`].
+//! [`RecordingWindowRenderer`] captures the frames rendered by any window renderer into such an archive.
 
 use std::collections::HashMap;
 use std::io::{Read, Seek, Write};
 
+use bytemuck::Contiguous;
 use image::{ImageBuffer, ImageEncoder, RgbaImage};
-use peniko::{Blob, Brush, FontData, ImageAlphaType, ImageBrush, ImageData, ImageFormat};
+use peniko::kurbo::Affine;
+use peniko::{Blob, Color, FontData, ImageAlphaType, ImageBrush, ImageData, ImageFormat};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use zip::write::SimpleFileOptions;
 use zip::{ZipArchive, ZipWriter};
 
+use anyrender::Paint;
 use anyrender::recording::{FillCommand, GlyphRunCommand, RenderCommand, Scene, StrokeCommand};
 
+pub mod binary;
 mod font_writer;
 mod json_formatter;
+mod recording_renderer;
+mod sequence;
 
 use font_writer::FontWriter;
+pub use recording_renderer::RecordingWindowRenderer;
+pub use sequence::{FrameMetadata, SceneSequenceReader, SceneSequenceWriter};
 
 /// A render command with resources replaced by IDs.
 pub type SerializableRenderCommand = RenderCommand<FontResourceId, ResourceId>;
 
 /// A brush with images replaced by IDs.
-pub type SerializableBrush = Brush<ImageBrush<ResourceId>>;
+pub type SerializableBrush = Paint<ImageBrush<ResourceId>>;
 
 /// A unique identifier for a serialized resource.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -54,7 +72,11 @@ pub struct SceneArchive {
     pub commands: Vec<SerializableRenderCommand>,
     /// Font data (one per font resource, optionally WOFF2-compressed and/or subsetted).
     pub fonts: Vec<Blob<u8>>,
+    /// Image pixels (one per image resource, as RGBA8).
     pub images: Vec<ImageData>,
+    /// The original encoded bytes of each image resource, if they are stored in the archive (see
+    /// [`ImageMetadata::original_encoding`]). Otherwise the image is stored as a PNG encoded from its pixels.
+    pub encoded_images: Vec<Option<Blob<u8>>>,
 }
 
 /// The resources manifest stored in the archive.
@@ -66,11 +88,17 @@ pub struct ResourceManifest {
     pub tolerance: f64,
     pub images: Vec<ImageMetadata>,
     pub fonts: Vec<FontMetadata>,
+    /// How the draw commands are encoded (archives before version 3 always use JSON)
+    #[serde(default)]
+    pub command_encoding: CommandEncoding,
 }
 
 impl ResourceManifest {
     /// Current archive format version. Bump this when the format changes.
-    pub const CURRENT_VERSION: u32 = 1;
+    pub const CURRENT_VERSION: u32 = 4;
+
+    /// The oldest archive format version which can still be deserialized.
+    pub const MIN_SUPPORTED_VERSION: u32 = 1;
 
     pub fn new(tolerance: f64) -> Self {
         Self {
@@ -78,6 +106,37 @@ impl ResourceManifest {
             tolerance,
             images: Vec::new(),
             fonts: Vec::new(),
+            command_encoding: CommandEncoding::default(),
+        }
+    }
+}
+
+/// How draw commands are encoded in an archive.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandEncoding {
+    /// Human-readable JSON, stored in `draw_commands.json`
+    #[default]
+    Json,
+    /// The compact binary layout described in the [`binary`] module, stored in `draw_commands.bin`. Path points
+    /// are stored with single precision.
+    Binary,
+}
+
+impl CommandEncoding {
+    /// The name of the file in the archive that contains the draw commands
+    pub fn file_name(self) -> &'static str {
+        match self {
+            CommandEncoding::Json => "draw_commands.json",
+            CommandEncoding::Binary => "draw_commands.bin",
+        }
+    }
+
+    /// The extension of files containing draw commands in this encoding
+    pub fn extension(self) -> &'static str {
+        match self {
+            CommandEncoding::Json => "json",
+            CommandEncoding::Binary => "bin",
         }
     }
 }
@@ -93,6 +152,45 @@ pub struct ImageMetadata {
     pub alpha_type: ImageAlphaType,
     pub width: u32,
     pub height: u32,
+    /// The encoding of the image's original bytes, if they are stored in the archive. In that case the
+    /// resource's hash is of the stored file, rather than of the image's RGBA8 pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_encoding: Option<ImageEncoding>,
+}
+
+/// The encoding of an image file stored in an archive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageEncoding {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ImageEncoding {
+    /// The extension of image files with this encoding
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageEncoding::Png => "png",
+            ImageEncoding::Jpeg => "jpg",
+            ImageEncoding::Webp => "webp",
+        }
+    }
+
+    fn image_format(self) -> image::ImageFormat {
+        match self {
+            ImageEncoding::Png => image::ImageFormat::Png,
+            ImageEncoding::Jpeg => image::ImageFormat::Jpeg,
+            ImageEncoding::Webp => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// The original encoded bytes of an image, see [`SerializeConfig::with_encoded_image`].
+#[derive(Clone, Debug)]
+pub struct EncodedImage {
+    pub encoding: ImageEncoding,
+    pub data: Blob<u8>,
 }
 
 /// Metadata for a font resource.
@@ -111,7 +209,8 @@ pub struct FontMetadata {
 pub struct ResourceEntry {
     pub id: ResourceId,
     pub kind: ResourceKind,
-    /// The size of the raw decompressed resource data in bytes.
+    /// The size of the raw decompressed resource data in bytes (or of the stored file, for images stored with
+    /// their original encoding).
     pub size: usize,
     /// SHA-256 hash of the resource's raw content
     pub sha256_hash: String,
@@ -132,16 +231,26 @@ struct ResourceCollector {
     fonts: FontWriter,
     /// Maps Blob ID to ResourceId for images
     image_id_map: HashMap<u64, ResourceId>,
+    /// Maps the hash of an image's content to its ResourceId, so that identical images in different blobs are
+    /// only stored once
+    image_content_map: HashMap<String, ResourceId>,
     /// Collected images
     images: Vec<ImageData>,
+    /// The content hash of each collected image, which names its file in the archive
+    image_hashes: Vec<String>,
+    /// Original encoded bytes provided for images, keyed by Blob ID
+    encoded_sources: HashMap<u64, EncodedImage>,
 }
 
 impl ResourceCollector {
     fn new(config: SerializeConfig) -> Self {
         Self {
-            fonts: FontWriter::new(config),
             image_id_map: HashMap::new(),
+            image_content_map: HashMap::new(),
             images: Vec::new(),
+            image_hashes: Vec::new(),
+            encoded_sources: config.encoded_images.clone(),
+            fonts: FontWriter::new(config),
         }
     }
 
@@ -152,24 +261,65 @@ impl ResourceCollector {
             return id;
         }
 
-        let id = ResourceId(self.images.len());
+        let hash = image_content_hash(image);
+        let id = *self
+            .image_content_map
+            .entry(hash.clone())
+            .or_insert_with(|| {
+                self.images.push(image.clone());
+                self.image_hashes.push(hash);
+                ResourceId(self.images.len() - 1)
+            });
         self.image_id_map.insert(blob_id, id);
-        self.images.push(image.clone());
+
+        // If this blob was deduplicated with an earlier one, keep any encoded bytes provided for it
+        let stored_id = self.images[id.0].data.id();
+        if stored_id != blob_id
+            && !self.encoded_sources.contains_key(&stored_id)
+            && let Some(encoded) = self.encoded_sources.get(&blob_id).cloned()
+        {
+            self.encoded_sources.insert(stored_id, encoded);
+        }
         id
     }
 
-    /// Convert a [`Brush`] to a [`SerializableBrush`] by registering images.
-    fn convert_brush(&mut self, brush: &Brush) -> SerializableBrush {
+    /// Convert a [`Paint`] to a [`SerializableBrush`] by registering images.
+    ///
+    /// Custom paints are backend-specific and can't be stored in the archive, so they are replaced by a
+    /// transparent brush (which is how backends that don't support them render them).
+    fn convert_brush(&mut self, brush: &Paint) -> SerializableBrush {
         match brush {
-            Brush::Solid(color) => Brush::Solid(*color),
-            Brush::Gradient(gradient) => Brush::Gradient(gradient.clone()),
-            Brush::Image(image_brush) => {
+            Paint::Solid(color) => Paint::Solid(*color),
+            Paint::Gradient(gradient) => Paint::Gradient(gradient.clone()),
+            Paint::Image(image_brush) => {
                 let id = self.register_image(&image_brush.image);
-                Brush::Image(ImageBrush {
+                Paint::Image(ImageBrush {
                     image: id,
                     sampler: image_brush.sampler,
                 })
             }
+            Paint::Custom(_) => Paint::Solid(Color::TRANSPARENT),
+        }
+    }
+
+    /// Convert a list of [`RenderCommand`]s to [`SerializableRenderCommand`]s, flattening any scene
+    /// fragments into their commands.
+    fn convert_commands(
+        &mut self,
+        cmds: &[RenderCommand],
+        transform: Affine,
+        out: &mut Vec<SerializableRenderCommand>,
+    ) {
+        for cmd in cmds {
+            match cmd {
+                RenderCommand::Fragment(fragment) => self.convert_commands(
+                    fragment.fragment.commands(),
+                    transform * fragment.transform,
+                    out,
+                ),
+                cmd if transform == Affine::IDENTITY => out.push(self.convert_command(cmd)),
+                cmd => out.push(self.convert_command(cmd).apply_transform(transform)),
+            }
         }
     }
 
@@ -218,45 +368,157 @@ impl ResourceCollector {
             RenderCommand::BoxShadow(shadow) => {
                 SerializableRenderCommand::BoxShadow(shadow.clone())
             }
+            RenderCommand::Fragment(_) => {
+                unreachable!("fragments are flattened by convert_commands")
+            }
+        }
+    }
+
+    /// Process the collected resources, adding their metadata to `manifest`. Returns the font data, the images
+    /// (normalized to RGBA8) and any original encoded image bytes to store in the archive.
+    #[allow(clippy::type_complexity)]
+    fn into_resources(
+        self,
+        manifest: &mut ResourceManifest,
+    ) -> Result<(Vec<Blob<u8>>, Vec<ImageData>, Vec<Option<Blob<u8>>>), ArchiveError> {
+        // Normalize all images to RGBA8
+        let images: Vec<ImageData> = self
+            .images
+            .iter()
+            .map(|image| {
+                let data = convert_to_rgba(image)?;
+                Ok(ImageData {
+                    data,
+                    format: ImageFormat::Rgba8,
+                    alpha_type: image.alpha_type,
+                    width: image.width,
+                    height: image.height,
+                })
+            })
+            .collect::<Result<Vec<_>, ArchiveError>>()?;
+
+        // Add image metadata
+        let mut encoded_images = Vec::with_capacity(images.len());
+        for (idx, (original, normalized)) in self.images.iter().zip(images.iter()).enumerate() {
+            // Images with the same normalized pixels can still differ in format or alpha type, so their files are
+            // named by the deduplication key rather than by the stored content
+            let content_hash = &self.image_hashes[idx];
+            let data = normalized.data.data();
+            let encoded = self.encoded_sources.get(&original.data.id());
+            if let Some(encoded) = encoded {
+                check_encoded_image(original, encoded)?;
+            }
+            let hash = sha256_hex(encoded.map_or(data, |encoded| encoded.data.data()));
+            let extension = encoded.map_or("png", |encoded| encoded.encoding.extension());
+            let path = format!("images/{content_hash}.{extension}");
+
+            manifest.images.push(ImageMetadata {
+                entry: ResourceEntry {
+                    id: ResourceId(idx),
+                    kind: ResourceKind::Image,
+                    size: encoded.map_or(data.len(), |encoded| encoded.data.data().len()),
+                    sha256_hash: hash,
+                    path,
+                },
+                format: original.format,
+                alpha_type: original.alpha_type,
+                width: original.width,
+                height: original.height,
+                original_encoding: encoded.map(|encoded| encoded.encoding),
+            });
+            encoded_images.push(encoded.map(|encoded| encoded.data.clone()));
         }
+
+        // Add font metadata.
+        let mut fonts = Vec::new();
+        for (idx, result) in self.fonts.into_processed().enumerate() {
+            let font = result?;
+            manifest.fonts.push(FontMetadata {
+                entry: ResourceEntry {
+                    id: ResourceId(idx),
+                    kind: ResourceKind::Font,
+                    size: font.raw_size,
+                    sha256_hash: font.hash,
+                    path: font.path,
+                },
+            });
+            fonts.push(Blob::from(font.stored_data));
+        }
+
+        Ok((fonts, images, encoded_images))
     }
 }
 
+/// A hash of an image's pixels, format and dimensions, used to deduplicate images
+fn image_content_hash(image: &ImageData) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update([image.format.into_integer(), image.alpha_type.into_integer()]);
+    hasher.update(image.width.to_le_bytes());
+    hasher.update(image.height.to_le_bytes());
+    hasher.update(image.data.data());
+    hex_encode(&hasher.finalize())
+}
+
+/// Check that the original encoded bytes provided for an image can be decoded and match its dimensions
+fn check_encoded_image(image: &ImageData, encoded: &EncodedImage) -> Result<(), ArchiveError> {
+    let reader = image::ImageReader::with_format(
+        std::io::Cursor::new(encoded.data.data()),
+        encoded.encoding.image_format(),
+    );
+    let (width, height) = reader.into_dimensions()?;
+    if (width, height) != (image.width, image.height) {
+        return Err(ArchiveError::InvalidFormat(format!(
+            "Encoded image is {width}x{height}, but the image it was provided for is {}x{}",
+            image.width, image.height
+        )));
+    }
+    Ok(())
+}
+
 /// Reconstructs resources from deserialized data.
+///
+/// Resources which haven't been loaded are `None`, which allows frames of a [`SceneSequenceReader`] to load
+/// only the resources they use.
 struct ResourceReconstructor {
-    fonts: Vec<FontData>,
-    images: Vec<ImageData>,
+    fonts: Vec<Option<FontData>>,
+    images: Vec<Option<ImageData>>,
 }
 
 impl ResourceReconstructor {
     fn new(fonts: Vec<FontData>, images: Vec<ImageData>) -> Self {
-        Self { fonts, images }
+        Self {
+            fonts: fonts.into_iter().map(Some).collect(),
+            images: images.into_iter().map(Some).collect(),
+        }
     }
 
     fn get_font(&self, id: ResourceId) -> Result<&FontData, ArchiveError> {
         self.fonts
             .get(id.0)
+            .and_then(Option::as_ref)
             .ok_or(ArchiveError::ResourceNotFound(id))
     }
 
     fn get_image(&self, id: ResourceId) -> Result<&ImageData, ArchiveError> {
         self.images
             .get(id.0)
+            .and_then(Option::as_ref)
             .ok_or(ArchiveError::ResourceNotFound(id))
     }
 
-    /// Convert a [`SerializableBrush`] back to a [`Brush`].
-    fn convert_brush(&self, brush: &SerializableBrush) -> Result<Brush, ArchiveError> {
+    /// Convert a [`SerializableBrush`] back to a [`Paint`].
+    fn convert_brush(&self, brush: &SerializableBrush) -> Result<Paint, ArchiveError> {
         Ok(match brush {
-            Brush::Solid(color) => Brush::Solid(*color),
-            Brush::Gradient(gradient) => Brush::Gradient(gradient.clone()),
-            Brush::Image(image_brush) => {
+            Paint::Solid(color) => Paint::Solid(*color),
+            Paint::Gradient(gradient) => Paint::Gradient(gradient.clone()),
+            Paint::Image(image_brush) => {
                 let image = self.get_image(image_brush.image)?;
-                Brush::Image(ImageBrush {
+                Paint::Image(ImageBrush {
                     image: image.clone(),
                     sampler: image_brush.sampler,
                 })
             }
+            Paint::Custom(custom) => Paint::Custom(custom.clone()),
         })
     }
 
@@ -305,6 +567,9 @@ impl ResourceReconstructor {
             SerializableRenderCommand::BoxShadow(shadow) => {
                 RenderCommand::BoxShadow(shadow.clone())
             }
+            SerializableRenderCommand::Fragment(fragment) => {
+                RenderCommand::Fragment(fragment.clone())
+            }
         })
     }
 }
@@ -337,8 +602,8 @@ fn encode_rgba_to_png(rgba_data: &[u8], width: u32, height: u32) -> Result<Vec<u
     Ok(png_data)
 }
 
-fn decode_png_to_rgba(png_data: &[u8]) -> Result<Vec<u8>, ArchiveError> {
-    let img = image::load_from_memory_with_format(png_data, image::ImageFormat::Png)?;
+fn decode_to_rgba(data: &[u8], encoding: ImageEncoding) -> Result<Vec<u8>, ArchiveError> {
+    let img = image::load_from_memory_with_format(data, encoding.image_format())?;
     Ok(img.into_rgba8().into_raw())
 }
 
@@ -379,77 +644,209 @@ fn convert_to_rgba(image: &ImageData) -> Result<Blob<u8>, ArchiveError> {
     }
 }
 
-impl SceneArchive {
-    /// Create a new SceneArchive from a recorded Scene.
-    pub fn from_scene(scene: &Scene, config: &SerializeConfig) -> Result<Self, ArchiveError> {
-        let mut manifest = ResourceManifest::new(scene.tolerance);
-        let mut collector = ResourceCollector::new(config.clone());
+/// Convert an image stored in an archive (as RGBA8) back to its original format.
+fn restore_image(image: &ImageData, meta: &ImageMetadata) -> Result<ImageData, ArchiveError> {
+    let data = convert_from_rgba(&image.data, meta.format)?;
+    Ok(ImageData {
+        data,
+        format: meta.format,
+        alpha_type: image.alpha_type,
+        width: image.width,
+        height: image.height,
+    })
+}
 
-        let commands: Vec<_> = scene
-            .commands
-            .iter()
-            .map(|cmd| collector.convert_command(cmd))
-            .collect();
+/// Decode font data stored in an archive, which may be WOFF2-compressed.
+fn decode_font(font_blob: &Blob<u8>) -> Result<FontData, ArchiveError> {
+    let data = font_blob.data();
+    let ttf_data = if data.starts_with(b"wOF2") {
+        wuff::decompress_woff2(data)
+            .map_err(|e| ArchiveError::FontProcessing(format!("WOFF2 decoding failed: {e}")))?
+    } else {
+        data.to_vec()
+    };
+    Ok(FontData::new(Blob::from(ttf_data), 0))
+}
 
-        // Normalize all images to RGBA8
-        let images: Vec<ImageData> = collector
-            .images
-            .iter()
-            .map(|image| {
-                let data = convert_to_rgba(image)?;
-                Ok(ImageData {
-                    data,
-                    format: ImageFormat::Rgba8,
-                    alpha_type: image.alpha_type,
-                    width: image.width,
-                    height: image.height,
-                })
-            })
-            .collect::<Result<Vec<_>, ArchiveError>>()?;
+fn write_manifest<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    manifest: &ResourceManifest,
+) -> Result<(), ArchiveError> {
+    zip.start_file("resources.json", SimpleFileOptions::default())?;
+    let manifest_json = serde_json::to_string_pretty(manifest)?;
+    zip.write_all(manifest_json.as_bytes())?;
+    Ok(())
+}
 
-        // Add image metadata
-        for (idx, (original, normalized)) in collector.images.iter().zip(images.iter()).enumerate()
-        {
-            let data = normalized.data.data();
-            let hash = sha256_hex(data);
-            let path = format!("images/{}.png", hash);
+fn write_commands<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    path: &str,
+    encoding: CommandEncoding,
+    commands: &[SerializableRenderCommand],
+) -> Result<(), ArchiveError> {
+    zip.start_file(path, SimpleFileOptions::default())?;
+    match encoding {
+        CommandEncoding::Json => {
+            let commands_json = json_formatter::to_json_depth_limited(&commands, 3)?;
+            zip.write_all(commands_json.as_bytes())?;
+        }
+        CommandEncoding::Binary => {
+            zip.write_all(&binary::encode_commands(commands)?)?;
+        }
+    }
+    Ok(())
+}
 
-            manifest.images.push(ImageMetadata {
-                entry: ResourceEntry {
-                    id: ResourceId(idx),
-                    kind: ResourceKind::Image,
-                    size: data.len(),
-                    sha256_hash: hash,
-                    path,
-                },
-                format: original.format,
-                alpha_type: original.alpha_type,
-                width: original.width,
-                height: original.height,
-            });
+/// Write image files (as PNG, or their original encoded bytes) and font files
+fn write_resources<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    manifest: &ResourceManifest,
+    fonts: &[Blob<u8>],
+    images: &[ImageData],
+    encoded_images: &[Option<Blob<u8>>],
+) -> Result<(), ArchiveError> {
+    let options = SimpleFileOptions::default();
+
+    for (idx, image) in images.iter().enumerate() {
+        let path = &manifest.images[idx].entry.path;
+        zip.start_file(path, options)?;
+        match encoded_images.get(idx) {
+            Some(Some(encoded)) => zip.write_all(encoded.data())?,
+            _ => {
+                let png_data = encode_rgba_to_png(image.data.data(), image.width, image.height)?;
+                zip.write_all(&png_data)?;
+            }
         }
+    }
 
-        // Add font metadata.
-        let mut fonts = Vec::new();
-        for (idx, result) in collector.fonts.into_processed().enumerate() {
-            let font = result?;
-            manifest.fonts.push(FontMetadata {
-                entry: ResourceEntry {
-                    id: ResourceId(idx),
-                    kind: ResourceKind::Font,
-                    size: font.raw_size,
-                    sha256_hash: font.hash,
-                    path: font.path,
-                },
-            });
-            fonts.push(Blob::from(font.stored_data));
+    for (idx, font_data) in fonts.iter().enumerate() {
+        let path = &manifest.fonts[idx].entry.path;
+        zip.start_file(path, options)?;
+        zip.write_all(font_data.data())?;
+    }
+
+    Ok(())
+}
+
+/// Read resources.json and check that its version is supported
+fn read_manifest<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+) -> Result<ResourceManifest, ArchiveError> {
+    let manifest: ResourceManifest = {
+        let mut file = zip.by_name("resources.json")?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        serde_json::from_str(&contents)?
+    };
+
+    if !(ResourceManifest::MIN_SUPPORTED_VERSION..=ResourceManifest::CURRENT_VERSION)
+        .contains(&manifest.version)
+    {
+        return Err(ArchiveError::UnsupportedVersion(manifest.version));
+    }
+
+    Ok(manifest)
+}
+
+fn read_commands<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    path: &str,
+    encoding: CommandEncoding,
+) -> Result<Vec<SerializableRenderCommand>, ArchiveError> {
+    let mut file = zip.by_name(path)?;
+    Ok(match encoding {
+        CommandEncoding::Json => {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            serde_json::from_str(&contents)?
         }
+        CommandEncoding::Binary => {
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+            binary::decode_commands(&contents)?
+        }
+    })
+}
+
+/// Read an image, verifying its hash. Also returns the file's contents if they are the image's original encoded
+/// bytes.
+fn read_image<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    meta: &ImageMetadata,
+) -> Result<(ImageData, Option<Blob<u8>>), ArchiveError> {
+    let mut file = zip.by_name(&meta.entry.path)?;
+    let mut file_data = Vec::new();
+    file.read_to_end(&mut file_data)?;
+    let encoding = meta.original_encoding.unwrap_or(ImageEncoding::Png);
+    let rgba_data = decode_to_rgba(&file_data, encoding)?;
+
+    // Verify hash (of the file itself for original encoded bytes, or of the pixels for re-encoded PNGs)
+    let hash = match meta.original_encoding {
+        Some(_) => sha256_hex(&file_data),
+        None => sha256_hex(&rgba_data),
+    };
+    if hash != meta.entry.sha256_hash {
+        return Err(ArchiveError::InvalidFormat(format!(
+            "Hash mismatch for {}: expected {}, got {}",
+            meta.entry.path, meta.entry.sha256_hash, hash
+        )));
+    }
+    if rgba_data.len() != meta.width as usize * meta.height as usize * 4 {
+        return Err(ArchiveError::InvalidFormat(format!(
+            "Image {} doesn't match its dimensions ({}x{})",
+            meta.entry.path, meta.width, meta.height
+        )));
+    }
+
+    let image = ImageData {
+        data: Blob::from(rgba_data),
+        format: ImageFormat::Rgba8,
+        alpha_type: meta.alpha_type,
+        width: meta.width,
+        height: meta.height,
+    };
+    let encoded = meta.original_encoding.map(|_| Blob::from(file_data));
+    Ok((image, encoded))
+}
+
+/// Read font data (which may be WOFF2-compressed or raw TTF/OTF), verifying its hash
+fn read_font<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    meta: &FontMetadata,
+) -> Result<Blob<u8>, ArchiveError> {
+    let mut file = zip.by_name(&meta.entry.path)?;
+    let mut raw_data = Vec::new();
+    file.read_to_end(&mut raw_data)?;
+
+    // Verify hash
+    let hash = sha256_hex(&raw_data);
+    if hash != meta.entry.sha256_hash {
+        return Err(ArchiveError::InvalidFormat(format!(
+            "Hash mismatch for {}: expected {}, got {}",
+            meta.entry.path, meta.entry.sha256_hash, hash
+        )));
+    }
+    Ok(Blob::from(raw_data))
+}
+
+impl SceneArchive {
+    /// Create a new SceneArchive from a recorded Scene.
+    pub fn from_scene(scene: &Scene, config: &SerializeConfig) -> Result<Self, ArchiveError> {
+        let mut manifest = ResourceManifest::new(scene.tolerance);
+        manifest.command_encoding = config.command_encoding;
+        let mut collector = ResourceCollector::new(config.clone());
+
+        let mut commands = Vec::with_capacity(scene.commands.len());
+        collector.convert_commands(&scene.commands, Affine::IDENTITY, &mut commands);
+
+        let (fonts, images, encoded_images) = collector.into_resources(&mut manifest)?;
 
         Ok(Self {
             manifest,
             commands,
             fonts,
             images,
+            encoded_images,
         })
     }
 
@@ -460,33 +857,14 @@ impl SceneArchive {
             .images
             .iter()
             .zip(self.manifest.images.iter())
-            .map(|(image, meta)| {
-                let data = convert_from_rgba(&image.data, meta.format)?;
-                Ok(ImageData {
-                    data,
-                    format: meta.format,
-                    alpha_type: image.alpha_type,
-                    width: image.width,
-                    height: image.height,
-                })
-            })
+            .map(|(image, meta)| restore_image(image, meta))
             .collect::<Result<Vec<_>, ArchiveError>>()?;
 
         // Decode fonts.
         let fonts_ttf: Vec<FontData> = self
             .fonts
             .iter()
-            .map(|font_blob| {
-                let data = font_blob.data();
-                let ttf_data = if data.starts_with(b"wOF2") {
-                    wuff::decompress_woff2(data).map_err(|e| {
-                        ArchiveError::FontProcessing(format!("WOFF2 decoding failed: {e}"))
-                    })?
-                } else {
-                    data.to_vec()
-                };
-                Ok(FontData::new(Blob::from(ttf_data), 0))
-            })
+            .map(decode_font)
             .collect::<Result<Vec<_>, ArchiveError>>()?;
 
         let reconstructor = ResourceReconstructor::new(fonts_ttf, images);
@@ -506,36 +884,20 @@ impl SceneArchive {
     /// Serialize the archive to a zip file.
     pub fn serialize<W: Write + Seek>(&self, writer: W) -> Result<(), ArchiveError> {
         let mut zip = ZipWriter::new(writer);
-        let options = SimpleFileOptions::default();
-
-        // Write resources.json
-        {
-            zip.start_file("resources.json", options)?;
-            let manifest_json = serde_json::to_string_pretty(&self.manifest)?;
-            zip.write_all(manifest_json.as_bytes())?;
-        }
-
-        // Write draw_commands.json
-        {
-            zip.start_file("draw_commands.json", options)?;
-            let commands_json = json_formatter::to_json_depth_limited(&self.commands, 3)?;
-            zip.write_all(commands_json.as_bytes())?;
-        }
-
-        // Write image files as PNG
-        for (idx, image) in self.images.iter().enumerate() {
-            let path = &self.manifest.images[idx].entry.path;
-            let png_data = encode_rgba_to_png(image.data.data(), image.width, image.height)?;
-            zip.start_file(path, options)?;
-            zip.write_all(&png_data)?;
-        }
-
-        // Write font files
-        for (idx, font_data) in self.fonts.iter().enumerate() {
-            let path = &self.manifest.fonts[idx].entry.path;
-            zip.start_file(path, options)?;
-            zip.write_all(font_data.data())?;
-        }
+        write_manifest(&mut zip, &self.manifest)?;
+        write_commands(
+            &mut zip,
+            self.manifest.command_encoding.file_name(),
+            self.manifest.command_encoding,
+            &self.commands,
+        )?;
+        write_resources(
+            &mut zip,
+            &self.manifest,
+            &self.fonts,
+            &self.images,
+            &self.encoded_images,
+        )?;
 
         zip.finish()?;
         Ok(())
@@ -545,76 +907,36 @@ impl SceneArchive {
     pub fn deserialize<R: Read + Seek>(reader: R) -> Result<Self, ArchiveError> {
         let mut zip = ZipArchive::new(reader)?;
 
-        // Read resources.json
-        let manifest: ResourceManifest = {
-            let mut file = zip.by_name("resources.json")?;
-            let mut contents = String::new();
-            file.read_to_string(&mut contents)?;
-            serde_json::from_str(&contents)?
-        };
+        let mut manifest = read_manifest(&mut zip)?;
 
-        // Check version
-        if manifest.version != ResourceManifest::CURRENT_VERSION {
-            return Err(ArchiveError::UnsupportedVersion(manifest.version));
-        }
+        // Read draw_commands.bin or draw_commands.json, whichever is present
+        manifest.command_encoding = [CommandEncoding::Binary, CommandEncoding::Json]
+            .into_iter()
+            .find(|encoding| zip.index_for_name(encoding.file_name()).is_some())
+            .ok_or_else(|| ArchiveError::InvalidFormat("Missing draw commands".to_string()))?;
+        let commands = read_commands(
+            &mut zip,
+            manifest.command_encoding.file_name(),
+            manifest.command_encoding,
+        )?;
 
-        // Read draw_commands.json
-        let commands: Vec<SerializableRenderCommand> = {
-            let mut file = zip.by_name("draw_commands.json")?;
-            let mut contents = String::new();
-            file.read_to_string(&mut contents)?;
-            serde_json::from_str(&contents)?
-        };
-
-        // Read images
-        let mut images = Vec::with_capacity(manifest.images.len());
-        for meta in &manifest.images {
-            let mut file = zip.by_name(&meta.entry.path)?;
-            let mut png_data = Vec::new();
-            file.read_to_end(&mut png_data)?;
-            let rgba_data = decode_png_to_rgba(&png_data)?;
-
-            // Verify hash
-            let hash = sha256_hex(&rgba_data);
-            if hash != meta.entry.sha256_hash {
-                return Err(ArchiveError::InvalidFormat(format!(
-                    "Hash mismatch for {}: expected {}, got {}",
-                    meta.entry.path, meta.entry.sha256_hash, hash
-                )));
-            }
-
-            images.push(ImageData {
-                data: Blob::from(rgba_data),
-                format: ImageFormat::Rgba8,
-                alpha_type: meta.alpha_type,
-                width: meta.width,
-                height: meta.height,
-            });
-        }
-
-        // Read fonts (may be WOFF2-compressed or raw TTF/OTF)
-        let mut fonts: Vec<Blob<u8>> = Vec::with_capacity(manifest.fonts.len());
-        for meta in &manifest.fonts {
-            let mut file = zip.by_name(&meta.entry.path)?;
-            let mut raw_data = Vec::new();
-            file.read_to_end(&mut raw_data)?;
-
-            // Verify hash
-            let hash = sha256_hex(&raw_data);
-            if hash != meta.entry.sha256_hash {
-                return Err(ArchiveError::InvalidFormat(format!(
-                    "Hash mismatch for {}: expected {}, got {}",
-                    meta.entry.path, meta.entry.sha256_hash, hash
-                )));
-            }
-            fonts.push(Blob::from(raw_data));
-        }
+        let (images, encoded_images) = manifest
+            .images
+            .iter()
+            .map(|meta| read_image(&mut zip, meta))
+            .collect::<Result<(Vec<_>, Vec<_>), ArchiveError>>()?;
+        let fonts = manifest
+            .fonts
+            .iter()
+            .map(|meta| read_font(&mut zip, meta))
+            .collect::<Result<Vec<_>, ArchiveError>>()?;
 
         Ok(Self {
             manifest,
             commands,
             fonts,
             images,
+            encoded_images,
         })
     }
 }
@@ -623,6 +945,9 @@ impl SceneArchive {
 pub struct SerializeConfig {
     subset_fonts: bool,
     woff2_fonts: bool,
+    command_encoding: CommandEncoding,
+    /// Original encoded bytes for images, keyed by the Blob ID of the image's pixels
+    encoded_images: HashMap<u64, EncodedImage>,
 }
 
 impl SerializeConfig {
@@ -641,6 +966,30 @@ impl SerializeConfig {
         self.woff2_fonts = woff2_fonts;
         self
     }
+
+    /// Choose how draw commands are encoded. Defaults to [`CommandEncoding::Json`].
+    pub fn with_command_encoding(mut self, command_encoding: CommandEncoding) -> Self {
+        self.command_encoding = command_encoding;
+        self
+    }
+
+    /// Store `data` (the original encoded bytes of `image`, such as a JPEG photo) in the archive instead of
+    /// re-encoding the image's pixels as a PNG. Applies to every image sharing the same pixel [`Blob`].
+    pub fn with_encoded_image(
+        mut self,
+        image: &ImageData,
+        encoding: ImageEncoding,
+        data: impl Into<Blob<u8>>,
+    ) -> Self {
+        self.encoded_images.insert(
+            image.data.id(),
+            EncodedImage {
+                encoding,
+                data: data.into(),
+            },
+        );
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -653,6 +1002,7 @@ pub enum ArchiveError {
     InvalidFormat(String),
     ResourceNotFound(ResourceId),
     UnsupportedVersion(u32),
+    FrameNotFound(usize),
 }
 
 impl std::fmt::Display for ArchiveError {
@@ -666,6 +1016,7 @@ impl std::fmt::Display for ArchiveError {
             ArchiveError::InvalidFormat(msg) => write!(f, "Invalid format: {}", msg),
             ArchiveError::ResourceNotFound(id) => write!(f, "Resource not found: {:?}", id),
             ArchiveError::UnsupportedVersion(v) => write!(f, "Unsupported version: {}", v),
+            ArchiveError::FrameNotFound(index) => write!(f, "Frame not found: {}", index),
         }
     }
 }
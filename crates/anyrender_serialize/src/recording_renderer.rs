@@ -0,0 +1,313 @@
+//! A [`WindowRenderer`] wrapper which captures rendered frames into a sequence archive
+
+use std::collections::VecDeque;
+use std::io::{Seek, Write};
+use std::sync::Arc;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use anyrender::recording::Scene;
+use anyrender::{
+    DynPaintScene, PaintScene, RenderError, TeePaintScene, WindowHandle, WindowRenderer,
+};
+
+use crate::{ArchiveError, SceneSequenceWriter, SerializeConfig};
+
+/// The number of frames which can be queued for the capture thread before rendering waits for it to catch up
+const MAX_QUEUED_FRAMES: usize = 8;
+
+/// A frame sent to the capture thread
+struct CapturedFrame {
+    scene: Scene,
+    timestamp: Duration,
+    width: u32,
+    height: u32,
+}
+
+/// An active capture: a background thread which writes frames into a [`SceneSequenceWriter`]
+struct Capture {
+    start: Instant,
+    sender: Option<SyncSender<CapturedFrame>>,
+    worker: Option<JoinHandle<Result<(), ArchiveError>>>,
+}
+
+impl Capture {
+    fn start<W: Write + Seek + Send + 'static>(
+        writer: W,
+        config: SerializeConfig,
+        retained_duration: Option<Duration>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<CapturedFrame>(MAX_QUEUED_FRAMES);
+        let worker = thread::spawn(move || {
+            let mut sequence = SceneSequenceWriter::new(writer, &config);
+            let Some(retained_duration) = retained_duration else {
+                // Stream every frame into the archive as soon as it arrives
+                for frame in receiver {
+                    sequence.append_frame(
+                        &frame.scene,
+                        frame.timestamp,
+                        frame.width,
+                        frame.height,
+                    )?;
+                }
+                sequence.finish()?;
+                return Ok(());
+            };
+
+            // Only keep the most recent frames, which are written once the capture is stopped
+            let mut frames: VecDeque<CapturedFrame> = VecDeque::new();
+            for frame in receiver {
+                let cutoff = frame.timestamp.saturating_sub(retained_duration);
+                frames.push_back(frame);
+                while frames.front().is_some_and(|frame| frame.timestamp < cutoff) {
+                    frames.pop_front();
+                }
+            }
+            let start = frames
+                .front()
+                .map_or(Duration::ZERO, |frame| frame.timestamp);
+            for frame in frames {
+                sequence.append_frame(
+                    &frame.scene,
+                    frame.timestamp - start,
+                    frame.width,
+                    frame.height,
+                )?;
+            }
+            sequence.finish()?;
+            Ok(())
+        });
+
+        Self {
+            start: Instant::now(),
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    fn send(&self, scene: Scene, width: u32, height: u32) {
+        let frame = CapturedFrame {
+            scene,
+            timestamp: self.start.elapsed(),
+            width,
+            height,
+        };
+        // If the capture thread has stopped early, its error is reported when the capture is stopped
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(frame);
+        }
+    }
+
+    /// Wait for the capture thread to write the remaining frames and finish the archive
+    fn finish(&mut self) -> Result<(), ArchiveError> {
+        self.sender = None;
+        match self.worker.take().map(JoinHandle::join) {
+            None | Some(Ok(Ok(()))) => Ok(()),
+            Some(Ok(Err(err))) => Err(err),
+            Some(Err(_)) => Err(ArchiveError::Io(std::io::Error::other(
+                "capture thread panicked",
+            ))),
+        }
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Draw a frame into `painter`, and also into `recording` if it is present
+fn draw_frame<P: PaintScene>(
+    painter: &mut P,
+    recording: Option<&mut Scene>,
+    draw_fn: impl for<'a> FnOnce(&mut &'a mut (dyn DynPaintScene + 'a)),
+) {
+    match recording {
+        Some(recording) => {
+            let mut tee = TeePaintScene::new(painter, recording);
+            draw_fn(&mut (&mut tee as &mut dyn DynPaintScene));
+        }
+        None => draw_fn(&mut (painter as &mut dyn DynPaintScene)),
+    }
+}
+
+/// A [`WindowRenderer`] that passes rendering through to another renderer, and can capture the rendered frames
+/// into a sequence archive (see [`SceneSequenceWriter`]) for later inspection or replay.
+///
+/// While a capture is active, the draw commands of each frame are recorded into a [`Scene`] alongside being
+/// rendered, and sent to a background thread which writes them into the archive. This works with any backend,
+/// since frames are captured at the [`PaintScene`] level.
+///
+/// By default every frame is written as soon as it is rendered. At most a few frames are queued for the
+/// background thread: if it can't keep up, rendering waits for it rather than buffering an unbounded number of
+/// frames. With
+/// [`with_retained_duration`](Self::with_retained_duration), only the frames rendered within that duration of the
+/// end of the capture are written, which allows the last few seconds of rendering to be saved when a problem is
+/// noticed:
+///
+/// ```ignore
+/// let mut renderer = RecordingWindowRenderer::new(VelloWindowRenderer::new())
+///     .with_retained_duration(Duration::from_secs(10));
+/// renderer.start_capture(File::create("capture.zip")?)?;
+/// // ...render frames...
+/// // When the user presses the "report a bug" button:
+/// renderer.stop_capture()?;
+/// ```
+///
+/// The scene painter is a `&mut dyn DynPaintScene` whether or not a capture is active, since the painter type
+/// can't depend on whether frames are being recorded. This means that every draw call is dynamically dispatched,
+/// non-primitive shapes are converted into paths before they reach the inner renderer's painter, and draw
+/// functions can't use the inner renderer's concrete painter type. Applications which don't need to capture
+/// frames should use the inner renderer directly to avoid this overhead.
+pub struct RecordingWindowRenderer<R: WindowRenderer + 'static> {
+    inner: R,
+    config: SerializeConfig,
+    retained_duration: Option<Duration>,
+    width: u32,
+    height: u32,
+    capture: Option<Capture>,
+}
+
+impl<R: WindowRenderer + 'static> RecordingWindowRenderer<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            config: SerializeConfig::new(),
+            retained_duration: None,
+            width: 0,
+            height: 0,
+            capture: None,
+        }
+    }
+
+    /// Set the config used to write archives. Applies to captures started after this is called.
+    pub fn with_config(mut self, config: SerializeConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Only write the frames rendered within `duration` of the end of each capture, rather than every frame.
+    /// Applies to captures started after this is called.
+    pub fn with_retained_duration(mut self, duration: Duration) -> Self {
+        self.retained_duration = Some(duration);
+        self
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Stop any active capture (waiting for it to finish) and unwrap the inner renderer
+    pub fn into_inner(mut self) -> R {
+        self.capture = None;
+        self.inner
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// Start capturing frames into an archive written to `writer`.
+    ///
+    /// If a capture is already active it is stopped first, and the result of finishing it is returned.
+    pub fn start_capture<W: Write + Seek + Send + 'static>(
+        &mut self,
+        writer: W,
+    ) -> Result<(), ArchiveError> {
+        let result = self.stop_capture();
+        self.capture = Some(Capture::start(
+            writer,
+            self.config.clone(),
+            self.retained_duration,
+        ));
+        result
+    }
+
+    /// Stop capturing frames, waiting for the background thread to finish writing the archive.
+    ///
+    /// Returns any error encountered while writing the archive. Does nothing if no capture is active.
+    pub fn stop_capture(&mut self) -> Result<(), ArchiveError> {
+        match self.capture.take() {
+            Some(mut capture) => capture.finish(),
+            None => Ok(()),
+        }
+    }
+
+    fn send_frame(&self, recording: Option<Scene>) {
+        if let (Some(capture), Some(scene)) = (&self.capture, recording) {
+            capture.send(scene, self.width, self.height);
+        }
+    }
+}
+
+impl<R: WindowRenderer + 'static> WindowRenderer for RecordingWindowRenderer<R> {
+    type ScenePainter<'a>
+        = &'a mut (dyn DynPaintScene + 'a)
+    where
+        Self: 'a;
+
+    fn resume(&mut self, window: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.inner.resume(window, width, height);
+    }
+
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
+        self.width = width;
+        self.height = height;
+        self.inner.try_resume(window, width, height)
+    }
+
+    fn suspend(&mut self) {
+        self.inner.suspend();
+    }
+
+    fn is_active(&self) -> bool {
+        self.inner.is_active()
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.inner.set_size(width, height);
+    }
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        // The inner renderer may skip the frame (for example while the window is minimized), in which case
+        // nothing is captured
+        let capturing = self.capture.is_some();
+        let mut recording = None;
+        self.inner.render(|painter| {
+            let mut scene = capturing.then(Scene::new);
+            draw_frame(painter, scene.as_mut(), draw_fn);
+            recording = scene;
+        });
+        self.send_frame(recording);
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        let capturing = self.capture.is_some();
+        let mut recording = None;
+        self.inner.try_render(|painter| {
+            let mut scene = capturing.then(Scene::new);
+            draw_frame(painter, scene.as_mut(), draw_fn);
+            recording = scene;
+        })?;
+        self.send_frame(recording);
+        Ok(())
+    }
+}
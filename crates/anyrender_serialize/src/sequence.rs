@@ -0,0 +1,265 @@
+//! Archives containing a sequence of frames which share one pool of resources.
+//!
+//! A sequence archive uses the same layout as a [`SceneArchive`](crate::SceneArchive), except that the draw
+//! commands of each frame are stored in a separate file and listed in `frames.json`:
+//!
+//! - `resources.json` - Metadata mapping resource files to IDs (shared by every frame)
+//! - `frames.json` - The [`FrameMetadata`] of each frame, in order
+//! - `frames/<index>.{json,bin}` - The draw commands of each frame, encoded as configured by
+//!   [`SerializeConfig::with_command_encoding`]
+//! - `images/` and `fonts/` - Deduplicated resources used by any frame
+//!
+//! Frames can be appended one at a time with [`SceneSequenceWriter`], and read back individually with
+//! [`SceneSequenceReader`], which only loads the resources that the requested frame uses. A captured sequence can
+//! be replayed through any [`ImageRenderer`](anyrender::ImageRenderer):
+//!
+//! ```ignore
+//! let mut reader = SceneSequenceReader::open(File::open("capture.zip")?)?;
+//! let mut renderer = VelloCpuImageRenderer::new(1, 1);
+//! for index in 0..reader.frame_count() {
+//!     let FrameMetadata { width, height, .. } = *reader.frame(index).unwrap();
+//!     let scene = reader.read_frame(index)?;
+//!     renderer.resize(width, height);
+//!     renderer.render_to_vec(|painter| painter.append_scene(scene, Affine::IDENTITY), &mut buffer);
+//! }
+//! ```
+
+use std::io::{Read, Seek, Write};
+use std::time::Duration;
+
+use anyrender::Paint;
+use anyrender::recording::Scene;
+use peniko::Blob;
+use peniko::kurbo::Affine;
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::{
+    ArchiveError, CommandEncoding, ResourceCollector, ResourceId, ResourceManifest,
+    ResourceReconstructor, SerializableRenderCommand, SerializeConfig, decode_font, read_commands,
+    read_font, read_image, read_manifest, restore_image, write_commands, write_manifest,
+    write_resources,
+};
+
+/// Metadata for a frame in a sequence archive.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FrameMetadata {
+    /// The time at which the frame was captured, relative to the start of the sequence
+    pub timestamp: Duration,
+    /// The width of the viewport the frame was drawn into
+    pub width: u32,
+    /// The height of the viewport the frame was drawn into
+    pub height: u32,
+    /// Scene tolerance (used for path flattening)
+    pub tolerance: f64,
+    /// Path to the frame's draw commands within the archive
+    pub path: String,
+}
+
+/// Writes frames to a sequence archive as they are captured.
+///
+/// The draw commands of each frame are written immediately. Resources are deduplicated across frames and written
+/// (along with the manifest) by [`finish`](Self::finish), so fonts are only subsetted once every frame is known.
+pub struct SceneSequenceWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    collector: ResourceCollector,
+    command_encoding: CommandEncoding,
+    frames: Vec<FrameMetadata>,
+}
+
+impl<W: Write + Seek> SceneSequenceWriter<W> {
+    pub fn new(writer: W, config: &SerializeConfig) -> Self {
+        Self {
+            zip: ZipWriter::new(writer),
+            collector: ResourceCollector::new(config.clone()),
+            command_encoding: config.command_encoding,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Append a frame, drawn into a viewport of `width` by `height` at `timestamp`
+    pub fn append_frame(
+        &mut self,
+        scene: &Scene,
+        timestamp: Duration,
+        width: u32,
+        height: u32,
+    ) -> Result<(), ArchiveError> {
+        let mut commands = Vec::with_capacity(scene.commands.len());
+        self.collector
+            .convert_commands(&scene.commands, Affine::IDENTITY, &mut commands);
+
+        let path = format!(
+            "frames/{}.{}",
+            self.frames.len(),
+            self.command_encoding.extension()
+        );
+        write_commands(&mut self.zip, &path, self.command_encoding, &commands)?;
+        self.frames.push(FrameMetadata {
+            timestamp,
+            width,
+            height,
+            tolerance: scene.tolerance,
+            path,
+        });
+        Ok(())
+    }
+
+    /// The number of frames appended so far
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Write the shared resources and the manifests, returning the underlying writer
+    pub fn finish(mut self) -> Result<W, ArchiveError> {
+        // The manifest's tolerance isn't used by sequences (each frame has its own), so use the first frame's
+        let tolerance = self
+            .frames
+            .first()
+            .map_or(Scene::default().tolerance, |frame| frame.tolerance);
+        let mut manifest = ResourceManifest::new(tolerance);
+        manifest.command_encoding = self.command_encoding;
+        let (fonts, images, encoded_images) = self.collector.into_resources(&mut manifest)?;
+
+        write_manifest(&mut self.zip, &manifest)?;
+        self.zip
+            .start_file("frames.json", SimpleFileOptions::default())?;
+        self.zip
+            .write_all(serde_json::to_string_pretty(&self.frames)?.as_bytes())?;
+        write_resources(&mut self.zip, &manifest, &fonts, &images, &encoded_images)?;
+
+        Ok(self.zip.finish()?)
+    }
+}
+
+/// Reads individual frames from a sequence archive.
+///
+/// Only the manifests are read when the archive is opened. Each frame's draw commands are read on demand, along
+/// with any resources that haven't been used by a previously read frame.
+pub struct SceneSequenceReader<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    manifest: ResourceManifest,
+    frames: Vec<FrameMetadata>,
+    resources: ResourceReconstructor,
+    /// The original encoded bytes of each loaded image, if they are stored in the archive
+    encoded_images: Vec<Option<Blob<u8>>>,
+}
+
+impl<R: Read + Seek> SceneSequenceReader<R> {
+    pub fn open(reader: R) -> Result<Self, ArchiveError> {
+        let mut zip = ZipArchive::new(reader)?;
+        let manifest = read_manifest(&mut zip)?;
+        let frames: Vec<FrameMetadata> = {
+            let mut file = zip.by_name("frames.json")?;
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            serde_json::from_str(&contents)?
+        };
+        let resources = ResourceReconstructor {
+            fonts: vec![None; manifest.fonts.len()],
+            images: vec![None; manifest.images.len()],
+        };
+        let encoded_images = vec![None; manifest.images.len()];
+        Ok(Self {
+            zip,
+            manifest,
+            frames,
+            resources,
+            encoded_images,
+        })
+    }
+
+    pub fn manifest(&self) -> &ResourceManifest {
+        &self.manifest
+    }
+
+    pub fn frames(&self) -> &[FrameMetadata] {
+        &self.frames
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&FrameMetadata> {
+        self.frames.get(index)
+    }
+
+    /// Read the draw commands of a frame without loading the resources they reference
+    pub fn read_commands(
+        &mut self,
+        index: usize,
+    ) -> Result<Vec<SerializableRenderCommand>, ArchiveError> {
+        let frame = self
+            .frames
+            .get(index)
+            .ok_or(ArchiveError::FrameNotFound(index))?;
+        read_commands(&mut self.zip, &frame.path, self.manifest.command_encoding)
+    }
+
+    /// The original encoded bytes of an image, if they are stored in the archive (see
+    /// [`ImageMetadata::original_encoding`](crate::ImageMetadata::original_encoding)) and the image has been
+    /// loaded by a previously read frame
+    pub fn encoded_image(&self, id: ResourceId) -> Option<&Blob<u8>> {
+        self.encoded_images.get(id.0)?.as_ref()
+    }
+
+    /// Read a frame, loading any resources it uses
+    pub fn read_frame(&mut self, index: usize) -> Result<Scene, ArchiveError> {
+        let commands = self.read_commands(index)?;
+        for cmd in &commands {
+            let (brush, font) = match cmd {
+                SerializableRenderCommand::Stroke(stroke) => (&stroke.brush, None),
+                SerializableRenderCommand::Fill(fill) => (&fill.brush, None),
+                SerializableRenderCommand::GlyphRun(glyph_run) => {
+                    (&glyph_run.brush, Some(glyph_run.font_data.resource_id))
+                }
+                _ => continue,
+            };
+            if let Paint::Image(image_brush) = brush {
+                self.load_image(image_brush.image)?;
+            }
+            if let Some(id) = font {
+                self.load_font(id)?;
+            }
+        }
+
+        let commands: Result<Vec<_>, _> = commands
+            .iter()
+            .map(|cmd| self.resources.convert_command(cmd))
+            .collect();
+        Ok(Scene {
+            tolerance: self.frames[index].tolerance,
+            commands: commands?,
+        })
+    }
+
+    fn load_image(&mut self, id: ResourceId) -> Result<(), ArchiveError> {
+        let (Some(slot), Some(meta)) = (
+            self.resources.images.get_mut(id.0),
+            self.manifest.images.get(id.0),
+        ) else {
+            return Err(ArchiveError::ResourceNotFound(id));
+        };
+        if slot.is_none() {
+            let (image, encoded) = read_image(&mut self.zip, meta)?;
+            *slot = Some(restore_image(&image, meta)?);
+            self.encoded_images[id.0] = encoded;
+        }
+        Ok(())
+    }
+
+    fn load_font(&mut self, id: ResourceId) -> Result<(), ArchiveError> {
+        let (Some(slot), Some(meta)) = (
+            self.resources.fonts.get_mut(id.0),
+            self.manifest.fonts.get(id.0),
+        ) else {
+            return Err(ArchiveError::ResourceNotFound(id));
+        };
+        if slot.is_none() {
+            *slot = Some(decode_font(&read_font(&mut self.zip, meta)?)?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! Integration tests for capturing frames with `RecordingWindowRenderer`.
+
+use std::io::{Cursor, Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyrender::recording::RenderCommand;
+use anyrender::{NullScenePainter, PaintScene, WindowHandle, WindowRenderer};
+use anyrender_serialize::{RecordingWindowRenderer, SceneSequenceReader};
+use kurbo::{Affine, Rect};
+use peniko::{Color, Fill};
+
+/// A window renderer which runs the draw function against a [`NullScenePainter`], unless it is set to skip
+/// frames (as renderers do while the window is minimized)
+#[derive(Default)]
+struct CountingWindowRenderer {
+    frames: usize,
+    skip_frames: bool,
+}
+
+impl WindowRenderer for CountingWindowRenderer {
+    type ScenePainter<'a>
+        = NullScenePainter
+    where
+        Self: 'a;
+
+    fn resume(&mut self, _window: Arc<dyn WindowHandle>, _width: u32, _height: u32) {}
+
+    fn suspend(&mut self) {}
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn set_size(&mut self, _width: u32, _height: u32) {}
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        if !self.skip_frames {
+            self.frames += 1;
+            draw_fn(&mut NullScenePainter);
+        }
+    }
+}
+
+/// An in-memory archive which can be read after the capture thread has finished writing it
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Cursor<Vec<u8>>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for SharedBuffer {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0.lock().unwrap().seek(pos)
+    }
+}
+
+fn render_frame(renderer: &mut RecordingWindowRenderer<CountingWindowRenderer>, index: usize) {
+    renderer.render(|painter| {
+        painter.fill(
+            Fill::NonZero,
+            Affine::translate((index as f64, 0.0)),
+            Color::BLACK,
+            None,
+            &Rect::new(0.0, 0.0, 10.0, 10.0),
+        );
+    });
+}
+
+#[test]
+fn test_captures_frames_only_while_capturing() {
+    let mut renderer = RecordingWindowRenderer::new(CountingWindowRenderer::default());
+    renderer.set_size(64, 48);
+    render_frame(&mut renderer, 0);
+
+    let buffer = SharedBuffer::default();
+    renderer.start_capture(buffer.clone()).unwrap();
+    assert!(renderer.is_capturing());
+    render_frame(&mut renderer, 1);
+    render_frame(&mut renderer, 2);
+    renderer.stop_capture().unwrap();
+    render_frame(&mut renderer, 3);
+    assert_eq!(renderer.into_inner().frames, 4);
+
+    let data = buffer.0.lock().unwrap().get_ref().clone();
+    let mut reader = SceneSequenceReader::open(Cursor::new(data)).unwrap();
+    assert_eq!(reader.frame_count(), 2);
+    for (index, expected_x) in [(0, 1.0), (1, 2.0)] {
+        let frame = reader.frame(index).unwrap();
+        assert_eq!((frame.width, frame.height), (64, 48));
+        let scene = reader.read_frame(index).unwrap();
+        let [RenderCommand::Fill(fill)] = scene.commands.as_slice() else {
+            panic!("expected a single fill, found {:?}", scene.commands);
+        };
+        assert_eq!(fill.transform, Affine::translate((expected_x, 0.0)));
+    }
+}
+
+#[test]
+fn test_skipped_frames_are_not_captured() {
+    let mut renderer = RecordingWindowRenderer::new(CountingWindowRenderer::default());
+    let buffer = SharedBuffer::default();
+    renderer.start_capture(buffer.clone()).unwrap();
+    render_frame(&mut renderer, 0);
+    renderer.inner_mut().skip_frames = true;
+    render_frame(&mut renderer, 1);
+    renderer.try_render(|_| {}).unwrap();
+    renderer.inner_mut().skip_frames = false;
+    render_frame(&mut renderer, 2);
+    renderer.stop_capture().unwrap();
+
+    let data = buffer.0.lock().unwrap().get_ref().clone();
+    let mut reader = SceneSequenceReader::open(Cursor::new(data)).unwrap();
+    assert_eq!(reader.frame_count(), 2);
+    let scene = reader.read_frame(1).unwrap();
+    let RenderCommand::Fill(fill) = &scene.commands[0] else {
+        panic!("expected a fill, found {:?}", scene.commands[0]);
+    };
+    assert_eq!(fill.transform, Affine::translate((2.0, 0.0)));
+}
+
+#[test]
+fn test_retained_duration_keeps_recent_frames() {
+    let mut renderer = RecordingWindowRenderer::new(CountingWindowRenderer::default())
+        .with_retained_duration(Duration::ZERO);
+    let buffer = SharedBuffer::default();
+    renderer.start_capture(buffer.clone()).unwrap();
+    for index in 0..3 {
+        render_frame(&mut renderer, index);
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    renderer.stop_capture().unwrap();
+
+    let data = buffer.0.lock().unwrap().get_ref().clone();
+    let mut reader = SceneSequenceReader::open(Cursor::new(data)).unwrap();
+    assert_eq!(reader.frame_count(), 1);
+    assert_eq!(reader.frame(0).unwrap().timestamp, Duration::ZERO);
+    let scene = reader.read_frame(0).unwrap();
+    let RenderCommand::Fill(fill) = &scene.commands[0] else {
+        panic!("expected a fill, found {:?}", scene.commands[0]);
+    };
+    assert_eq!(fill.transform, Affine::translate((2.0, 0.0)));
+}
+
+#[test]
+fn test_captures_more_frames_than_can_be_queued() {
+    let mut renderer = RecordingWindowRenderer::new(CountingWindowRenderer::default());
+    let buffer = SharedBuffer::default();
+    renderer.start_capture(buffer.clone()).unwrap();
+    // Rendering waits for the capture thread when its queue is full, so no frames are lost
+    for index in 0..50 {
+        render_frame(&mut renderer, index);
+    }
+    renderer.stop_capture().unwrap();
+
+    let data = buffer.0.lock().unwrap().get_ref().clone();
+    let reader = SceneSequenceReader::open(Cursor::new(data)).unwrap();
+    assert_eq!(reader.frame_count(), 50);
+}
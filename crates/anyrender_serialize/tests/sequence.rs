@@ -0,0 +1,114 @@
+//! Integration tests for multi-frame sequence archives.
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use anyrender::recording::{RenderCommand, Scene};
+use anyrender::{Glyph, Paint, PaintScene};
+use anyrender_serialize::{
+    ArchiveError, CommandEncoding, SceneSequenceReader, SceneSequenceWriter, SerializeConfig,
+};
+use kurbo::{Affine, Rect};
+use peniko::{Blob, Color, Fill, FontData, ImageAlphaType, ImageBrush, ImageData, ImageFormat};
+
+fn roboto_font() -> FontData {
+    static ROBOTO_BYTES: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+    FontData::new(Blob::from(ROBOTO_BYTES.to_vec()), 0)
+}
+
+fn build_frame(index: usize, font: &FontData, image: &ImageBrush) -> Scene {
+    let mut scene = Scene::new();
+    let offset = index as f64 * 10.0;
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((offset, 0.0)),
+        image.as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 2.0, 1.0),
+    );
+    scene.draw_glyphs(
+        font,
+        16.0,
+        false,
+        &[],
+        Fill::NonZero,
+        Color::BLACK,
+        1.0,
+        Affine::translate((offset, 20.0)),
+        None,
+        [Glyph {
+            id: 43,
+            x: 0.0,
+            y: 0.0,
+        }]
+        .into_iter(),
+    );
+    scene
+}
+
+fn write_sequence(config: &SerializeConfig, frame_count: usize) -> Vec<u8> {
+    let font = roboto_font();
+    let image = ImageBrush::new(ImageData {
+        data: Blob::from(vec![255, 0, 0, 255, 0, 0, 255, 255]),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 2,
+        height: 1,
+    });
+
+    let mut writer = SceneSequenceWriter::new(Cursor::new(Vec::new()), config);
+    for index in 0..frame_count {
+        let scene = build_frame(index, &font, &image);
+        let timestamp = Duration::from_millis(16 * index as u64);
+        writer.append_frame(&scene, timestamp, 320, 240).unwrap();
+    }
+    assert_eq!(writer.frame_count(), frame_count);
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_frames_share_resources_and_can_be_read_out_of_order() {
+    for encoding in [CommandEncoding::Json, CommandEncoding::Binary] {
+        let config = SerializeConfig::new().with_command_encoding(encoding);
+        let data = write_sequence(&config, 3);
+
+        let mut reader = SceneSequenceReader::open(Cursor::new(data)).unwrap();
+        assert_eq!(reader.frame_count(), 3);
+        assert_eq!(reader.manifest().images.len(), 1);
+        assert_eq!(reader.manifest().fonts.len(), 1);
+
+        let frame = reader.frame(2).unwrap();
+        assert_eq!(frame.timestamp, Duration::from_millis(32));
+        assert_eq!((frame.width, frame.height), (320, 240));
+
+        for index in [2, 0, 1] {
+            let scene = reader.read_frame(index).unwrap();
+            assert_eq!(scene.commands.len(), 2);
+            let RenderCommand::Fill(fill) = &scene.commands[0] else {
+                panic!("expected a fill, found {:?}", scene.commands[0]);
+            };
+            assert_eq!(
+                fill.transform,
+                Affine::translate((index as f64 * 10.0, 0.0))
+            );
+            let Paint::Image(image) = &fill.brush else {
+                panic!("expected an image brush");
+            };
+            assert_eq!(image.image.data.data(), &[255, 0, 0, 255, 0, 0, 255, 255]);
+            assert!(matches!(scene.commands[1], RenderCommand::GlyphRun(_)));
+        }
+
+        assert!(matches!(
+            reader.read_frame(3),
+            Err(ArchiveError::FrameNotFound(3))
+        ));
+    }
+}
+
+#[test]
+fn test_empty_sequence() {
+    let data = write_sequence(&SerializeConfig::new(), 0);
+    let reader = SceneSequenceReader::open(Cursor::new(data)).unwrap();
+    assert_eq!(reader.frame_count(), 0);
+    assert!(reader.manifest().images.is_empty());
+}
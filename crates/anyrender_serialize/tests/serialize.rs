@@ -1,16 +1,18 @@
 //! Integration tests for scene serialization.
 
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Write};
 
-use anyrender::recording::{RenderCommand, Scene};
-use anyrender::{Glyph, PaintScene};
+use anyrender::recording::{RecordedShape, RenderCommand, Scene};
+use anyrender::{Glyph, Paint, PaintScene};
 use anyrender_serialize::{
-    ArchiveError, ResourceManifest, SceneArchive, SerializableRenderCommand, SerializeConfig,
+    ArchiveError, CommandEncoding, ImageEncoding, ResourceManifest, SceneArchive,
+    SerializableRenderCommand, SerializeConfig,
 };
-use kurbo::{Affine, Rect, Stroke};
+use image::ImageEncoder;
+use kurbo::{Affine, BezPath, Rect, Shape, Stroke};
 use peniko::{
-    Blob, Brush, Color, Compose, Fill, FontData, ImageAlphaType, ImageBrush, ImageData,
-    ImageFormat, Mix,
+    Blob, Color, ColorStop, Compose, Fill, FontData, Gradient, ImageAlphaType, ImageBrush,
+    ImageData, ImageFormat, Mix,
 };
 use read_fonts::TableProvider;
 use zip::ZipArchive;
@@ -201,6 +203,121 @@ fn test_multiple_different_images() {
     assert_eq!(extract_image_pixels(&restored, 1), blue_pixels);
 }
 
+#[test]
+fn test_images_are_deduplicated_by_content() {
+    let mut scene = Scene::new();
+    // Identical pixels in two different blobs
+    for _ in 0..2 {
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            ImageBrush::new(make_1x1_image(255, 0, 0, 255)).as_ref(),
+            None,
+            &Rect::new(0.0, 0.0, 50.0, 50.0),
+        );
+    }
+
+    let archive = SceneArchive::from_scene(&scene, &default_config()).unwrap();
+    assert_eq!(archive.manifest.images.len(), 1);
+    assert_eq!(archive.images.len(), 1);
+}
+
+#[test]
+fn test_images_with_the_same_pixels_in_different_formats() {
+    let image = |data: [u8; 4], format, alpha_type| ImageData {
+        data: Blob::from(data.to_vec()),
+        format,
+        alpha_type,
+        width: 1,
+        height: 1,
+    };
+    // All of these normalize to the same RGBA8 pixels, but must be restored with their own format and alpha type
+    let images = [
+        image([255, 0, 0, 128], ImageFormat::Rgba8, ImageAlphaType::Alpha),
+        image(
+            [255, 0, 0, 128],
+            ImageFormat::Rgba8,
+            ImageAlphaType::AlphaPremultiplied,
+        ),
+        image([0, 0, 255, 128], ImageFormat::Bgra8, ImageAlphaType::Alpha),
+    ];
+
+    let mut scene = Scene::new();
+    for image in &images {
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            ImageBrush::new(image.clone()).as_ref(),
+            None,
+            &Rect::new(0.0, 0.0, 50.0, 50.0),
+        );
+    }
+
+    let data = serialize_to_vec(&scene, &default_config()).unwrap();
+    let archive = archive_deserialize_from_slice(&data).unwrap();
+    assert_eq!(archive.manifest.images.len(), 3);
+    let restored = archive.to_scene().unwrap();
+    for (idx, image) in images.iter().enumerate() {
+        let RenderCommand::Fill(fill) = &restored.commands[idx] else {
+            panic!("expected a fill");
+        };
+        let Paint::Image(brush) = &fill.brush else {
+            panic!("expected an image brush");
+        };
+        assert_eq!(brush.image.format, image.format);
+        assert_eq!(brush.image.alpha_type, image.alpha_type);
+        assert_eq!(brush.image.data.data(), image.data.data());
+    }
+}
+
+#[test]
+fn test_encoded_image_bytes_are_preserved() {
+    let image = ImageData {
+        data: Blob::from(vec![255, 0, 0, 255, 0, 0, 255, 255]),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 2,
+        height: 1,
+    };
+    let mut png_data = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png_data)
+        .write_image(image.data.data(), 2, 1, image::ExtendedColorType::Rgba8)
+        .unwrap();
+
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        ImageBrush::new(image.clone()).as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 50.0, 50.0),
+    );
+    let config = default_config().with_encoded_image(&image, ImageEncoding::Png, png_data.clone());
+    let data = serialize_to_vec(&scene, &config).unwrap();
+
+    let archive = archive_deserialize_from_slice(&data).unwrap();
+    let meta = &archive.manifest.images[0];
+    assert_eq!(meta.original_encoding, Some(ImageEncoding::Png));
+    assert_eq!(meta.entry.size, png_data.len());
+
+    // The provided bytes are stored verbatim rather than being re-encoded
+    let mut stored = Vec::new();
+    ZipArchive::new(Cursor::new(&data))
+        .unwrap()
+        .by_name(&meta.entry.path)
+        .unwrap()
+        .read_to_end(&mut stored)
+        .unwrap();
+    assert_eq!(stored, png_data);
+    assert_eq!(
+        archive.encoded_images[0].as_ref().map(|blob| blob.data()),
+        Some(png_data.as_slice())
+    );
+
+    let restored = archive.to_scene().unwrap();
+    assert_eq!(extract_image_pixels(&restored, 0), image.data.data());
+}
+
 #[test]
 fn test_glyph_run_roundtrip() {
     let font = roboto_font();
@@ -304,7 +421,7 @@ fn test_font_deduplication() {
 
 #[test]
 fn test_resource_manifest_version() {
-    assert_eq!(ResourceManifest::CURRENT_VERSION, 1);
+    assert_eq!(ResourceManifest::CURRENT_VERSION, 4);
 }
 
 #[test]
@@ -328,7 +445,8 @@ fn test_archive_contains_expected_files() {
         .read_to_string(&mut resources_json)
         .unwrap();
     let manifest: ResourceManifest = serde_json::from_str(&resources_json).unwrap();
-    assert_eq!(manifest.version, 1);
+    assert_eq!(manifest.version, 4);
+    assert_eq!(manifest.command_encoding, CommandEncoding::Json);
     assert!(manifest.images.is_empty());
     assert!(manifest.fonts.is_empty());
 
@@ -340,6 +458,190 @@ fn test_archive_contains_expected_files() {
         .unwrap();
     let commands: Vec<SerializableRenderCommand> = serde_json::from_str(&commands_json).unwrap();
     assert_eq!(commands.len(), 1);
+    // Primitive shapes are stored as-is rather than being flattened into paths
+    assert!(matches!(
+        &commands[0],
+        SerializableRenderCommand::Fill(fill) if fill.shape == RecordedShape::Rect(Rect::new(0.0, 0.0, 100.0, 100.0))
+    ));
+}
+
+#[test]
+fn test_binary_command_encoding_roundtrip() {
+    let font = roboto_font();
+    let mut scene = build_glyph_scene(&font);
+
+    // Path points are stored as f32, so use coordinates which are exactly representable
+    let mut path = BezPath::new();
+    path.move_to((0.5, 0.25));
+    path.quad_to((10.0, 20.0), (30.125, 40.0));
+    path.curve_to((1.0, 2.0), (3.0, 4.0), (5.0, 6.0));
+    path.close_path();
+    scene.push_clip_layer(Affine::rotate(0.3), &path);
+    let gradient = Gradient::new_linear((0.0, 0.0), (100.0, 0.0)).with_stops(
+        [
+            ColorStop::from((0.0, Color::from_rgb8(255, 0, 0))),
+            ColorStop::from((1.0, Color::from_rgb8(0, 0, 255))),
+        ]
+        .as_slice(),
+    );
+    scene.stroke(
+        &Stroke::new(2.0).with_dashes(1.0, [4.0, 2.0]),
+        Affine::translate((1.0, 2.0)),
+        &gradient,
+        Some(Affine::scale(2.0)),
+        &path,
+    );
+    let image = ImageBrush::new(make_1x1_image(1, 2, 3, 255));
+    scene.fill(
+        Fill::EvenOdd,
+        Affine::IDENTITY,
+        image.as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 1.0, 1.0),
+    );
+    scene.pop_layer();
+    scene.draw_box_shadow(
+        Affine::IDENTITY,
+        Rect::new(0.0, 0.0, 10.0, 10.0),
+        Color::BLACK,
+        2.0,
+        4.0,
+    );
+
+    let config = SerializeConfig::new().with_command_encoding(CommandEncoding::Binary);
+    let original = SceneArchive::from_scene(&scene, &config).unwrap();
+    let data = archive_serialize_to_vec(&original).unwrap();
+
+    let zip = ZipArchive::new(Cursor::new(&data)).unwrap();
+    assert!(zip.index_for_name("draw_commands.bin").is_some());
+    assert!(zip.index_for_name("draw_commands.json").is_none());
+
+    let archive = archive_deserialize_from_slice(&data).unwrap();
+    assert_eq!(archive.manifest.command_encoding, CommandEncoding::Binary);
+    // Compare the archived commands, since fonts and images get new blob IDs when they're restored
+    assert_eq!(archive.commands, original.commands);
+}
+
+#[test]
+fn test_scene_fragments_are_flattened() {
+    let mut fragment = Scene::new();
+    fragment.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &Rect::new(0.0, 0.0, 10.0, 10.0),
+    );
+    let mut nested = Scene::new();
+    nested.append_scene(fragment, Affine::translate((5.0, 0.0)));
+    let mut scene = Scene::new();
+    scene.append_scene(nested, Affine::scale(2.0));
+
+    for encoding in [CommandEncoding::Json, CommandEncoding::Binary] {
+        let config = SerializeConfig::new().with_command_encoding(encoding);
+        let data = serialize_to_vec(&scene, &config).unwrap();
+        let restored = deserialize_from_slice(&data).unwrap();
+        assert_eq!(restored, scene.flatten());
+    }
+}
+
+#[test]
+fn test_command_encoding_is_detected_from_archive_contents() {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &Rect::new(0.0, 0.0, 100.0, 100.0),
+    );
+    let config = SerializeConfig::new().with_command_encoding(CommandEncoding::Binary);
+    let data = serialize_to_vec(&scene, &config).unwrap();
+
+    // Rewrite the archive with a manifest that doesn't record the encoding, as written by older versions
+    let mut zip = ZipArchive::new(Cursor::new(&data)).unwrap();
+    let mut rewritten = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).unwrap();
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).unwrap();
+        if file.name() == "resources.json" {
+            let mut manifest: serde_json::Value = serde_json::from_slice(&contents).unwrap();
+            manifest.as_object_mut().unwrap().remove("command_encoding");
+            contents = serde_json::to_vec(&manifest).unwrap();
+        }
+        rewritten
+            .start_file(file.name(), zip::write::SimpleFileOptions::default())
+            .unwrap();
+        rewritten.write_all(&contents).unwrap();
+    }
+    let data = rewritten.finish().unwrap().into_inner();
+
+    let archive = archive_deserialize_from_slice(&data).unwrap();
+    assert_eq!(archive.manifest.command_encoding, CommandEncoding::Binary);
+    assert_eq!(archive.to_scene().unwrap(), scene);
+}
+
+#[test]
+fn test_version_1_archives_with_svg_path_shapes_are_readable() {
+    let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let mut scene = Scene::new();
+    scene.push_clip_layer(Affine::IDENTITY, &rect);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &rect,
+    );
+    scene.pop_layer();
+    let data = serialize_to_vec(&scene, &default_config()).unwrap();
+
+    // Rewrite the archive as version 1 wrote it, with every shape stored as an SVG path string
+    let svg = rect.to_path(0.1).to_svg();
+    let mut zip = ZipArchive::new(Cursor::new(&data)).unwrap();
+    let mut rewritten = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).unwrap();
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&contents).unwrap();
+        if file.name() == "resources.json" {
+            json["version"] = 1.into();
+            json.as_object_mut().unwrap().remove("command_encoding");
+        } else {
+            // Commands without fields (`PopLayer`) are stored as plain strings
+            let commands = json.as_array_mut().unwrap().iter_mut();
+            for command in commands.filter_map(|command| command.as_object_mut()) {
+                for fields in command.values_mut() {
+                    for field in ["clip", "shape"] {
+                        if let Some(shape) = fields.get_mut(field) {
+                            *shape = svg.clone().into();
+                        }
+                    }
+                }
+            }
+        }
+        rewritten
+            .start_file(file.name(), zip::write::SimpleFileOptions::default())
+            .unwrap();
+        rewritten
+            .write_all(&serde_json::to_vec(&json).unwrap())
+            .unwrap();
+    }
+    let data = rewritten.finish().unwrap().into_inner();
+
+    let restored = deserialize_from_slice(&data).unwrap();
+    assert_eq!(restored.commands.len(), 3);
+    let expected = RecordedShape::Path(BezPath::from_svg(&svg).unwrap());
+    match &restored.commands[0] {
+        RenderCommand::PushClipLayer(clip) => assert_eq!(clip.clip, expected),
+        other => panic!("Expected PushClipLayer command, got {other:?}"),
+    }
+    match &restored.commands[1] {
+        RenderCommand::Fill(fill) => assert_eq!(fill.shape, expected),
+        other => panic!("Expected Fill command, got {other:?}"),
+    }
 }
 
 // Helpers
@@ -393,7 +695,7 @@ fn make_1x1_image(r: u8, g: u8, b: u8, a: u8) -> ImageData {
 fn extract_image_pixels(scene: &Scene, command_index: usize) -> Vec<u8> {
     match &scene.commands[command_index] {
         RenderCommand::Fill(f) => match &f.brush {
-            Brush::Image(img) => img.image.data.data().to_vec(),
+            Paint::Image(img) => img.image.data.data().to_vec(),
             other => panic!("Expected image brush, got {other:?}"),
         },
         other => panic!("Expected Fill command, got {other:?}"),
@@ -1,5 +1,6 @@
-use anyrender::ImageRenderer;
+use anyrender::{ImageRenderer, PixelRegion, RenderError};
 use debug_timer::debug_timer;
+use kurbo::{Affine, Rect};
 use skia_safe::{AlphaType, Color, ColorType, ImageInfo, SurfaceProps, graphics, surfaces};
 
 use crate::{SkiaScenePainter, scene::SkiaSceneCache};
@@ -49,21 +50,53 @@ impl ImageRenderer for SkiaImageRenderer {
         draw_fn: F,
         buffer: &mut Vec<u8>,
     ) {
+        self.try_render_to_vec(draw_fn, buffer)
+            .expect("Error wrapping pixel buffer");
+    }
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F, buffer: &mut [u8]) {
+        self.try_render(draw_fn, buffer)
+            .expect("Error wrapping pixel buffer");
+    }
+
+    fn try_render_to_vec<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), RenderError> {
+        buffer.resize(self.image_info.compute_min_byte_size(), 0);
+        self.try_render(draw_fn, buffer)
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        buffer: &mut [u8],
+    ) -> Result<(), RenderError> {
         debug_timer!(timer, feature = "log_frame_times");
 
+        let buffer_len = buffer.len();
         let mut surface = surfaces::wrap_pixels(
             &self.image_info,
             &mut buffer[..],
             None,
             Some(&self.surface_props),
         )
-        .unwrap();
+        .ok_or_else(|| {
+            RenderError::render(format!(
+                "buffer of {} bytes is too small for a {}x{} image",
+                buffer_len,
+                self.image_info.width(),
+                self.image_info.height()
+            ))
+        })?;
 
         surface.canvas().clear(Color::WHITE);
 
         draw_fn(&mut SkiaScenePainter {
             inner: surface.canvas(),
             cache: &mut self.scene_cache,
+            base_transform: Affine::IDENTITY,
         });
         timer.record_time("render");
 
@@ -71,30 +104,71 @@ impl ImageRenderer for SkiaImageRenderer {
         timer.record_time("cache next gen");
 
         timer.print_times("skia_raster: ");
+
+        Ok(())
     }
 
-    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F, buffer: &mut [u8]) {
+    fn render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) {
+        self.try_render_region(draw_fn, region, buffer, stride)
+            .expect("Error wrapping pixel buffer");
+    }
+
+    fn try_render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), RenderError> {
+        let width = self.image_info.width() as u32;
+        let height = self.image_info.height() as u32;
+        let Some(region) = PixelRegion::new(region, width, height) else {
+            return Ok(());
+        };
+
         debug_timer!(timer, feature = "log_frame_times");
 
-        let mut surface = surfaces::wrap_pixels(
-            &self.image_info,
-            &mut buffer[..],
+        // Wrap just the region of the buffer, so that Skia clips drawing to the region
+        let image_info = ImageInfo::new(
+            (region.width as i32, region.height as i32),
+            ColorType::RGBA8888,
+            AlphaType::Opaque,
             None,
+        );
+        let offset = region.y as usize * stride + region.x as usize * 4;
+        let buffer_len = buffer.len();
+        let mut surface = surfaces::wrap_pixels(
+            &image_info,
+            buffer.get_mut(offset..).unwrap_or_default(),
+            stride,
             Some(&self.surface_props),
         )
-        .unwrap();
+        .ok_or_else(|| {
+            RenderError::render(format!(
+                "buffer of {buffer_len} bytes with a stride of {stride} is too small for a {width}x{height} image"
+            ))
+        })?;
 
         surface.canvas().clear(Color::WHITE);
 
         draw_fn(&mut SkiaScenePainter {
             inner: surface.canvas(),
             cache: &mut self.scene_cache,
+            base_transform: Affine::translate((-(region.x as f64), -(region.y as f64))),
         });
         timer.record_time("render");
 
         self.scene_cache.next_gen();
         timer.record_time("cache next gen");
 
-        timer.print_times("skia_raster: ");
+        timer.print_times("skia_raster (region): ");
+
+        Ok(())
     }
 }
@@ -1,4 +1,5 @@
 mod image_renderer;
+mod pdf_renderer;
 mod scene;
 mod window_renderer;
 
@@ -12,5 +13,6 @@ mod opengl;
 mod vulkan;
 
 pub use image_renderer::SkiaImageRenderer;
+pub use pdf_renderer::{PdfMetadata, SkiaPdfRenderer};
 pub use scene::SkiaScenePainter;
 pub use window_renderer::*;
@@ -14,6 +14,8 @@ use skia_safe::{
     scalar,
 };
 
+use anyrender::RenderError;
+
 use crate::window_renderer::SkiaBackend;
 
 pub struct MetalBackend {
@@ -24,8 +26,13 @@ pub struct MetalBackend {
 }
 
 impl MetalBackend {
-    pub fn new(window: Arc<dyn anyrender::WindowHandle>, width: u32, height: u32) -> Self {
-        let device = MTLCreateSystemDefaultDevice().expect("no device found");
+    pub fn new(
+        window: Arc<dyn anyrender::WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, RenderError> {
+        let device =
+            MTLCreateSystemDefaultDevice().ok_or_else(|| RenderError::resume("no device found"))?;
 
         let metal_layer = {
             let layer = CAMetalLayer::new();
@@ -37,7 +44,11 @@ impl MetalBackend {
             layer.setFramebufferOnly(false);
             layer.setDrawableSize(CGSize::new(width as f64, height as f64));
 
-            let view_ptr = match window.window_handle().unwrap().as_raw() {
+            let view_ptr = match window
+                .window_handle()
+                .map_err(|err| RenderError::resume(err.to_string()))?
+                .as_raw()
+            {
                 #[cfg(target_os = "macos")]
                 raw_window_handle::RawWindowHandle::AppKit(appkit) => {
                     appkit.ns_view.as_ptr() as *mut NSView
@@ -69,7 +80,7 @@ impl MetalBackend {
 
         let command_queue = device
             .newCommandQueue()
-            .expect("unable to get command queue");
+            .ok_or_else(|| RenderError::resume("unable to get command queue"))?;
 
         let backend = unsafe {
             mtl::BackendContext::new(
@@ -78,14 +89,15 @@ impl MetalBackend {
             )
         };
 
-        let skia_context = gpu::direct_contexts::make_metal(&backend, None).unwrap();
+        let skia_context = gpu::direct_contexts::make_metal(&backend, None)
+            .ok_or_else(|| RenderError::resume("couldn't create Skia Metal context"))?;
 
-        Self {
+        Ok(Self {
             metal_layer,
             command_queue,
             skia: skia_context,
             prepared_drawable: None,
-        }
+        })
     }
 }
 
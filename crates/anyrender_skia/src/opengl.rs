@@ -16,6 +16,8 @@ use skia_safe::{
     },
 };
 
+use anyrender::RenderError;
+
 use crate::window_renderer::SkiaBackend;
 
 pub(crate) struct OpenGLBackend {
@@ -31,9 +33,15 @@ impl OpenGLBackend {
         window: Arc<dyn anyrender::WindowHandle>,
         width: u32,
         height: u32,
-    ) -> OpenGLBackend {
-        let raw_display_handle = window.display_handle().unwrap().as_raw();
-        let raw_window_handle = window.window_handle().unwrap().as_raw();
+    ) -> Result<OpenGLBackend, RenderError> {
+        let raw_display_handle = window
+            .display_handle()
+            .map_err(|err| RenderError::resume(err.to_string()))?
+            .as_raw();
+        let raw_window_handle = window
+            .window_handle()
+            .map_err(|err| RenderError::resume(err.to_string()))?
+            .as_raw();
 
         let gl_display = unsafe {
             Display::new(
@@ -45,14 +53,14 @@ impl OpenGLBackend {
                 #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "ios")))]
                 DisplayApiPreference::Egl,
             )
-            .unwrap()
+            .map_err(RenderError::resume)?
         };
 
         let gl_config_template = ConfigTemplateBuilder::new().with_transparency(true).build();
         let gl_config = unsafe {
             gl_display
                 .find_configs(gl_config_template)
-                .unwrap()
+                .map_err(RenderError::resume)?
                 .reduce(|accum, config| {
                     let transparency_check = config.supports_transparency().unwrap_or(false)
                         & !accum.supports_transparency().unwrap_or(false);
@@ -63,7 +71,7 @@ impl OpenGLBackend {
                         accum
                     }
                 })
-                .unwrap()
+                .ok_or_else(|| RenderError::resume("no suitable OpenGL config found"))?
         };
 
         let gl_context_attrs = ContextAttributesBuilder::new().build(Some(raw_window_handle));
@@ -76,17 +84,19 @@ impl OpenGLBackend {
         let gl_not_current_context = unsafe {
             gl_display
                 .create_context(&gl_config, &gl_context_attrs)
-                .unwrap()
+                .map_err(RenderError::resume)?
         };
 
         let gl_surface = unsafe {
             gl_config
                 .display()
                 .create_window_surface(&gl_config, &gl_surface_attrs)
-                .unwrap()
+                .map_err(RenderError::resume)?
         };
 
-        let gl_context = gl_not_current_context.make_current(&gl_surface).unwrap();
+        let gl_context = gl_not_current_context
+            .make_current(&gl_surface)
+            .map_err(RenderError::resume)?;
 
         gl::load_with(|s| {
             gl_config
@@ -102,9 +112,10 @@ impl OpenGLBackend {
                 .display()
                 .get_proc_address(CString::new(name).unwrap().as_c_str())
         })
-        .unwrap();
+        .ok_or_else(|| RenderError::resume("couldn't create Skia OpenGL interface"))?;
 
-        let mut gr_context = direct_contexts::make_gl(interface, None).unwrap();
+        let mut gr_context = direct_contexts::make_gl(interface, None)
+            .ok_or_else(|| RenderError::resume("couldn't create Skia OpenGL context"))?;
 
         let mut fb_info = {
             let mut fboid: gl::types::GLint = 0;
@@ -119,7 +130,7 @@ impl OpenGLBackend {
             }
         };
 
-        OpenGLBackend {
+        Ok(OpenGLBackend {
             surface: Some(Self::create_surface(
                 width,
                 height,
@@ -132,7 +143,7 @@ impl OpenGLBackend {
             gl_surface,
             gl_context,
             fb_info,
-        }
+        })
     }
 
     fn create_surface(
@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+use std::ptr::NonNull;
+
+use anyrender::RenderError;
+use kurbo::{Affine, Size};
+use skia_safe::{Document, pdf};
+
+use crate::{SkiaScenePainter, scene::SkiaSceneCache};
+
+/// Document metadata for PDFs created by [`SkiaPdfRenderer`]
+#[derive(Clone, Debug, Default)]
+pub struct PdfMetadata {
+    pub title: String,
+    pub author: String,
+    pub subject: String,
+    pub keywords: String,
+    /// The name of the application that created the document
+    pub creator: String,
+    /// The resolution (in DPI) used for content that has to be rasterized, such as blurs. Skia's default
+    /// (72 DPI) is used if this is `None`.
+    pub raster_dpi: Option<f32>,
+}
+
+impl PdfMetadata {
+    fn to_skia(&self) -> pdf::Metadata {
+        pdf::Metadata {
+            title: self.title.clone(),
+            author: self.author.clone(),
+            subject: self.subject.clone(),
+            keywords: self.keywords.clone(),
+            creator: self.creator.clone(),
+            raster_dpi: self.raster_dpi,
+            ..Default::default()
+        }
+    }
+}
+
+/// Forwards writes to the document's writer, keeping the first error since Skia ignores write errors
+struct ErrorTrackingWriter<'a> {
+    inner: &'a mut dyn Write,
+    error: Option<io::Error>,
+}
+
+impl ErrorTrackingWriter<'_> {
+    /// Keep the error if it is the first, returning an error of the same kind to Skia
+    fn track(&mut self, err: io::Error) -> io::Error {
+        let kind = err.kind();
+        self.error.get_or_insert(err);
+        kind.into()
+    }
+}
+
+impl Write for ErrorTrackingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf).map_err(|err| self.track(err))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().map_err(|err| self.track(err))
+    }
+}
+
+/// Renders scenes to the pages of a PDF document using Skia's PDF backend.
+///
+/// Each call to [`render_page`](SkiaPdfRenderer::render_page) adds a page to the document. Page sizes are
+/// in PDF points (1/72 inch), which is also the unit of the scene's coordinate space. The document is
+/// written to the writer as pages are added, and is completed by [`finish`](SkiaPdfRenderer::finish).
+pub struct SkiaPdfRenderer<'a> {
+    /// `Some` until the document is closed, except while a page is being drawn
+    document: Option<Document<'a>>,
+    /// The writer the document writes to. It is owned by the renderer, and freed once the document is closed.
+    writer: NonNull<ErrorTrackingWriter<'a>>,
+    page_size: Size,
+    scene_cache: SkiaSceneCache,
+}
+
+impl<'a> SkiaPdfRenderer<'a> {
+    /// The size of an A4 page in points
+    pub const A4: Size = Size::new(595.0, 842.0);
+    /// The size of a US Letter page in points
+    pub const LETTER: Size = Size::new(612.0, 792.0);
+
+    /// Create a PDF document which is written to `writer`, with pages of `page_size` by default
+    pub fn new(writer: &'a mut impl Write, page_size: Size, metadata: &PdfMetadata) -> Self {
+        let writer = NonNull::from(Box::leak(Box::new(ErrorTrackingWriter {
+            inner: writer,
+            error: None,
+        })));
+        // SAFETY: the writer is only freed (in `drop`) after the document that borrows it has been closed,
+        // and isn't accessed by the renderer while the document is open
+        let document =
+            pdf::new_document(unsafe { &mut *writer.as_ptr() }, Some(&metadata.to_skia()));
+        Self {
+            document: Some(document),
+            writer,
+            page_size,
+            scene_cache: SkiaSceneCache::default(),
+        }
+    }
+
+    /// The page size used by [`render_page`](SkiaPdfRenderer::render_page)
+    pub fn page_size(&self) -> Size {
+        self.page_size
+    }
+
+    /// Set the page size used for subsequent calls to [`render_page`](SkiaPdfRenderer::render_page)
+    pub fn set_page_size(&mut self, page_size: Size) {
+        self.page_size = page_size;
+    }
+
+    /// The number of pages that have been rendered
+    pub fn page_count(&self) -> usize {
+        self.document
+            .as_ref()
+            .map_or(0, |document| document.pages())
+    }
+
+    /// Add a page to the document, drawing its content with `draw_fn`
+    pub fn render_page<F: FnOnce(&mut SkiaScenePainter<'_>)>(&mut self, draw_fn: F) {
+        self.render_page_with_size(self.page_size, draw_fn);
+    }
+
+    /// Add a page of the specified size to the document, drawing its content with `draw_fn`
+    pub fn render_page_with_size<F: FnOnce(&mut SkiaScenePainter<'_>)>(
+        &mut self,
+        page_size: Size,
+        draw_fn: F,
+    ) {
+        let document = self
+            .document
+            .take()
+            .expect("document is only taken while drawing a page");
+        let mut page = document.begin_page((page_size.width as f32, page_size.height as f32), None);
+
+        draw_fn(&mut SkiaScenePainter {
+            inner: page.canvas(),
+            cache: &mut self.scene_cache,
+            base_transform: Affine::IDENTITY,
+        });
+
+        self.document = Some(page.end_page());
+        self.scene_cache.next_gen();
+    }
+
+    /// Complete the document, writing any remaining data to the writer. Returns the first error that occurred
+    /// while writing the document.
+    ///
+    /// The document is also completed when the renderer is dropped, but any error is then ignored.
+    pub fn finish(mut self) -> Result<(), RenderError> {
+        self.close();
+        // SAFETY: the document has been closed, so nothing else references the writer
+        match unsafe { self.writer.as_mut() }.error.take() {
+            Some(err) => Err(RenderError::render(err)),
+            None => Ok(()),
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(document) = self.document.take() {
+            document.close();
+        }
+    }
+}
+
+impl Drop for SkiaPdfRenderer<'_> {
+    fn drop(&mut self) {
+        self.close();
+        // SAFETY: the writer was allocated by `new`, and the document which borrowed it has been closed
+        drop(unsafe { Box::from_raw(self.writer.as_ptr()) });
+    }
+}
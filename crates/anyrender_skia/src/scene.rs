@@ -54,6 +54,8 @@ impl Default for SkiaSceneCache {
 pub struct SkiaScenePainter<'a> {
     pub(crate) inner: &'a Canvas,
     pub(crate) cache: &'a mut SkiaSceneCache,
+    /// Transform applied to all drawing commands (used to offset region renders)
+    pub(crate) base_transform: kurbo::Affine,
 }
 
 impl SkiaScenePainter<'_> {
@@ -73,7 +75,8 @@ impl SkiaScenePainter<'_> {
     }
 
     fn set_matrix(&self, transform: kurbo::Affine) {
-        self.inner.set_matrix(&sk_kurbo::m44_from_affine(transform));
+        self.inner
+            .set_matrix(&sk_kurbo::m44_from_affine(self.base_transform * transform));
     }
 
     fn concat_matrix(&self, transform: kurbo::Affine) {
@@ -126,7 +129,13 @@ impl SkiaScenePainter<'_> {
 
                 self.cache.paint.set_shader(image_shader);
             }
-            anyrender::Paint::Custom(_) => unreachable!(), // ToDo: figure out what to do with this
+            // Custom paints are translated into "invisible" as they are not supported
+            anyrender::Paint::Custom(_) => {
+                self.cache.paint.set_color4f(
+                    sk_peniko::color4f_from_alpha_color(peniko::Color::TRANSPARENT),
+                    &ColorSpace::new_srgb(),
+                );
+            }
         }
     }
 
@@ -1,5 +1,6 @@
-use anyrender::WindowRenderer;
+use anyrender::{RenderError, WindowRenderer};
 use debug_timer::debug_timer;
+use kurbo::Affine;
 use skia_safe::{Color, Surface, graphics};
 use std::sync::Arc;
 
@@ -50,19 +51,31 @@ impl WindowRenderer for SkiaWindowRenderer {
         Self: 'a;
 
     fn resume(&mut self, window: Arc<dyn anyrender::WindowHandle>, width: u32, height: u32) {
+        self.try_resume(window, width, height)
+            .expect("Error creating surface");
+    }
+
+    fn try_resume(
+        &mut self,
+        window: Arc<dyn anyrender::WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
         graphics::set_font_cache_count_limit(100);
         graphics::set_typeface_cache_count_limit(100);
         graphics::set_resource_cache_total_bytes_limit(10485760);
 
         #[cfg(any(target_os = "macos", target_os = "ios"))]
-        let backend = crate::metal::MetalBackend::new(window, width, height);
+        let backend = crate::metal::MetalBackend::new(window, width, height)?;
         #[cfg(not(any(target_os = "macos", target_os = "ios")))]
-        let backend = crate::opengl::OpenGLBackend::new(window, width, height);
+        let backend = crate::opengl::OpenGLBackend::new(window, width, height)?;
 
         self.render_state = RenderState::Active(Box::new(ActiveRenderState {
             backend: Box::new(backend),
             scene_cache: SkiaSceneCache::default(),
-        }))
+        }));
+
+        Ok(())
     }
 
     fn suspend(&mut self) {
@@ -97,6 +110,7 @@ impl WindowRenderer for SkiaWindowRenderer {
         draw_fn(&mut SkiaScenePainter {
             inner: surface.canvas(),
             cache: &mut state.scene_cache,
+            base_transform: Affine::IDENTITY,
         });
         timer.record_time("cmd");
 
@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+
+use anyrender::{PaintScene, RenderError};
+use anyrender_skia::{PdfMetadata, SkiaPdfRenderer};
+use kurbo::{Affine, Rect, Size};
+use peniko::{Color, Fill};
+
+fn fill_page(scene: &mut impl PaintScene) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &Rect::new(10.0, 10.0, 100.0, 100.0),
+    );
+}
+
+#[test]
+fn renders_pages_to_a_pdf() {
+    let mut pdf = Vec::new();
+    let mut renderer = SkiaPdfRenderer::new(&mut pdf, SkiaPdfRenderer::A4, &PdfMetadata::default());
+    renderer.render_page(fill_page);
+    renderer.render_page_with_size(Size::new(200.0, 100.0), fill_page);
+    assert_eq!(renderer.page_count(), 2);
+    renderer.finish().unwrap();
+
+    assert!(pdf.starts_with(b"%PDF-"));
+    let text = String::from_utf8_lossy(&pdf);
+    let pages = text.matches("/Type /Page").count() - text.matches("/Type /Pages").count();
+    assert_eq!(pages, 2);
+    assert!(text.contains("/Count 2"));
+    assert!(text.trim_end().ends_with("%%EOF"));
+}
+
+/// A writer which fails once it has been given `limit` bytes
+struct FailingWriter {
+    written: usize,
+    limit: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() > self.limit {
+            return Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"));
+        }
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn finish_reports_write_errors() {
+    let mut writer = FailingWriter {
+        written: 0,
+        limit: 16,
+    };
+    let mut renderer =
+        SkiaPdfRenderer::new(&mut writer, SkiaPdfRenderer::A4, &PdfMetadata::default());
+    renderer.render_page(fill_page);
+    assert!(matches!(renderer.finish(), Err(RenderError::Render(_))));
+}
@@ -0,0 +1,62 @@
+use anyrender::{ImageRenderer, PaintScene, RenderError};
+use anyrender_skia::SkiaImageRenderer;
+use kurbo::{Affine, Rect, RoundedRect, Stroke};
+use peniko::{Color, Fill};
+
+const WIDTH: u32 = 50;
+const HEIGHT: u32 = 40;
+
+fn draw(scene: &mut impl PaintScene) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::rotate_about(0.3, (25.0, 20.0)),
+        Color::from_rgb8(0, 0, 255),
+        None,
+        &RoundedRect::new(10.0, 8.0, 40.0, 32.0, 6.0),
+    );
+    scene.stroke(
+        &Stroke::new(3.0),
+        Affine::IDENTITY,
+        Color::from_rgb8(0, 160, 0),
+        None,
+        &Rect::new(4.0, 4.0, 46.0, 36.0),
+    );
+}
+
+#[test]
+fn render_region_only_writes_the_region() {
+    let mut renderer = SkiaImageRenderer::new(WIDTH, HEIGHT);
+    let mut full = Vec::new();
+    renderer.render_to_vec(draw, &mut full);
+
+    // Use a stride with padding at the end of each row, which must also be left untouched
+    let stride = WIDTH as usize * 4 + 8;
+    let mut partial = vec![3u8; stride * HEIGHT as usize];
+    renderer.render_region(draw, Rect::new(20.4, 0.0, 60.0, 17.5), &mut partial, stride);
+
+    for y in 0..HEIGHT as usize {
+        for x in 0..stride / 4 {
+            let actual = &partial[y * stride + x * 4..][..4];
+            if (20..WIDTH as usize).contains(&x) && y < 18 {
+                let idx = (y * WIDTH as usize + x) * 4;
+                assert_eq!(actual, &full[idx..idx + 4], "pixel ({x}, {y})");
+            } else {
+                assert_eq!(actual, [3; 4], "pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn try_render_region_reports_a_short_buffer() {
+    let mut renderer = SkiaImageRenderer::new(WIDTH, HEIGHT);
+    let stride = WIDTH as usize * 4;
+    let mut buffer = vec![0u8; stride * 10];
+    let result = renderer.try_render_region(
+        draw,
+        Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+        &mut buffer,
+        stride,
+    );
+    assert!(matches!(result, Err(RenderError::Render(_))));
+}
@@ -0,0 +1,37 @@
+//! Encoding images as data URIs
+
+use base64::Engine;
+use image::{ExtendedColorType, ImageEncoder, codecs::png::PngEncoder};
+use peniko::{ImageAlphaType, ImageData, ImageFormat};
+
+/// Encode an image as a PNG data URI. Returns `None` if the image data doesn't match its dimensions.
+pub(crate) fn png_data_uri(image: &ImageData) -> Option<String> {
+    let data = image.data.data();
+    if data.len() != image.width as usize * image.height as usize * 4 {
+        return None;
+    }
+
+    // PNGs are stored as unpremultiplied RGBA
+    let mut rgba = Vec::with_capacity(data.len());
+    for pixel in data.chunks_exact(4) {
+        let [mut r, g, mut b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        if image.format == ImageFormat::Bgra8 {
+            std::mem::swap(&mut r, &mut b);
+        }
+        if image.alpha_type == ImageAlphaType::AlphaPremultiplied && a != 0 && a != 255 {
+            let unpremultiply = |c: u8| ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+            rgba.extend_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), a]);
+        } else {
+            rgba.extend_from_slice(&[r, g, b, a]);
+        }
+    }
+
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(&rgba, image.width, image.height, ExtendedColorType::Rgba8)
+        .ok()?;
+
+    let mut uri = String::from("data:image/png;base64,");
+    base64::engine::general_purpose::STANDARD.encode_string(&png, &mut uri);
+    Some(uri)
+}
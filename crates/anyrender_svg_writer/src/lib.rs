@@ -0,0 +1,33 @@
+//! An SVG output backend for the [`anyrender`] 2D drawing abstraction
+//!
+//! [`SvgScenePainter`] implements [`PaintScene`](anyrender::PaintScene) and emits a standalone SVG document,
+//! allowing the same drawing code that is used to render to a window to be exported as vector graphics:
+//!
+//!  - Fills and strokes become `<path>` elements
+//!  - Gradients become `<linearGradient>`/`<radialGradient>` elements
+//!  - Images are embedded as PNG data URIs
+//!  - Layers become `<g>` elements with a `clipPath`, opacity and `mix-blend-mode`
+//!  - Glyph runs are emitted as outlines (so the document doesn't depend on any fonts being available)
+//!  - Box shadows become blurred `<rect>` elements
+//!
+//! Some features can't be represented in SVG and are approximated: sweep gradients are drawn using their first
+//! color stop, gradients are always interpolated in sRGB, compose modes other than `SrcOver` are ignored and
+//! custom paints are not drawn.
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod image;
+mod scene;
+mod util;
+
+pub use scene::SvgScenePainter;
+
+/// Draw a scene with `draw_fn` and return it as an SVG document
+pub fn render_to_svg<F: FnOnce(&mut SvgScenePainter)>(
+    draw_fn: F,
+    width: u32,
+    height: u32,
+) -> String {
+    let mut painter = SvgScenePainter::new(width, height);
+    draw_fn(&mut painter);
+    painter.to_svg()
+}
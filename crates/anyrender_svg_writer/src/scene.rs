@@ -0,0 +1,429 @@
+use crate::{image, util};
+use anyrender::{Glyph, NormalizedCoord, Paint, PaintRef, PaintScene, glyph_run_outline};
+use kurbo::{Affine, Point, Rect, Shape, Stroke};
+use peniko::{
+    BlendMode, Color, Extend, Fill, FontData, Gradient, GradientKind, ImageBrushRef, ImageQuality,
+    StyleRef,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+/// Identifies an embedded image by its blob id, width, height, format, alpha type and quality (the same blob can
+/// be drawn as differently shaped images)
+type ImageKey = (u64, u32, u32, u8, u8, u8);
+
+/// A [`PaintScene`] that records drawing commands as an SVG document.
+///
+/// Call [`to_svg`](SvgScenePainter::to_svg) to get the document once drawing is complete.
+pub struct SvgScenePainter {
+    width: u32,
+    height: u32,
+    /// Gradients, patterns, images, clip paths and filters referenced by the body
+    defs: String,
+    body: String,
+    /// The number of `<g>` elements that are currently open
+    layer_depth: usize,
+    next_id: usize,
+    /// The ids of the `<image>` definitions for each image that has been drawn
+    images: HashMap<ImageKey, Option<String>>,
+}
+
+impl SvgScenePainter {
+    /// Create a painter for an SVG document of the specified size
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            defs: String::new(),
+            body: String::new(),
+            layer_depth: 0,
+            next_id: 0,
+            images: HashMap::new(),
+        }
+    }
+
+    /// The SVG document. Any layers which have not been popped are closed.
+    pub fn to_svg(&self) -> String {
+        let (width, height) = (self.width, self.height);
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        svg.push('\n');
+        if !self.defs.is_empty() {
+            svg.push_str("<defs>\n");
+            svg.push_str(&self.defs);
+            svg.push_str("</defs>\n");
+        }
+        svg.push_str(&self.body);
+        for _ in 0..self.layer_depth {
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{}", self.next_id)
+    }
+
+    /// Push a `<g>` element clipped by the specified shape
+    fn push_group(&mut self, transform: Affine, clip: &impl Shape, attrs: &str) {
+        let id = self.next_id("clip");
+        let _ = write!(
+            self.defs,
+            r#"<clipPath id="{id}"><path d="{}""#,
+            clip.to_path(DEFAULT_TOLERANCE).to_svg()
+        );
+        util::write_transform(&mut self.defs, "transform", transform);
+        self.defs.push_str("/></clipPath>\n");
+
+        let _ = writeln!(self.body, r#"<g clip-path="url(#{id})"{attrs}>"#);
+        self.layer_depth += 1;
+    }
+
+    /// Write the attributes for painting with `paint`, where `name` is either `fill` or `stroke`. Any paint
+    /// servers that are needed are added to the defs. `bounds` is the area that is painted, in the user
+    /// space of the element.
+    ///
+    /// Returns `None` if nothing should be drawn (custom paints are not supported).
+    fn paint_attrs(
+        &mut self,
+        name: &str,
+        paint: PaintRef<'_>,
+        brush_transform: Option<Affine>,
+        bounds: Rect,
+    ) -> Option<String> {
+        let brush_transform = brush_transform.unwrap_or_default();
+        let mut attrs = String::new();
+        match paint {
+            Paint::Solid(color) => {
+                util::write_color(&mut attrs, name, &format!("{name}-opacity"), color)
+            }
+            Paint::Gradient(gradient) => {
+                if let Some(id) = self.gradient(gradient, brush_transform) {
+                    let _ = write!(attrs, r#" {name}="url(#{id})""#);
+                } else {
+                    // Sweep gradients have no SVG equivalent
+                    let color = gradient
+                        .stops
+                        .first()
+                        .map(|stop| stop.color.to_alpha_color())
+                        .unwrap_or(Color::TRANSPARENT);
+                    util::write_color(&mut attrs, name, &format!("{name}-opacity"), color);
+                }
+            }
+            Paint::Image(image) => {
+                let id = self.image_pattern(image, brush_transform, bounds)?;
+                let _ = write!(attrs, r#" {name}="url(#{id})""#);
+                if image.sampler.alpha < 1.0 {
+                    let _ = write!(attrs, r#" {name}-opacity="{}""#, image.sampler.alpha);
+                }
+            }
+            Paint::Custom(_) => return None,
+        }
+        Some(attrs)
+    }
+
+    /// Add a gradient to the defs, returning its id. Returns `None` for sweep gradients.
+    fn gradient(&mut self, gradient: &Gradient, transform: Affine) -> Option<String> {
+        let id;
+        match gradient.kind {
+            GradientKind::Linear(pos) => {
+                id = self.next_id("gradient");
+                let _ = write!(
+                    self.defs,
+                    r#"<linearGradient id="{id}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}""#,
+                    pos.start.x, pos.start.y, pos.end.x, pos.end.y
+                );
+            }
+            GradientKind::Radial(pos) => {
+                id = self.next_id("gradient");
+                let _ = write!(
+                    self.defs,
+                    r#"<radialGradient id="{id}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}""#,
+                    pos.end_center.x, pos.end_center.y, pos.end_radius
+                );
+                if pos.start_center != pos.end_center || pos.start_radius != 0.0 {
+                    let _ = write!(
+                        self.defs,
+                        r#" fx="{}" fy="{}" fr="{}""#,
+                        pos.start_center.x, pos.start_center.y, pos.start_radius
+                    );
+                }
+            }
+            GradientKind::Sweep(_) => return None,
+        }
+        let _ = write!(
+            self.defs,
+            r#" spreadMethod="{}""#,
+            util::spread_method(gradient.extend)
+        );
+        util::write_transform(&mut self.defs, "gradientTransform", transform);
+        self.defs.push_str(">\n");
+
+        for stop in gradient.stops.iter() {
+            let _ = write!(self.defs, r#"<stop offset="{}""#, stop.offset);
+            util::write_color(
+                &mut self.defs,
+                "stop-color",
+                "stop-opacity",
+                stop.color.to_alpha_color(),
+            );
+            self.defs.push_str("/>\n");
+        }
+
+        let element = match gradient.kind {
+            GradientKind::Linear(_) => "linearGradient",
+            _ => "radialGradient",
+        };
+        let _ = writeln!(self.defs, "</{element}>");
+        Some(id)
+    }
+
+    /// Add a pattern which draws an image to the defs, returning its id. Returns `None` if the image
+    /// couldn't be encoded.
+    fn image_pattern(
+        &mut self,
+        image: ImageBrushRef<'_>,
+        transform: Affine,
+        bounds: Rect,
+    ) -> Option<String> {
+        let image_data = image.image;
+        let key = (
+            image_data.data.id(),
+            image_data.width,
+            image_data.height,
+            image_data.format as u8,
+            image_data.alpha_type as u8,
+            image.sampler.quality as u8,
+        );
+        let image_id = match self.images.get(&key) {
+            Some(id) => id.clone(),
+            None => {
+                // Each image is only embedded once, and referenced by every pattern that draws it
+                let id = image::png_data_uri(image_data).map(|uri| {
+                    let id = self.next_id("image");
+                    let rendering = match image.sampler.quality {
+                        ImageQuality::Low => r#" style="image-rendering:pixelated""#,
+                        ImageQuality::Medium | ImageQuality::High => "",
+                    };
+                    let _ = writeln!(
+                        self.defs,
+                        r#"<image id="{id}" width="{}" height="{}"{rendering} href="{uri}"/>"#,
+                        image_data.width, image_data.height
+                    );
+                    id
+                });
+                self.images.insert(key, id.clone());
+                id
+            }
+        }?;
+
+        // SVG patterns always repeat, so an image which isn't repeated (SVG can't pad or reflect it) gets a tile
+        // which covers the whole painted area
+        let mut tile = Rect::new(0.0, 0.0, image_data.width as f64, image_data.height as f64);
+        if transform.determinant() != 0.0 {
+            let painted = transform.inverse().transform_rect_bbox(bounds);
+            if image.sampler.x_extend == Extend::Pad {
+                tile.x0 = tile.x0.min(painted.x0);
+                tile.x1 = tile.x1.max(painted.x1);
+            }
+            if image.sampler.y_extend == Extend::Pad {
+                tile.y0 = tile.y0.min(painted.y0);
+                tile.y1 = tile.y1.max(painted.y1);
+            }
+        }
+
+        let id = self.next_id("pattern");
+        let _ = write!(
+            self.defs,
+            r#"<pattern id="{id}" patternUnits="userSpaceOnUse""#
+        );
+        if tile.origin() != Point::ZERO {
+            let _ = write!(self.defs, r#" x="{}" y="{}""#, tile.x0, tile.y0);
+        }
+        let _ = write!(
+            self.defs,
+            r#" width="{}" height="{}""#,
+            tile.width(),
+            tile.height()
+        );
+        util::write_transform(&mut self.defs, "patternTransform", transform);
+        let _ = writeln!(self.defs, r##"><use href="#{image_id}"/></pattern>"##);
+        Some(id)
+    }
+
+    fn write_path(&mut self, transform: Affine, shape: &impl Shape, attrs: &str) {
+        let _ = write!(
+            self.body,
+            r#"<path d="{}"{attrs}"#,
+            shape.to_path(DEFAULT_TOLERANCE).to_svg()
+        );
+        util::write_transform(&mut self.body, "transform", transform);
+        self.body.push_str("/>\n");
+    }
+}
+
+impl PaintScene for SvgScenePainter {
+    fn reset(&mut self) {
+        self.defs.clear();
+        self.body.clear();
+        self.layer_depth = 0;
+        self.next_id = 0;
+        self.images.clear();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        let mut attrs = String::new();
+        if alpha < 1.0 {
+            let _ = write!(attrs, r#" opacity="{alpha}""#);
+        }
+        util::write_blend_mode(&mut attrs, blend.into());
+        self.push_group(transform, clip, &attrs);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        self.push_group(transform, clip, "");
+    }
+
+    fn pop_layer(&mut self) {
+        if self.layer_depth > 0 {
+            self.layer_depth -= 1;
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        // Include the joins, which can extend beyond half the stroke width
+        let inflate = style.width * style.miter_limit.max(1.0);
+        let bounds = shape.bounding_box().inflate(inflate, inflate);
+        let Some(paint) = self.paint_attrs("stroke", brush.into(), brush_transform, bounds) else {
+            return;
+        };
+        let mut attrs = String::from(r#" fill="none""#);
+        attrs.push_str(&paint);
+        util::write_stroke_style(&mut attrs, style);
+        self.write_path(transform, shape, &attrs);
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let bounds = shape.bounding_box();
+        let Some(mut attrs) = self.paint_attrs("fill", brush.into(), brush_transform, bounds)
+        else {
+            return;
+        };
+        util::write_fill_rule(&mut attrs, style);
+        self.write_path(transform, shape, &attrs);
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'s mut self,
+        font: &'a FontData,
+        font_size: f32,
+        _hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        brush: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = Glyph>,
+    ) {
+        let outline =
+            glyph_run_outline(font, font_size, normalized_coords, glyph_transform, glyphs);
+        if outline.is_empty() {
+            return;
+        }
+
+        let bounds = outline.bounding_box();
+        let mut attrs = match style.into() {
+            StyleRef::Fill(fill) => {
+                let Some(mut attrs) = self.paint_attrs("fill", brush.into(), None, bounds) else {
+                    return;
+                };
+                util::write_fill_rule(&mut attrs, fill);
+                attrs
+            }
+            StyleRef::Stroke(stroke) => {
+                let inflate = stroke.width * stroke.miter_limit.max(1.0);
+                let bounds = bounds.inflate(inflate, inflate);
+                let Some(paint) = self.paint_attrs("stroke", brush.into(), None, bounds) else {
+                    return;
+                };
+                let mut attrs = String::from(r#" fill="none""#);
+                attrs.push_str(&paint);
+                util::write_stroke_style(&mut attrs, stroke);
+                attrs
+            }
+        };
+        if brush_alpha < 1.0 {
+            let _ = write!(attrs, r#" opacity="{brush_alpha}""#);
+        }
+        self.write_path(transform, &outline, &attrs);
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        brush: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        let rect = rect.abs();
+        let mut attrs = String::new();
+        if std_dev > 0.0 {
+            // The filter region needs to include the blur, which extends 3 standard deviations beyond the rect
+            let id = self.next_id("shadow");
+            let region = rect.inflate(3.0 * std_dev, 3.0 * std_dev);
+            let _ = writeln!(
+                self.defs,
+                r#"<filter id="{id}" filterUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}"><feGaussianBlur stdDeviation="{std_dev}"/></filter>"#,
+                region.x0,
+                region.y0,
+                region.width(),
+                region.height()
+            );
+            let _ = write!(attrs, r#" filter="url(#{id})""#);
+        }
+
+        let _ = write!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}""#,
+            rect.x0,
+            rect.y0,
+            rect.width(),
+            rect.height()
+        );
+        if radius > 0.0 {
+            let _ = write!(self.body, r#" rx="{radius}""#);
+        }
+        util::write_color(&mut self.body, "fill", "fill-opacity", brush);
+        self.body.push_str(&attrs);
+        util::write_transform(&mut self.body, "transform", transform);
+        self.body.push_str("/>\n");
+    }
+}
@@ -0,0 +1,91 @@
+//! Helpers for formatting SVG attributes
+
+use kurbo::{Affine, Cap, Join, Stroke};
+use peniko::{BlendMode, Color, Extend, Fill, Mix};
+use std::fmt::Write;
+
+/// Write a transform attribute (e.g. `transform` or `gradientTransform`), unless it is the identity
+pub(crate) fn write_transform(out: &mut String, name: &str, transform: Affine) {
+    if transform != Affine::IDENTITY {
+        let [a, b, c, d, e, f] = transform.as_coeffs();
+        let _ = write!(out, r#" {name}="matrix({a} {b} {c} {d} {e} {f})""#);
+    }
+}
+
+/// Write a color attribute (e.g. `fill` or `stop-color`) and its corresponding opacity attribute
+pub(crate) fn write_color(out: &mut String, name: &str, opacity_name: &str, color: Color) {
+    let [r, g, b, _] = color.to_rgba8().to_u8_array();
+    let _ = write!(out, r##" {name}="#{r:02x}{g:02x}{b:02x}""##);
+    let alpha = color.components[3];
+    if alpha < 1.0 {
+        let _ = write!(out, r#" {opacity_name}="{alpha}""#);
+    }
+}
+
+pub(crate) fn write_fill_rule(out: &mut String, fill: Fill) {
+    if fill == Fill::EvenOdd {
+        out.push_str(r#" fill-rule="evenodd""#);
+    }
+}
+
+pub(crate) fn write_stroke_style(out: &mut String, stroke: &Stroke) {
+    let _ = write!(out, r#" stroke-width="{}""#, stroke.width);
+    match stroke.join {
+        Join::Bevel => out.push_str(r#" stroke-linejoin="bevel""#),
+        Join::Round => out.push_str(r#" stroke-linejoin="round""#),
+        Join::Miter => {
+            let _ = write!(out, r#" stroke-miterlimit="{}""#, stroke.miter_limit);
+        }
+    }
+    // SVG doesn't support different start and end caps
+    match stroke.start_cap {
+        Cap::Butt => {}
+        Cap::Round => out.push_str(r#" stroke-linecap="round""#),
+        Cap::Square => out.push_str(r#" stroke-linecap="square""#),
+    }
+    if !stroke.dash_pattern.is_empty() {
+        out.push_str(r#" stroke-dasharray=""#);
+        for (i, dash) in stroke.dash_pattern.iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            let _ = write!(out, "{dash}");
+        }
+        out.push('"');
+        if stroke.dash_offset != 0.0 {
+            let _ = write!(out, r#" stroke-dashoffset="{}""#, stroke.dash_offset);
+        }
+    }
+}
+
+/// Write the `style` attribute for a layer's blend mode. Only the mix mode is supported, as SVG has no
+/// equivalent of the compose modes.
+pub(crate) fn write_blend_mode(out: &mut String, blend: BlendMode) {
+    let mode = match blend.mix {
+        Mix::Normal => return,
+        Mix::Multiply => "multiply",
+        Mix::Screen => "screen",
+        Mix::Overlay => "overlay",
+        Mix::Darken => "darken",
+        Mix::Lighten => "lighten",
+        Mix::ColorDodge => "color-dodge",
+        Mix::ColorBurn => "color-burn",
+        Mix::HardLight => "hard-light",
+        Mix::SoftLight => "soft-light",
+        Mix::Difference => "difference",
+        Mix::Exclusion => "exclusion",
+        Mix::Hue => "hue",
+        Mix::Saturation => "saturation",
+        Mix::Color => "color",
+        Mix::Luminosity => "luminosity",
+    };
+    let _ = write!(out, r#" style="mix-blend-mode:{mode}""#);
+}
+
+pub(crate) fn spread_method(extend: Extend) -> &'static str {
+    match extend {
+        Extend::Pad => "pad",
+        Extend::Repeat => "repeat",
+        Extend::Reflect => "reflect",
+    }
+}
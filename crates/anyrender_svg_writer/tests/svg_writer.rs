@@ -0,0 +1,222 @@
+use anyrender::{Glyph, PaintScene};
+use anyrender_svg_writer::{SvgScenePainter, render_to_svg};
+use kurbo::{Affine, Circle, Rect, Stroke};
+use peniko::{
+    Blob, Color, Extend, Fill, FontData, Gradient, ImageAlphaType, ImageBrush, ImageData,
+    ImageFormat, ImageQuality, Mix,
+};
+use skrifa::{FontRef, MetadataProvider};
+
+const ROBOTO: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+
+/// Check that the document is valid by parsing it with usvg
+fn parse(svg: &str) -> usvg::Tree {
+    usvg::Tree::from_str(svg, &usvg::Options::default()).expect("invalid SVG")
+}
+
+#[test]
+fn fills_and_strokes_become_paths() {
+    let svg = render_to_svg(
+        |scene| {
+            scene.fill(
+                Fill::EvenOdd,
+                Affine::translate((10.0, 10.0)),
+                Color::from_rgb8(255, 0, 0),
+                None,
+                &Rect::new(0.0, 0.0, 20.0, 20.0),
+            );
+            scene.stroke(
+                &Stroke::new(2.0),
+                Affine::IDENTITY,
+                Color::from_rgba8(0, 0, 255, 128),
+                None,
+                &Circle::new((50.0, 50.0), 10.0),
+            );
+        },
+        100,
+        100,
+    );
+
+    assert!(svg.contains(r##"fill="#ff0000" fill-rule="evenodd""##));
+    assert!(svg.contains(r##"fill="none" stroke="#0000ff" stroke-opacity="##));
+    assert!(svg.contains(r#"transform="matrix(1 0 0 1 10 10)""#));
+    let tree = parse(&svg);
+    assert_eq!(tree.root().children().len(), 2);
+}
+
+#[test]
+fn layers_become_clipped_groups() {
+    let mut scene = SvgScenePainter::new(100, 100);
+    scene.push_layer(
+        Mix::Multiply,
+        0.5,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, 50.0, 50.0),
+    );
+    scene.push_clip_layer(Affine::IDENTITY, &Circle::new((25.0, 25.0), 20.0));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::BLACK,
+        None,
+        &Rect::new(0.0, 0.0, 100.0, 100.0),
+    );
+    scene.pop_layer();
+    // The outer layer is left open, and closed by `to_svg`
+    let svg = scene.to_svg();
+
+    assert_eq!(svg.matches("<clipPath").count(), 2);
+    assert_eq!(svg.matches("<g ").count(), svg.matches("</g>").count());
+    assert!(svg.contains(r#"opacity="0.5" style="mix-blend-mode:multiply""#));
+    parse(&svg);
+}
+
+#[test]
+fn gradients_and_images_are_defined() {
+    let gradient = Gradient::new_linear((0.0, 0.0), (100.0, 0.0))
+        .with_stops([Color::WHITE, Color::BLACK].as_slice());
+    let image = ImageBrush::new(ImageData {
+        data: Blob::from(vec![255u8; 2 * 2 * 4]),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 2,
+        height: 2,
+    });
+
+    let svg = render_to_svg(
+        |scene| {
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                &gradient,
+                None,
+                &Rect::new(0.0, 0.0, 100.0, 50.0),
+            );
+            scene.draw_image(image.as_ref(), Affine::translate((0.0, 50.0)));
+            scene.draw_image(image.as_ref(), Affine::translate((10.0, 50.0)));
+        },
+        100,
+        100,
+    );
+
+    assert!(svg.contains("<linearGradient"));
+    // The image is only embedded once
+    assert_eq!(svg.matches("data:image/png;base64,").count(), 1);
+    assert_eq!(svg.matches("<pattern").count(), 2);
+    parse(&svg);
+}
+
+#[test]
+fn images_keep_their_quality_and_extend() {
+    let image = ImageData {
+        data: Blob::from(vec![255u8; 2 * 2 * 4]),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 2,
+        height: 2,
+    };
+    let smooth = ImageBrush::new(image.clone()).with_extend(Extend::Repeat);
+    let pixelated = ImageBrush::new(image)
+        .with_quality(ImageQuality::Low)
+        .with_extend(Extend::Pad);
+
+    let svg = render_to_svg(
+        |scene| {
+            let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                smooth.as_ref(),
+                None,
+                &rect,
+            );
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                pixelated.as_ref(),
+                None,
+                &rect,
+            );
+        },
+        100,
+        100,
+    );
+
+    // The same image is embedded once for each quality it is drawn with
+    assert_eq!(svg.matches("data:image/png;base64,").count(), 2);
+    assert_eq!(svg.matches("image-rendering:pixelated").count(), 1);
+    // A repeating image tiles, while a padded image gets a single tile covering the whole fill
+    assert!(svg.contains(r#"patternUnits="userSpaceOnUse" width="2" height="2""#));
+    assert!(svg.contains(r#"patternUnits="userSpaceOnUse" width="10" height="10""#));
+    parse(&svg);
+}
+
+#[test]
+fn images_sharing_a_blob_are_embedded_separately() {
+    let data = Blob::from([255u8, 0, 0, 255].repeat(4));
+    let image = |width, height| ImageData {
+        data: data.clone(),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width,
+        height,
+    };
+
+    let svg = render_to_svg(
+        |scene| {
+            let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+            for image in [image(2, 2), image(4, 1)] {
+                let brush = ImageBrush::new(image);
+                scene.fill(Fill::NonZero, Affine::IDENTITY, brush.as_ref(), None, &rect);
+            }
+        },
+        100,
+        100,
+    );
+
+    assert_eq!(svg.matches("data:image/png;base64,").count(), 2);
+    assert!(svg.contains(r#"width="2" height="2""#));
+    assert!(svg.contains(r#"width="4" height="1""#));
+    parse(&svg);
+}
+
+#[test]
+fn glyphs_become_outlines() {
+    let font = FontData::new(Blob::new(std::sync::Arc::new(ROBOTO)), 0);
+    let glyph_id = FontRef::new(ROBOTO)
+        .unwrap()
+        .charmap()
+        .map('H')
+        .unwrap()
+        .to_u32();
+    let svg = render_to_svg(
+        |scene| {
+            scene.draw_glyphs(
+                &font,
+                16.0,
+                false,
+                &[],
+                Fill::NonZero,
+                Color::BLACK,
+                1.0,
+                Affine::translate((10.0, 50.0)),
+                None,
+                [Glyph {
+                    id: glyph_id,
+                    x: 0.0,
+                    y: 0.0,
+                }]
+                .into_iter(),
+            );
+        },
+        100,
+        100,
+    );
+
+    assert!(!svg.contains("<text"));
+    let tree = parse(&svg);
+    let bounds = tree.root().abs_bounding_box();
+    // The glyph sits on the baseline
+    assert!((bounds.bottom() - 50.0).abs() < 0.5, "{bounds:?}");
+    assert!(bounds.top() < 40.0, "{bounds:?}");
+}
@@ -0,0 +1,178 @@
+//! Comparing RGBA8 images
+
+use std::path::Path;
+
+use image::{Rgba, RgbaImage};
+
+/// How the difference between two pixels is measured
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CompareMode {
+    /// Pixels differ if any channel differs by more than the tolerance
+    Channel { tolerance: u8 },
+    /// Pixels differ if their perceptual difference (measured in the YIQ color space, after blending onto
+    /// white) exceeds the threshold. The threshold ranges from `0.0` (exact) to `1.0` (anything matches),
+    /// with `0.1` being a reasonable default.
+    Perceptual { threshold: f64 },
+}
+
+/// Options for [`compare_images`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CompareOptions {
+    pub mode: CompareMode,
+    /// The number of pixels which may differ before the images are considered to not match
+    pub max_differing_pixels: usize,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompareOptions {
+    /// Options which only accept identical images
+    pub const fn new() -> Self {
+        Self {
+            mode: CompareMode::Channel { tolerance: 0 },
+            max_differing_pixels: 0,
+        }
+    }
+
+    /// Accept pixels whose channels each differ by at most `tolerance`
+    pub const fn with_channel_tolerance(mut self, tolerance: u8) -> Self {
+        self.mode = CompareMode::Channel { tolerance };
+        self
+    }
+
+    /// Compare pixels perceptually (see [`CompareMode::Perceptual`])
+    pub const fn with_perceptual_threshold(mut self, threshold: f64) -> Self {
+        self.mode = CompareMode::Perceptual { threshold };
+        self
+    }
+
+    /// Accept up to `max_differing_pixels` pixels which differ
+    pub const fn with_max_differing_pixels(mut self, max_differing_pixels: usize) -> Self {
+        self.max_differing_pixels = max_differing_pixels;
+        self
+    }
+}
+
+/// The result of comparing two images with [`compare_images`]
+#[derive(Clone, Debug)]
+pub struct ImageComparison {
+    pub width: u32,
+    pub height: u32,
+    /// The number of pixels which differ according to the [`CompareMode`]
+    pub differing_pixels: usize,
+    /// The largest difference in any channel of any pixel
+    pub max_channel_difference: u8,
+    /// Whether the images match according to the [`CompareOptions`]
+    pub matches: bool,
+    /// Whether each pixel differs, in row-major order
+    differing: Vec<bool>,
+}
+
+/// The maximum possible value of [`yiq_delta`]
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+/// Convert a premultiplied RGBA8 pixel to YIQ, after blending it onto white
+fn to_yiq(pixel: &[u8]) -> [f64; 3] {
+    let background = 255.0 * (1.0 - pixel[3] as f64 / 255.0);
+    let [r, g, b] = [0, 1, 2].map(|c| pixel[c] as f64 + background);
+    [
+        r * 0.29889531 + g * 0.58662247 + b * 0.11448223,
+        r * 0.59597799 - g * 0.27417610 - b * 0.32180189,
+        r * 0.21147017 - g * 0.52261711 + b * 0.31114694,
+    ]
+}
+
+/// The squared perceptual difference between two pixels
+/// (from "Measuring perceived color difference using YIQ NTSC transmission color space in mobile applications"
+/// by Y. Kotsarenko and F. Ramos)
+fn yiq_delta(a: &[u8], b: &[u8]) -> f64 {
+    let [y1, i1, q1] = to_yiq(a);
+    let [y2, i2, q2] = to_yiq(b);
+    let (y, i, q) = (y1 - y2, i1 - i2, q1 - q2);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+/// Compare two RGBA8 images of size `width` x `height`, such as those produced by
+/// [`ImageRenderer::render_to_vec`](anyrender::ImageRenderer::render_to_vec).
+///
+/// Panics if either buffer doesn't match the size.
+pub fn compare_images(
+    actual: &[u8],
+    expected: &[u8],
+    width: u32,
+    height: u32,
+    options: &CompareOptions,
+) -> ImageComparison {
+    let len = width as usize * height as usize * 4;
+    assert_eq!(actual.len(), len, "actual image doesn't match the size");
+    assert_eq!(expected.len(), len, "expected image doesn't match the size");
+
+    let mut max_channel_difference = 0;
+    let differing: Vec<bool> = actual
+        .chunks_exact(4)
+        .zip(expected.chunks_exact(4))
+        .map(|(a, b)| {
+            let max = a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).max().unwrap();
+            max_channel_difference = max_channel_difference.max(max);
+            match options.mode {
+                CompareMode::Channel { tolerance } => max > tolerance,
+                CompareMode::Perceptual { threshold } => {
+                    max != 0 && yiq_delta(a, b) > MAX_YIQ_DELTA * threshold * threshold
+                }
+            }
+        })
+        .collect();
+    let differing_pixels = differing.iter().filter(|differs| **differs).count();
+
+    ImageComparison {
+        width,
+        height,
+        differing_pixels,
+        max_channel_difference,
+        matches: differing_pixels <= options.max_differing_pixels,
+        differing,
+    }
+}
+
+impl ImageComparison {
+    /// Create an image showing the expected image, the actual image, and the differing pixels side-by-side.
+    ///
+    /// In the diff panel, differing pixels are highlighted in red on top of a faded copy of the expected image.
+    pub fn diff_image(&self, actual: &[u8], expected: &[u8]) -> RgbaImage {
+        let (width, height) = (self.width, self.height);
+        let mut image = RgbaImage::new(width * 3, height);
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) as usize;
+                let pixel =
+                    |buffer: &[u8]| -> [u8; 4] { buffer[idx * 4..idx * 4 + 4].try_into().unwrap() };
+                image.put_pixel(x, y, Rgba(pixel(expected)));
+                image.put_pixel(x + width, y, Rgba(pixel(actual)));
+
+                let diff = if self.differing[idx] {
+                    [255, 0, 0, 255]
+                } else {
+                    let [luma, ..] = to_yiq(&pixel(expected));
+                    let faded = (255.0 - (255.0 - luma) * 0.1) as u8;
+                    [faded, faded, faded, 255]
+                };
+                image.put_pixel(x + width * 2, y, Rgba(diff));
+            }
+        }
+        image
+    }
+
+    /// Write the [`diff_image`](Self::diff_image) as a PNG to `path`
+    pub fn write_diff_image(
+        &self,
+        actual: &[u8],
+        expected: &[u8],
+        path: impl AsRef<Path>,
+    ) -> Result<(), image::ImageError> {
+        self.diff_image(actual, expected).save(path)
+    }
+}
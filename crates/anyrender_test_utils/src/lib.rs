@@ -0,0 +1,41 @@
+//! Image comparison and snapshot testing utilities for [`anyrender`] backends
+//!
+//! - [`compare_images`] compares two RGBA8 buffers (such as those produced by
+//!   [`ImageRenderer::render_to_vec`](anyrender::ImageRenderer::render_to_vec)) with a per-channel or
+//!   perceptual tolerance, and can produce a diff image highlighting the pixels which differ.
+//! - [`assert_scene_snapshot!`] renders a scene and compares it against a golden image in the calling
+//!   crate's `snapshots` directory. Golden images are created or updated by running the tests with the
+//!   `ANYRENDER_UPDATE_SNAPSHOTS` environment variable set.
+
+mod compare;
+mod snapshot;
+
+pub use compare::*;
+pub use snapshot::*;
+
+/// Render a scene with a [`SnapshotRenderer`] and assert that it matches the golden image
+/// `snapshots/<name>.png` (relative to the calling crate's manifest directory).
+///
+/// On a mismatch, a diff image is written to `snapshots/<name>.diff.png` and the assertion panics. Set the
+/// `ANYRENDER_UPDATE_SNAPSHOTS` environment variable to create or update the golden image instead.
+///
+/// ```no_run
+/// # use anyrender_test_utils::{SnapshotRenderer, assert_scene_snapshot};
+/// # fn example<R: anyrender::ImageRenderer>(scene: anyrender::Scene) {
+/// let mut renderer = SnapshotRenderer::<R>::new(200, 100);
+/// assert_scene_snapshot!(scene, renderer, "button");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_scene_snapshot {
+    ($scene:expr, $renderer:expr, $name:expr $(,)?) => {
+        if let Err(err) = $crate::check_scene_snapshot(
+            &$scene,
+            &mut $renderer,
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots"),
+            $name,
+        ) {
+            panic!("{}", err);
+        }
+    };
+}
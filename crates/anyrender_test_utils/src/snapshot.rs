@@ -0,0 +1,198 @@
+//! Comparing rendered scenes against golden images
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyrender::{ImageRenderer, PaintScene, Scene};
+use image::RgbaImage;
+use kurbo::Affine;
+
+use crate::{CompareOptions, ImageComparison, compare_images};
+
+/// If this environment variable is set then snapshot assertions create or overwrite golden images instead
+/// of comparing against them
+pub const UPDATE_SNAPSHOTS_ENV_VAR: &str = "ANYRENDER_UPDATE_SNAPSHOTS";
+
+/// An [`ImageRenderer`] along with the size that scenes are rendered at and the options used to compare
+/// them against golden images
+pub struct SnapshotRenderer<R: ImageRenderer> {
+    renderer: R,
+    width: u32,
+    height: u32,
+    options: CompareOptions,
+}
+
+impl<R: ImageRenderer> SnapshotRenderer<R> {
+    /// Create a renderer for snapshots of size `width` x `height` which must match exactly
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            renderer: R::new(width, height),
+            width,
+            height,
+            options: CompareOptions::new(),
+        }
+    }
+
+    /// Set the options used to compare snapshots against golden images
+    pub fn with_options(mut self, options: CompareOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn options(&self) -> &CompareOptions {
+        &self.options
+    }
+
+    /// Render a scene to an RGBA8 buffer
+    pub fn render(&mut self, scene: &Scene) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        self.renderer.render_to_vec(
+            |painter| painter.append_scene(scene.clone(), Affine::IDENTITY),
+            &mut buffer,
+        );
+        self.renderer.reset();
+        buffer
+    }
+}
+
+/// The reason a snapshot assertion failed
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// There is no golden image (and [`UPDATE_SNAPSHOTS_ENV_VAR`] isn't set)
+    Missing(PathBuf),
+    /// The golden image has a different size to the rendered scene
+    SizeMismatch {
+        path: PathBuf,
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// The rendered scene doesn't match the golden image. A diff image was written to `diff_path`.
+    Mismatch {
+        path: PathBuf,
+        diff_path: PathBuf,
+        comparison: ImageComparison,
+    },
+    Image(image::ImageError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Missing(path) => write!(
+                f,
+                "snapshot {} doesn't exist (set {UPDATE_SNAPSHOTS_ENV_VAR}=1 to create it)",
+                path.display()
+            ),
+            SnapshotError::SizeMismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "snapshot {} is {}x{} but the scene was rendered at {}x{}",
+                path.display(),
+                expected.0,
+                expected.1,
+                actual.0,
+                actual.1
+            ),
+            SnapshotError::Mismatch {
+                path,
+                diff_path,
+                comparison,
+            } => write!(
+                f,
+                "snapshot {} doesn't match: {} pixels differ (max channel difference {}), diff written to {}",
+                path.display(),
+                comparison.differing_pixels,
+                comparison.max_channel_difference,
+                diff_path.display()
+            ),
+            SnapshotError::Image(e) => write!(f, "image error: {}", e),
+            SnapshotError::Io(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Image(e) => Some(e),
+            SnapshotError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for SnapshotError {
+    fn from(e: image::ImageError) -> Self {
+        SnapshotError::Image(e)
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+/// Render `scene` and compare it against the golden image `<dir>/<name>.png`.
+///
+/// If [`UPDATE_SNAPSHOTS_ENV_VAR`] is set then the golden image is created or overwritten instead. On a
+/// mismatch, a diff image is written to `<dir>/<name>.diff.png` (and removed again once the snapshot
+/// matches). This is usually called through [`assert_scene_snapshot!`](crate::assert_scene_snapshot).
+pub fn check_scene_snapshot<R: ImageRenderer>(
+    scene: &Scene,
+    renderer: &mut SnapshotRenderer<R>,
+    dir: impl AsRef<Path>,
+    name: &str,
+) -> Result<(), SnapshotError> {
+    let dir = dir.as_ref();
+    let path = dir.join(format!("{name}.png"));
+    let diff_path = dir.join(format!("{name}.diff.png"));
+    let (width, height) = (renderer.width, renderer.height);
+    let actual = renderer.render(scene);
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV_VAR).is_some() {
+        std::fs::create_dir_all(dir)?;
+        RgbaImage::from_raw(width, height, actual)
+            .expect("rendered image has the wrong size")
+            .save(&path)?;
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+
+    if !path.exists() {
+        return Err(SnapshotError::Missing(path));
+    }
+    let expected = image::open(&path)?.to_rgba8();
+    if expected.dimensions() != (width, height) {
+        return Err(SnapshotError::SizeMismatch {
+            path,
+            expected: expected.dimensions(),
+            actual: (width, height),
+        });
+    }
+
+    let comparison = compare_images(&actual, &expected, width, height, &renderer.options);
+    if comparison.matches {
+        let _ = std::fs::remove_file(&diff_path);
+        Ok(())
+    } else {
+        comparison.write_diff_image(&actual, &expected, &diff_path)?;
+        Err(SnapshotError::Mismatch {
+            path,
+            diff_path,
+            comparison,
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use anyrender::{PaintScene, Scene};
+use anyrender_test_utils::{
+    CompareOptions, SnapshotError, SnapshotRenderer, UPDATE_SNAPSHOTS_ENV_VAR,
+    assert_scene_snapshot, check_scene_snapshot, compare_images,
+};
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+use kurbo::{Affine, Circle, Rect};
+use peniko::{Color, Fill};
+
+fn solid(width: u32, height: u32, pixel: [u8; 4]) -> Vec<u8> {
+    pixel.repeat((width * height) as usize)
+}
+
+#[test]
+fn channel_tolerance_and_max_differing_pixels() {
+    let expected = solid(4, 4, [100, 100, 100, 255]);
+    let mut actual = expected.clone();
+    actual[0] = 103;
+    actual[4] = 110;
+
+    let exact = compare_images(&actual, &expected, 4, 4, &CompareOptions::new());
+    assert_eq!(exact.differing_pixels, 2);
+    assert_eq!(exact.max_channel_difference, 10);
+    assert!(!exact.matches);
+
+    let tolerant = CompareOptions::new().with_channel_tolerance(3);
+    assert_eq!(
+        compare_images(&actual, &expected, 4, 4, &tolerant).differing_pixels,
+        1
+    );
+    let allowed = tolerant.with_max_differing_pixels(1);
+    assert!(compare_images(&actual, &expected, 4, 4, &allowed).matches);
+}
+
+#[test]
+fn perceptual_mode_ignores_invisible_differences() {
+    let expected = solid(2, 2, [0, 0, 0, 0]);
+    // Slightly different fully transparent pixels and a small change in a dark color
+    let mut actual = expected.clone();
+    actual[..4].copy_from_slice(&[0, 0, 0, 1]);
+    let options = CompareOptions::new().with_perceptual_threshold(0.1);
+    assert!(compare_images(&actual, &expected, 2, 2, &options).matches);
+
+    // Black instead of transparent (i.e. white) is a large perceptual difference
+    actual[..4].copy_from_slice(&[0, 0, 0, 255]);
+    let comparison = compare_images(&actual, &expected, 2, 2, &options);
+    assert_eq!(comparison.differing_pixels, 1);
+
+    let diff = comparison.diff_image(&actual, &expected);
+    assert_eq!(diff.dimensions(), (6, 2));
+    assert_eq!(diff.get_pixel(4, 0).0, [255, 0, 0, 255]);
+    assert_ne!(diff.get_pixel(5, 0).0, [255, 0, 0, 255]);
+}
+
+fn scene(color: Color) -> Scene {
+    let mut scene = Scene::new();
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::WHITE,
+        None,
+        &Rect::new(0.0, 0.0, 32.0, 32.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        color,
+        None,
+        &Circle::new((16.0, 16.0), 10.0),
+    );
+    scene
+}
+
+#[test]
+fn scene_snapshot() {
+    let mut renderer = SnapshotRenderer::<VelloCpuImageRenderer>::new(32, 32)
+        .with_options(CompareOptions::new().with_channel_tolerance(1));
+    assert_scene_snapshot!(scene(Color::from_rgb8(200, 40, 40)), renderer, "circle");
+}
+
+#[test]
+fn scene_snapshot_mismatch_writes_diff() {
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV_VAR).is_some() {
+        return;
+    }
+    let dir = std::env::temp_dir().join("anyrender_test_utils_mismatch");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut renderer = SnapshotRenderer::<VelloCpuImageRenderer>::new(32, 32);
+    let golden = renderer.render(&scene(Color::from_rgb8(200, 40, 40)));
+    image::RgbaImage::from_raw(32, 32, golden)
+        .unwrap()
+        .save(dir.join("circle.png"))
+        .unwrap();
+
+    let result = check_scene_snapshot(
+        &scene(Color::from_rgb8(40, 40, 200)),
+        &mut renderer,
+        &dir,
+        "circle",
+    );
+    let Err(SnapshotError::Mismatch {
+        diff_path,
+        comparison,
+        ..
+    }) = result
+    else {
+        panic!("expected a mismatch, got {result:?}");
+    };
+    assert!(comparison.differing_pixels > 0);
+    assert!(diff_path.exists());
+
+    let missing = check_scene_snapshot(&Scene::new(), &mut renderer, &dir, "missing");
+    assert!(matches!(missing, Err(SnapshotError::Missing(_))));
+    std::fs::remove_dir_all(dir).unwrap();
+}
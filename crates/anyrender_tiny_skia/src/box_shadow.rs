@@ -0,0 +1,152 @@
+//! Rendering blurred rounded rectangles
+//!
+//! tiny-skia has no blur support, so box shadows are evaluated analytically per-pixel using the same
+//! approximation as vello (adapted from <https://git.sr.ht/~raph/blurrr>).
+
+use kurbo::{Affine, Point, Rect};
+use peniko::Color;
+use tiny_skia::{Mask, Pixmap, PixmapPaint, Transform};
+
+/// Approximate the erf function.
+///
+/// See <https://raphlinus.github.io/audio/2018/09/05/sigmoid.html> for an explanation of this approximation.
+fn compute_erf7(x: f32) -> f32 {
+    let x = x * core::f32::consts::FRAC_2_SQRT_PI;
+    let xx = x * x;
+    let x = x + (0.24295 + (0.03395 + 0.0104 * xx) * xx) * (x * xx);
+    x / (1.0 + x * x).sqrt()
+}
+
+/// The parameters of a blurred rounded rectangle, in the rectangle's local coordinate space
+struct BlurredRoundedRect {
+    exponent: f32,
+    recip_exponent: f32,
+    scale: f32,
+    std_dev_inv: f32,
+    min_edge: f32,
+    w: f32,
+    h: f32,
+    width: f32,
+    height: f32,
+    r1: f32,
+}
+
+impl BlurredRoundedRect {
+    fn new(width: f32, height: f32, radius: f32, std_dev: f32) -> Self {
+        let radius = radius.min(0.5 * width.min(height));
+        // To avoid divide by 0
+        let std_dev = std_dev.max(1e-6);
+
+        let min_edge = width.min(height);
+        let rmax = 0.5 * min_edge;
+        let r0 = radius.hypot(std_dev * 1.15).min(rmax);
+        let r1 = radius.hypot(std_dev * 2.0).min(rmax);
+        let exponent = 2.0 * r1 / r0;
+        let std_dev_inv = std_dev.recip();
+
+        // Pull in long end (make less eccentric)
+        let delta = 1.25
+            * std_dev
+            * ((-(0.5 * std_dev_inv * width).powi(2)).exp()
+                - (-(0.5 * std_dev_inv * height).powi(2)).exp());
+        let w = width + delta.min(0.0);
+        let h = height - delta.max(0.0);
+        let scale = 0.5 * compute_erf7(std_dev_inv * 0.5 * (w.max(h) - 0.5 * radius));
+
+        Self {
+            exponent,
+            recip_exponent: exponent.recip(),
+            scale,
+            std_dev_inv,
+            min_edge,
+            w,
+            h,
+            width,
+            height,
+            r1,
+        }
+    }
+
+    /// The coverage at `point`, relative to the top left corner of the rectangle
+    fn alpha(&self, point: Point) -> f32 {
+        let x = point.x as f32 - 0.5 * self.width;
+        let y = point.y as f32 - 0.5 * self.height;
+        let x0 = self.r1 + x.abs() - 0.5 * self.w;
+        let y0 = self.r1 + y.abs() - 0.5 * self.h;
+        let x1 = x0.max(0.0);
+        let y1 = y0.max(0.0);
+
+        let d_pos = (x1.powf(self.exponent) + y1.powf(self.exponent)).powf(self.recip_exponent);
+        let d_neg = x0.max(y0).min(0.0);
+        let d = d_pos + d_neg - self.r1;
+        self.scale
+            * (compute_erf7(self.std_dev_inv * (self.min_edge + d))
+                - compute_erf7(self.std_dev_inv * d))
+    }
+}
+
+/// Draw a blurred rounded rectangle into `target`
+pub(crate) fn draw_box_shadow(
+    target: &mut Pixmap,
+    mask: Option<&Mask>,
+    transform: Affine,
+    rect: Rect,
+    color: Color,
+    radius: f64,
+    std_dev: f64,
+) {
+    let rect = rect.abs();
+    if rect.is_zero_area() || transform.determinant() == 0.0 {
+        return;
+    }
+
+    // The blur is negligible beyond three standard deviations
+    let bounds = transform
+        .transform_rect_bbox(rect.inflate(3.0 * std_dev, 3.0 * std_dev))
+        .expand()
+        .intersect(Rect::new(
+            0.0,
+            0.0,
+            target.width() as f64,
+            target.height() as f64,
+        ));
+    if bounds.is_zero_area() {
+        return;
+    }
+    let Some(mut shadow) = Pixmap::new(bounds.width() as u32, bounds.height() as u32) else {
+        return;
+    };
+
+    let blurred = BlurredRoundedRect::new(
+        rect.width() as f32,
+        rect.height() as f32,
+        radius as f32,
+        std_dev as f32,
+    );
+    let to_local = Affine::translate((-rect.x0, -rect.y0)) * transform.inverse();
+    let [r, g, b, a] = color.components;
+    let width = shadow.width() as usize;
+    for (idx, pixel) in shadow.data_mut().chunks_exact_mut(4).enumerate() {
+        let device = Point::new(
+            bounds.x0 + (idx % width) as f64 + 0.5,
+            bounds.y0 + (idx / width) as f64 + 0.5,
+        );
+        let alpha = a * blurred.alpha(to_local * device).clamp(0.0, 1.0);
+        let premultiply = |c: f32| (c.clamp(0.0, 1.0) * alpha * 255.0).round() as u8;
+        pixel.copy_from_slice(&[
+            premultiply(r),
+            premultiply(g),
+            premultiply(b),
+            premultiply(1.0),
+        ]);
+    }
+
+    target.draw_pixmap(
+        bounds.x0 as i32,
+        bounds.y0 as i32,
+        shadow.as_ref(),
+        &PixmapPaint::default(),
+        Transform::identity(),
+        mask,
+    );
+}
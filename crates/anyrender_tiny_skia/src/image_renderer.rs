@@ -0,0 +1,147 @@
+use crate::TinySkiaScenePainter;
+use anyrender::{ImageRenderer, PaintScene, PixelRegion, RenderError};
+use debug_timer::debug_timer;
+use kurbo::{Affine, Rect};
+
+pub struct TinySkiaImageRenderer {
+    scene: TinySkiaScenePainter,
+    /// Scene used for rendering regions (kept to reuse its pixmap and image cache between renders)
+    region_scene: Option<TinySkiaScenePainter>,
+    width: u32,
+    height: u32,
+}
+
+impl ImageRenderer for TinySkiaImageRenderer {
+    type ScenePainter<'a> = TinySkiaScenePainter;
+
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            scene: TinySkiaScenePainter::new(width, height),
+            region_scene: None,
+            width,
+            height,
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.scene.resize(width, height);
+        self.width = width;
+        self.height = height;
+    }
+
+    fn reset(&mut self) {
+        self.scene.reset();
+    }
+
+    fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F, buffer: &mut [u8]) {
+        self.try_render(draw_fn, buffer)
+            .expect("Error rendering to buffer");
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        buffer: &mut [u8],
+    ) -> Result<(), RenderError> {
+        let len = self.width as usize * self.height as usize * 4;
+        if buffer.len() < len {
+            return Err(RenderError::render(format!(
+                "buffer of {} bytes is too small for a {}x{} image",
+                buffer.len(),
+                self.width,
+                self.height
+            )));
+        }
+
+        debug_timer!(timer, feature = "log_frame_times");
+
+        self.scene.base_transform = Affine::IDENTITY;
+        draw_fn(&mut self.scene);
+        timer.record_time("render");
+
+        // tiny-skia pixmaps are premultiplied RGBA8, which matches the output format
+        buffer[..len].copy_from_slice(&self.scene.pixmap().data()[..len]);
+        timer.record_time("copy");
+
+        timer.print_times("tiny-skia: ");
+
+        Ok(())
+    }
+
+    fn render_to_vec<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        buffer: &mut Vec<u8>,
+    ) {
+        buffer.resize(self.width as usize * self.height as usize * 4, 0);
+        self.render(draw_fn, buffer);
+    }
+
+    fn render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) {
+        self.try_render_region(draw_fn, region, buffer, stride)
+            .expect("Error rendering region");
+    }
+
+    fn try_render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), RenderError> {
+        let Some(region) = PixelRegion::new(region, self.width, self.height) else {
+            return Ok(());
+        };
+
+        let row_len = region.width as usize * 4;
+        let row_end = region.x as usize * 4 + row_len;
+        let required_len = (region.y + region.height - 1) as usize * stride + row_end;
+        if stride < row_end || buffer.len() < required_len {
+            return Err(RenderError::render(format!(
+                "buffer of {} bytes with a stride of {stride} is too small for a {}x{} image",
+                buffer.len(),
+                self.width,
+                self.height
+            )));
+        }
+
+        debug_timer!(timer, feature = "log_frame_times");
+
+        // Render into a region-sized pixmap with all drawing offset by the region's origin
+        let region_scene = self
+            .region_scene
+            .get_or_insert_with(|| TinySkiaScenePainter::new(region.width, region.height));
+        if (
+            region_scene.pixmap().width(),
+            region_scene.pixmap().height(),
+        ) != (region.width, region.height)
+        {
+            region_scene.resize(region.width, region.height);
+        }
+        region_scene.reset();
+        region_scene.base_transform = Affine::translate((-(region.x as f64), -(region.y as f64)));
+        draw_fn(region_scene);
+        timer.record_time("render");
+
+        for (row, src) in region_scene
+            .pixmap()
+            .data()
+            .chunks_exact(row_len)
+            .enumerate()
+        {
+            let start = (region.y as usize + row) * stride + region.x as usize * 4;
+            buffer[start..start + row_len].copy_from_slice(src);
+        }
+        timer.record_time("copy");
+
+        timer.print_times("tiny-skia (region): ");
+
+        Ok(())
+    }
+}
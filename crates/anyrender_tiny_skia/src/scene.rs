@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+
+use anyrender::{NormalizedCoord, Paint, PaintRef, PaintScene, glyph_run_outline};
+use kurbo::{Affine, Cap, Join, PathEl, Rect, Shape, Stroke};
+use peniko::{
+    BlendMode, Color, Compose, Extend, Fill, FontData, Gradient, GradientKind, ImageAlphaType,
+    ImageBrushRef, ImageData, ImageFormat, ImageQuality, Mix, StyleRef, color::Srgb,
+};
+use tiny_skia::{
+    FillRule, FilterQuality, GradientStop, LinearGradient, Mask, Pattern, Pixmap, PixmapPaint,
+    RadialGradient, Shader, SpreadMode, Transform,
+};
+
+use crate::box_shadow::draw_box_shadow;
+
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+fn to_transform(affine: Affine) -> Transform {
+    let [a, b, c, d, e, f] = affine.as_coeffs().map(|coeff| coeff as f32);
+    Transform::from_row(a, b, c, d, e, f)
+}
+
+fn to_path(shape: &impl Shape) -> Option<tiny_skia::Path> {
+    let mut builder = tiny_skia::PathBuilder::new();
+    for el in shape.path_elements(DEFAULT_TOLERANCE) {
+        match el {
+            PathEl::MoveTo(p) => builder.move_to(p.x as f32, p.y as f32),
+            PathEl::LineTo(p) => builder.line_to(p.x as f32, p.y as f32),
+            PathEl::QuadTo(p1, p2) => {
+                builder.quad_to(p1.x as f32, p1.y as f32, p2.x as f32, p2.y as f32);
+            }
+            PathEl::CurveTo(p1, p2, p3) => builder.cubic_to(
+                p1.x as f32,
+                p1.y as f32,
+                p2.x as f32,
+                p2.y as f32,
+                p3.x as f32,
+                p3.y as f32,
+            ),
+            PathEl::ClosePath => builder.close(),
+        }
+    }
+    builder.finish()
+}
+
+fn to_fill_rule(fill: Fill) -> FillRule {
+    match fill {
+        Fill::NonZero => FillRule::Winding,
+        Fill::EvenOdd => FillRule::EvenOdd,
+    }
+}
+
+fn to_stroke(stroke: &Stroke) -> tiny_skia::Stroke {
+    tiny_skia::Stroke {
+        width: stroke.width as f32,
+        miter_limit: stroke.miter_limit as f32,
+        // tiny-skia doesn't support different start and end caps
+        line_cap: match stroke.start_cap {
+            Cap::Butt => tiny_skia::LineCap::Butt,
+            Cap::Square => tiny_skia::LineCap::Square,
+            Cap::Round => tiny_skia::LineCap::Round,
+        },
+        line_join: match stroke.join {
+            Join::Bevel => tiny_skia::LineJoin::Bevel,
+            Join::Miter => tiny_skia::LineJoin::Miter,
+            Join::Round => tiny_skia::LineJoin::Round,
+        },
+        dash: tiny_skia::StrokeDash::new(
+            stroke
+                .dash_pattern
+                .iter()
+                .map(|dash| *dash as f32)
+                .collect(),
+            stroke.dash_offset as f32,
+        ),
+    }
+}
+
+fn to_color(color: Color) -> tiny_skia::Color {
+    let [r, g, b, a] = color.components.map(|c| c.clamp(0.0, 1.0));
+    tiny_skia::Color::from_rgba(r, g, b, a).unwrap_or(tiny_skia::Color::TRANSPARENT)
+}
+
+fn to_spread_mode(extend: Extend) -> SpreadMode {
+    match extend {
+        Extend::Pad => SpreadMode::Pad,
+        Extend::Repeat => SpreadMode::Repeat,
+        Extend::Reflect => SpreadMode::Reflect,
+    }
+}
+
+fn to_blend_mode(blend: BlendMode) -> tiny_skia::BlendMode {
+    use tiny_skia::BlendMode as B;
+    // tiny-skia can't combine a mix with a non-default compose, so the mix takes precedence
+    match blend.mix {
+        Mix::Normal => match blend.compose {
+            Compose::Clear => B::Clear,
+            Compose::Copy => B::Source,
+            Compose::Dest => B::Destination,
+            Compose::SrcOver => B::SourceOver,
+            Compose::DestOver => B::DestinationOver,
+            Compose::SrcIn => B::SourceIn,
+            Compose::DestIn => B::DestinationIn,
+            Compose::SrcOut => B::SourceOut,
+            Compose::DestOut => B::DestinationOut,
+            Compose::SrcAtop => B::SourceAtop,
+            Compose::DestAtop => B::DestinationAtop,
+            Compose::Xor => B::Xor,
+            Compose::Plus | Compose::PlusLighter => B::Plus,
+        },
+        Mix::Multiply => B::Multiply,
+        Mix::Screen => B::Screen,
+        Mix::Overlay => B::Overlay,
+        Mix::Darken => B::Darken,
+        Mix::Lighten => B::Lighten,
+        Mix::ColorDodge => B::ColorDodge,
+        Mix::ColorBurn => B::ColorBurn,
+        Mix::HardLight => B::HardLight,
+        Mix::SoftLight => B::SoftLight,
+        Mix::Difference => B::Difference,
+        Mix::Exclusion => B::Exclusion,
+        Mix::Hue => B::Hue,
+        Mix::Saturation => B::Saturation,
+        Mix::Color => B::Color,
+        Mix::Luminosity => B::Luminosity,
+    }
+}
+
+fn gradient_shader(gradient: &Gradient, transform: Transform) -> Option<Shader<'static>> {
+    let stops = |offset_of: &dyn Fn(f32) -> f32| {
+        gradient
+            .stops
+            .iter()
+            .map(|stop| {
+                GradientStop::new(
+                    offset_of(stop.offset),
+                    to_color(stop.color.to_alpha_color::<Srgb>()),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let spread_mode = to_spread_mode(gradient.extend);
+
+    match gradient.kind {
+        GradientKind::Linear(pos) => LinearGradient::new(
+            tiny_skia::Point::from_xy(pos.start.x as f32, pos.start.y as f32),
+            tiny_skia::Point::from_xy(pos.end.x as f32, pos.end.y as f32),
+            stops(&|offset| offset),
+            spread_mode,
+            transform,
+        ),
+        GradientKind::Radial(pos) => {
+            // tiny-skia's radial gradients always start with a radius of zero, so a non-zero start radius is
+            // emulated by remapping the stops (which is exact for concentric gradients)
+            let start = pos.start_radius / pos.end_radius;
+            let start = if start.is_finite() {
+                start.clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            RadialGradient::new(
+                tiny_skia::Point::from_xy(pos.start_center.x as f32, pos.start_center.y as f32),
+                tiny_skia::Point::from_xy(pos.end_center.x as f32, pos.end_center.y as f32),
+                pos.end_radius,
+                stops(&|offset| start + offset * (1.0 - start)),
+                spread_mode,
+                transform,
+            )
+        }
+        // TODO: sweep gradients (tiny-skia doesn't support them, so they are drawn with their first color)
+        GradientKind::Sweep(_) => gradient
+            .stops
+            .first()
+            .map(|stop| Shader::SolidColor(to_color(stop.color.to_alpha_color::<Srgb>()))),
+    }
+}
+
+/// Convert an image to a premultiplied RGBA pixmap. Returns `None` if the image is empty or the image data
+/// doesn't match its dimensions.
+fn image_to_pixmap(image: &ImageData) -> Option<Pixmap> {
+    let data = image.data.data();
+    if data.len() != image.width as usize * image.height as usize * 4 {
+        return None;
+    }
+    let mut pixmap = Pixmap::new(image.width, image.height)?;
+    for (src, dst) in data
+        .chunks_exact(4)
+        .zip(pixmap.data_mut().chunks_exact_mut(4))
+    {
+        let [mut r, g, mut b, a] = [src[0], src[1], src[2], src[3]];
+        if image.format == ImageFormat::Bgra8 {
+            std::mem::swap(&mut r, &mut b);
+        }
+        if image.alpha_type == ImageAlphaType::Alpha && a != 255 {
+            let premultiply = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+            dst.copy_from_slice(&[premultiply(r), premultiply(g), premultiply(b), a]);
+        } else {
+            dst.copy_from_slice(&[r, g, b, a]);
+        }
+    }
+    Some(pixmap)
+}
+
+/// Converted images, keyed by the id of their data
+///
+/// Images which aren't drawn in a frame are evicted when the next frame starts.
+#[derive(Default)]
+struct ImageCache {
+    current: HashMap<u64, Option<Pixmap>>,
+    previous: HashMap<u64, Option<Pixmap>>,
+}
+
+impl ImageCache {
+    fn next_frame(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    fn get(&mut self, image: &ImageData) -> Option<&Pixmap> {
+        let id = image.data.id();
+        self.current
+            .entry(id)
+            .or_insert_with(|| {
+                self.previous
+                    .remove(&id)
+                    .unwrap_or_else(|| image_to_pixmap(image))
+            })
+            .as_ref()
+    }
+}
+
+fn image_shader<'a>(
+    images: &'a mut ImageCache,
+    image: ImageBrushRef<'_>,
+    transform: Transform,
+) -> Option<Shader<'a>> {
+    let pixmap = images.get(image.image)?;
+    let quality = match image.sampler.quality {
+        ImageQuality::Low => FilterQuality::Nearest,
+        ImageQuality::Medium => FilterQuality::Bilinear,
+        ImageQuality::High => FilterQuality::Bicubic,
+    };
+    // tiny-skia patterns have a single spread mode for both axes
+    Some(Pattern::new(
+        pixmap.as_ref(),
+        to_spread_mode(image.sampler.x_extend),
+        quality,
+        image.sampler.alpha,
+        transform,
+    ))
+}
+
+/// Convert a paint to a tiny-skia paint. Returns `None` if nothing should be drawn.
+fn to_paint<'a>(
+    images: &'a mut ImageCache,
+    paint: PaintRef<'_>,
+    brush_transform: Option<Affine>,
+) -> Option<tiny_skia::Paint<'a>> {
+    let transform = to_transform(brush_transform.unwrap_or(Affine::IDENTITY));
+    let shader = match paint {
+        Paint::Solid(color) => Shader::SolidColor(to_color(color)),
+        Paint::Gradient(gradient) => gradient_shader(gradient, transform)?,
+        Paint::Image(image) => image_shader(images, image, transform)?,
+        // TODO: custom paint
+        Paint::Custom(_) => return None,
+    };
+    Some(tiny_skia::Paint {
+        shader,
+        ..Default::default()
+    })
+}
+
+/// A layer pushed by [`PaintScene::push_layer`] or [`PaintScene::push_clip_layer`]
+struct Layer {
+    /// The pixmap that the layer's content is drawn into, along with how it is composited into its parent
+    /// when it is popped. `None` for clip layers, which draw directly into their parent.
+    group: Option<(Pixmap, tiny_skia::BlendMode, f32)>,
+    /// The clip of the layer, intersected with the clips of all enclosing layers. `None` if the clip couldn't
+    /// be converted to a path, in which case nothing is drawn in the layer.
+    mask: Option<Mask>,
+}
+
+/// A [`PaintScene`] that paints into a [`tiny_skia::Pixmap`].
+///
+/// Drawing commands are rasterized immediately, with layers rendered into intermediate pixmaps which are
+/// composited into their parent when they are popped.
+pub struct TinySkiaScenePainter {
+    pixmap: Pixmap,
+    layers: Vec<Layer>,
+    images: ImageCache,
+    /// A transform applied to all drawing commands (used to offset region renders)
+    pub(crate) base_transform: Affine,
+}
+
+impl TinySkiaScenePainter {
+    /// Create a painter with a transparent pixmap of the specified size
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            pixmap: new_pixmap(width, height),
+            layers: Vec::new(),
+            images: ImageCache::default(),
+            base_transform: Affine::IDENTITY,
+        }
+    }
+
+    /// The pixmap that is painted into
+    pub fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        self.pixmap = new_pixmap(width, height);
+        self.layers.clear();
+    }
+
+    pub fn finish(self) -> Pixmap {
+        self.pixmap
+    }
+
+    fn transform(&self, transform: Affine) -> Transform {
+        to_transform(self.base_transform * transform)
+    }
+
+    /// Create the mask for a layer with the specified clip, intersected with the current clip
+    fn clip_mask(&self, transform: Affine, clip: &impl Shape) -> Option<Mask> {
+        let path = to_path(clip)?;
+        let transform = self.transform(transform);
+        let mut mask = match self.layers.last() {
+            Some(layer) => layer.mask.clone()?,
+            None => {
+                let mut mask = Mask::new(self.pixmap.width(), self.pixmap.height())?;
+                mask.data_mut().fill(255);
+                mask
+            }
+        };
+        mask.intersect_path(&path, FillRule::Winding, true, transform);
+        Some(mask)
+    }
+}
+
+/// The pixmap that the innermost layer with a group draws into
+fn group_pixmap<'a>(root: &'a mut Pixmap, layers: &'a mut [Layer]) -> &'a mut Pixmap {
+    layers
+        .iter_mut()
+        .rev()
+        .find_map(|layer| layer.group.as_mut().map(|(pixmap, ..)| pixmap))
+        .unwrap_or(root)
+}
+
+/// The pixmap that drawing commands currently draw into, and the mask that they should be drawn with.
+/// Returns `None` if the current layer is clipped out entirely.
+fn target<'a>(
+    root: &'a mut Pixmap,
+    layers: &'a mut [Layer],
+) -> Option<(&'a mut Pixmap, Option<&'a Mask>)> {
+    let Some((top, parents)) = layers.split_last_mut() else {
+        return Some((root, None));
+    };
+    let mask = top.mask.as_ref()?;
+    let pixmap = match &mut top.group {
+        Some((pixmap, ..)) => pixmap,
+        None => group_pixmap(root, parents),
+    };
+    Some((pixmap, Some(mask)))
+}
+
+fn new_pixmap(width: u32, height: u32) -> Pixmap {
+    // tiny-skia doesn't support empty pixmaps
+    Pixmap::new(width.max(1), height.max(1)).unwrap()
+}
+
+impl PaintScene for TinySkiaScenePainter {
+    fn reset(&mut self) {
+        self.pixmap.fill(tiny_skia::Color::TRANSPARENT);
+        self.layers.clear();
+        self.images.next_frame();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        let mask = self.clip_mask(transform, clip);
+        let pixmap = new_pixmap(self.pixmap.width(), self.pixmap.height());
+        self.layers.push(Layer {
+            group: Some((pixmap, to_blend_mode(blend.into()), alpha)),
+            mask,
+        });
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        let mask = self.clip_mask(transform, clip);
+        self.layers.push(Layer { group: None, mask });
+    }
+
+    fn pop_layer(&mut self) {
+        let Some(Layer {
+            group: Some((pixmap, blend_mode, opacity)),
+            mask: Some(mask),
+        }) = self.layers.pop()
+        else {
+            return;
+        };
+        let paint = PixmapPaint {
+            opacity,
+            blend_mode,
+            quality: FilterQuality::Nearest,
+        };
+        // The layer's mask includes the clips of its parents, so it is used for compositing
+        group_pixmap(&mut self.pixmap, &mut self.layers).draw_pixmap(
+            0,
+            0,
+            pixmap.as_ref(),
+            &paint,
+            Transform::identity(),
+            Some(&mask),
+        );
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        paint: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let Some(path) = to_path(shape) else {
+            return;
+        };
+        let transform = self.transform(transform);
+        let Some(paint) = to_paint(&mut self.images, paint.into(), brush_transform) else {
+            return;
+        };
+        if let Some((pixmap, mask)) = target(&mut self.pixmap, &mut self.layers) {
+            pixmap.stroke_path(&path, &paint, &to_stroke(style), transform, mask);
+        }
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        paint: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let Some(path) = to_path(shape) else {
+            return;
+        };
+        let transform = self.transform(transform);
+        let Some(paint) = to_paint(&mut self.images, paint.into(), brush_transform) else {
+            return;
+        };
+        if let Some((pixmap, mask)) = target(&mut self.pixmap, &mut self.layers) {
+            pixmap.fill_path(&path, &paint, to_fill_rule(style), transform, mask);
+        }
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'a mut self,
+        font: &'a FontData,
+        font_size: f32,
+        _hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        paint: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = anyrender::Glyph>,
+    ) {
+        let outline =
+            glyph_run_outline(font, font_size, normalized_coords, glyph_transform, glyphs);
+        let Some(path) = to_path(&outline) else {
+            return;
+        };
+        let transform = self.transform(transform);
+        let Some(mut paint) = to_paint(&mut self.images, paint.into(), None) else {
+            return;
+        };
+        if brush_alpha < 1.0 {
+            paint.shader.apply_opacity(brush_alpha);
+        }
+        if let Some((pixmap, mask)) = target(&mut self.pixmap, &mut self.layers) {
+            match style.into() {
+                StyleRef::Fill(fill) => {
+                    pixmap.fill_path(&path, &paint, to_fill_rule(fill), transform, mask)
+                }
+                StyleRef::Stroke(stroke) => {
+                    pixmap.stroke_path(&path, &paint, &to_stroke(stroke), transform, mask)
+                }
+            }
+        }
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        color: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        let transform = self.base_transform * transform;
+        if let Some((pixmap, mask)) = target(&mut self.pixmap, &mut self.layers) {
+            draw_box_shadow(pixmap, mask, transform, rect, color, radius, std_dev);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use anyrender::{Glyph, ImageRenderer, PaintScene, RenderError};
+use anyrender_tiny_skia::{TinySkiaImageRenderer, TinySkiaScenePainter};
+use kurbo::{Affine, Rect};
+use peniko::{
+    Blob, Color, Fill, FontData, Gradient, ImageAlphaType, ImageBrush, ImageData, ImageFormat, Mix,
+};
+use skrifa::{FontRef, MetadataProvider};
+
+const ROBOTO: &[u8] = include_bytes!("../../../assets/fonts/roboto/Roboto.ttf");
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Draw pixel-aligned rectangles, which tiny-skia fills exactly (without anti-aliased edges)
+fn draw_squares(scene: &mut impl PaintScene) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &Rect::new(16.0, 8.0, 32.0, 24.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((32.0, 24.0)),
+        Color::from_rgba8(0, 0, 255, 128),
+        None,
+        &Rect::new(0.0, 0.0, 16.0, 16.0),
+    );
+}
+
+/// The expected (premultiplied) color of each pixel drawn by [`draw_squares`]
+fn square_color(x: usize, y: usize) -> [u8; 4] {
+    if (16..32).contains(&x) && (8..24).contains(&y) {
+        [255, 0, 0, 255]
+    } else if (32..48).contains(&x) && (24..40).contains(&y) {
+        [0, 0, 128, 128]
+    } else {
+        [0; 4]
+    }
+}
+
+#[test]
+fn renders_premultiplied_rgba() {
+    let mut renderer = TinySkiaImageRenderer::new(WIDTH, HEIGHT);
+    let mut buffer = Vec::new();
+    renderer.render_to_vec(draw_squares, &mut buffer);
+
+    assert_eq!(buffer.len(), (WIDTH * HEIGHT * 4) as usize);
+    for (i, pixel) in buffer.chunks_exact(4).enumerate() {
+        let (x, y) = (i % WIDTH as usize, i / WIDTH as usize);
+        assert_eq!(pixel, square_color(x, y), "pixel ({x}, {y})");
+    }
+}
+
+#[test]
+fn render_region_offsets_drawing_to_the_region() {
+    let mut renderer = TinySkiaImageRenderer::new(WIDTH, HEIGHT);
+
+    // Use a stride with padding at the end of each row, which must be left untouched
+    let stride = WIDTH as usize * 4 + 16;
+    let mut buffer = vec![7u8; stride * HEIGHT as usize];
+    renderer.render_region(
+        draw_squares,
+        Rect::new(20.0, 10.0, 40.0, 30.0),
+        &mut buffer,
+        stride,
+    );
+
+    for y in 0..HEIGHT as usize {
+        for x in 0..stride / 4 {
+            let pixel = &buffer[y * stride + x * 4..][..4];
+            if (20..40).contains(&x) && (10..30).contains(&y) {
+                assert_eq!(pixel, square_color(x, y), "pixel ({x}, {y})");
+            } else {
+                assert_eq!(pixel, [7; 4], "pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn repeated_region_renders_start_from_a_clear_pixmap() {
+    let mut renderer = TinySkiaImageRenderer::new(WIDTH, HEIGHT);
+    let stride = WIDTH as usize * 4;
+    let mut buffer = vec![0u8; stride * HEIGHT as usize];
+    // The second region is the same size as the first, and doesn't contain any squares
+    renderer.render_region(
+        draw_squares,
+        Rect::new(16.0, 8.0, 32.0, 24.0),
+        &mut buffer,
+        stride,
+    );
+    renderer.render_region(
+        draw_squares,
+        Rect::new(0.0, 0.0, 16.0, 16.0),
+        &mut buffer,
+        stride,
+    );
+
+    // Only the red square is within the rendered regions
+    for y in 0..HEIGHT as usize {
+        for x in 0..WIDTH as usize {
+            let pixel = &buffer[y * stride + x * 4..][..4];
+            let expected = match (16..32).contains(&x) && (8..24).contains(&y) {
+                true => square_color(x, y),
+                false => [0; 4],
+            };
+            assert_eq!(pixel, expected, "pixel ({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn short_buffers_are_reported() {
+    let mut renderer = TinySkiaImageRenderer::new(WIDTH, HEIGHT);
+    let stride = WIDTH as usize * 4;
+    let mut buffer = vec![0u8; stride * 10];
+    assert!(matches!(
+        renderer.try_render(draw_squares, &mut buffer),
+        Err(RenderError::Render(_))
+    ));
+    assert!(matches!(
+        renderer.try_render_region(
+            draw_squares,
+            Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+            &mut buffer,
+            stride,
+        ),
+        Err(RenderError::Render(_))
+    ));
+}
+
+#[test]
+fn glyph_runs_are_drawn_with_the_brush_alpha() {
+    let font = FontData::new(Blob::from(ROBOTO.to_vec()), 0);
+    let glyph_id = FontRef::new(ROBOTO).unwrap().charmap().map('H').unwrap();
+
+    let mut scene = TinySkiaScenePainter::new(WIDTH, HEIGHT);
+    for (x, brush_alpha) in [(2.0, 1.0), (34.0, 0.5)] {
+        scene.draw_glyphs(
+            &font,
+            32.0,
+            false,
+            &[],
+            Fill::NonZero,
+            Color::BLACK,
+            brush_alpha,
+            Affine::IDENTITY,
+            None,
+            [Glyph {
+                id: glyph_id.to_u32(),
+                x,
+                y: 46.0,
+            }]
+            .into_iter(),
+        );
+    }
+    let pixmap = scene.finish();
+    let alpha = |x, y| pixmap.pixel(x, y).unwrap().alpha();
+
+    // The left stems of the two 'H's
+    assert_eq!(alpha(5, 35), 255);
+    assert!(alpha(37, 35).abs_diff(128) <= 1, "{}", alpha(37, 35));
+}
+
+#[test]
+fn layers_are_clipped_and_blended() {
+    let mut scene = TinySkiaScenePainter::new(WIDTH, HEIGHT);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(255, 255, 0),
+        None,
+        &Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+    );
+    scene.push_layer(
+        Mix::Multiply,
+        1.0,
+        Affine::IDENTITY,
+        &Rect::new(0.0, 0.0, 32.0, 48.0),
+    );
+    scene.push_clip_layer(Affine::IDENTITY, &Rect::new(0.0, 0.0, 64.0, 24.0));
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::from_rgb8(0, 255, 255),
+        None,
+        &Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+    );
+    scene.pop_layer();
+    scene.pop_layer();
+    let pixmap = scene.finish();
+
+    let rgba = |x, y| {
+        let color = pixmap.pixel(x, y).unwrap();
+        [color.red(), color.green(), color.blue(), color.alpha()]
+    };
+    // Yellow multiplied by cyan is green
+    assert_eq!(rgba(10, 10), [0, 255, 0, 255]);
+    // Outside of the layer's clip or the clip layer the background is untouched
+    assert_eq!(rgba(50, 10), [255, 255, 0, 255]);
+    assert_eq!(rgba(10, 40), [255, 255, 0, 255]);
+}
+
+#[test]
+fn draws_gradients_images_glyphs_and_shadows() {
+    let gradient = Gradient::new_linear((0.0, 0.0), (64.0, 0.0))
+        .with_stops([Color::BLACK, Color::WHITE].as_slice());
+    let image = ImageBrush::new(ImageData {
+        data: Blob::from([0u8, 0, 255, 255].repeat(4)),
+        format: ImageFormat::Rgba8,
+        alpha_type: ImageAlphaType::Alpha,
+        width: 2,
+        height: 2,
+    });
+    let font = FontData::new(Blob::from(ROBOTO.to_vec()), 0);
+    let glyph_id = FontRef::new(ROBOTO).unwrap().charmap().map('H').unwrap();
+
+    let mut scene = TinySkiaScenePainter::new(WIDTH, HEIGHT);
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        &gradient,
+        None,
+        &Rect::new(0.0, 0.0, 64.0, 8.0),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((0.0, 8.0)),
+        image.as_ref(),
+        None,
+        &Rect::new(0.0, 0.0, 2.0, 2.0),
+    );
+    scene.draw_glyphs(
+        &font,
+        32.0,
+        false,
+        &[],
+        Fill::NonZero,
+        Color::BLACK,
+        1.0,
+        Affine::IDENTITY,
+        None,
+        [Glyph {
+            id: glyph_id.to_u32(),
+            x: 2.0,
+            y: 46.0,
+        }]
+        .into_iter(),
+    );
+    scene.draw_box_shadow(
+        Affine::IDENTITY,
+        Rect::new(40.0, 20.0, 60.0, 40.0),
+        Color::BLACK,
+        2.0,
+        3.0,
+    );
+    let pixmap = scene.finish();
+    let alpha = |x, y| pixmap.pixel(x, y).unwrap().alpha();
+    let red = |x, y| pixmap.pixel(x, y).unwrap().red();
+
+    // The gradient gets lighter from left to right
+    assert!(red(2, 4) < red(32, 4) && red(32, 4) < red(62, 4));
+    // The image is blue
+    let blue = pixmap.pixel(1, 9).unwrap();
+    assert_eq!([blue.red(), blue.blue(), blue.alpha()], [0, 255, 255]);
+    // The left stem of the 'H' is filled
+    assert_eq!(alpha(5, 35), 255);
+    // The shadow is opaque in the middle and fades out at the edges
+    assert!(alpha(50, 30) > 250);
+    assert!(alpha(40, 30) > 64 && alpha(40, 30) < 192);
+    assert_eq!(alpha(30, 30), 0);
+}
@@ -1,4 +1,4 @@
-use anyrender::ImageRenderer;
+use anyrender::{ImageRenderer, RenderError};
 use rustc_hash::FxHashMap;
 use vello::{Renderer as VelloRenderer, RendererOptions, Scene as VelloScene};
 use wgpu::TextureUsages;
@@ -19,6 +19,10 @@ impl ImageRenderer for VelloImageRenderer {
         Self: 'a;
 
     fn new(width: u32, height: u32) -> Self {
+        Self::try_new(width, height).expect("No compatible device found")
+    }
+
+    fn try_new(width: u32, height: u32) -> Result<Self, RenderError> {
         // Create WGPUContext
         let mut context = WGPUContext::new();
 
@@ -29,7 +33,7 @@ impl ImageRenderer for VelloImageRenderer {
                 height,
                 usage: TextureUsages::STORAGE_BINDING,
             }))
-            .expect("No compatible device found");
+            .map_err(RenderError::init)?;
 
         // Create vello::Renderer
         let vello_renderer = VelloRenderer::new(
@@ -41,13 +45,13 @@ impl ImageRenderer for VelloImageRenderer {
                 pipeline_cache: None,
             },
         )
-        .expect("Got non-Send/Sync error from creating renderer");
+        .map_err(RenderError::init)?;
 
-        Self {
+        Ok(Self {
             buffer_renderer,
             vello_renderer,
             scene: VelloScene::new(),
-        }
+        })
     }
 
     fn resize(&mut self, width: u32, height: u32) {
@@ -73,6 +77,25 @@ impl ImageRenderer for VelloImageRenderer {
         draw_fn: F,
         cpu_buffer: &mut [u8],
     ) {
+        self.try_render(draw_fn, cpu_buffer)
+            .expect("Got non-Send/Sync error from rendering");
+    }
+
+    fn try_render_to_vec<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        cpu_buffer: &mut Vec<u8>,
+    ) -> Result<(), RenderError> {
+        let size = self.buffer_renderer.size();
+        cpu_buffer.resize((size.width * size.height * 4) as usize, 0);
+        self.try_render(draw_fn, cpu_buffer)
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        cpu_buffer: &mut [u8],
+    ) -> Result<(), RenderError> {
         draw_fn(&mut VelloScenePainter {
             inner: &mut self.scene,
             renderer: Some(&mut self.vello_renderer),
@@ -80,24 +103,28 @@ impl ImageRenderer for VelloImageRenderer {
         });
 
         let size = self.buffer_renderer.size();
-        self.vello_renderer
-            .render_to_texture(
-                self.buffer_renderer.device(),
-                self.buffer_renderer.queue(),
-                &self.scene,
-                &self.buffer_renderer.target_texture_view(),
-                &vello::RenderParams {
-                    base_color: vello::peniko::Color::TRANSPARENT,
-                    width: size.width,
-                    height: size.height,
-                    antialiasing_method: vello::AaConfig::Area,
-                },
-            )
-            .expect("Got non-Send/Sync error from rendering");
+        let result = self.vello_renderer.render_to_texture(
+            self.buffer_renderer.device(),
+            self.buffer_renderer.queue(),
+            &self.scene,
+            &self.buffer_renderer.target_texture_view(),
+            &vello::RenderParams {
+                base_color: vello::peniko::Color::TRANSPARENT,
+                width: size.width,
+                height: size.height,
+                antialiasing_method: vello::AaConfig::Area,
+            },
+        );
+        if let Err(err) = result {
+            self.scene.reset();
+            return Err(RenderError::render(err));
+        }
 
         self.buffer_renderer.copy_texture_to_buffer(cpu_buffer);
 
         // Empty the Vello scene (memory optimisation)
         self.scene.reset();
+
+        Ok(())
     }
 }
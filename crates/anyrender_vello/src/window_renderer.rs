@@ -1,4 +1,4 @@
-use anyrender::{WindowHandle, WindowRenderer};
+use anyrender::{RenderError, WindowHandle, WindowRenderer};
 use debug_timer::debug_timer;
 use peniko::Color;
 use rustc_hash::FxHashMap;
@@ -128,6 +128,16 @@ impl WindowRenderer for VelloWindowRenderer {
     }
 
     fn resume(&mut self, window_handle: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        self.try_resume(window_handle, width, height)
+            .expect("Error creating surface");
+    }
+
+    fn try_resume(
+        &mut self,
+        window_handle: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
         // Create wgpu_context::SurfaceRenderer
         let render_surface = pollster::block_on(self.wgpu_context.create_surface(
             window_handle.clone(),
@@ -145,7 +155,7 @@ impl WindowRenderer for VelloWindowRenderer {
                 usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
             }),
         ))
-        .expect("Error creating surface");
+        .map_err(RenderError::resume)?;
 
         // Create vello::Renderer
         let renderer = VelloRenderer::new(
@@ -158,7 +168,7 @@ impl WindowRenderer for VelloWindowRenderer {
                 pipeline_cache: None,
             },
         )
-        .unwrap();
+        .map_err(RenderError::resume)?;
 
         // Resume custom paint sources
         let device_handle = &render_surface.device_handle;
@@ -172,6 +182,8 @@ impl WindowRenderer for VelloWindowRenderer {
             renderer,
             render_surface,
         });
+
+        Ok(())
     }
 
     fn suspend(&mut self) {
@@ -191,8 +203,16 @@ impl WindowRenderer for VelloWindowRenderer {
     }
 
     fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        self.try_render(draw_fn)
+            .expect("failed to render to texture");
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
         let RenderState::Active(state) = &mut self.render_state else {
-            return;
+            return Ok(());
         };
 
         let render_surface = &mut state.render_surface;
@@ -208,21 +228,22 @@ impl WindowRenderer for VelloWindowRenderer {
         timer.record_time("cmd");
 
         let texture_view = render_surface.target_texture_view();
-        state
-            .renderer
-            .render_to_texture(
-                render_surface.device(),
-                render_surface.queue(),
-                &self.scene,
-                &texture_view,
-                &RenderParams {
-                    base_color: self.config.base_color,
-                    width: render_surface.config.width,
-                    height: render_surface.config.height,
-                    antialiasing_method: self.config.antialiasing_method,
-                },
-            )
-            .expect("failed to render to texture");
+        let result = state.renderer.render_to_texture(
+            render_surface.device(),
+            render_surface.queue(),
+            &self.scene,
+            &texture_view,
+            &RenderParams {
+                base_color: self.config.base_color,
+                width: render_surface.config.width,
+                height: render_surface.config.height,
+                antialiasing_method: self.config.antialiasing_method,
+            },
+        );
+        if let Err(err) = result {
+            self.scene.reset();
+            return Err(RenderError::render(err));
+        }
         timer.record_time("render");
 
         drop(texture_view);
@@ -230,10 +251,9 @@ impl WindowRenderer for VelloWindowRenderer {
         render_surface.maybe_blit_and_present();
         timer.record_time("present");
 
-        render_surface
+        let poll_result = render_surface
             .device()
-            .poll(wgpu::PollType::wait_indefinitely())
-            .unwrap();
+            .poll(wgpu::PollType::wait_indefinitely());
 
         timer.record_time("wait");
         timer.print_times("vello: ");
@@ -243,5 +263,9 @@ impl WindowRenderer for VelloWindowRenderer {
 
         // Empty the Vello scene (memory optimisation)
         self.scene.reset();
+
+        poll_result.map_err(RenderError::render)?;
+
+        Ok(())
     }
 }
@@ -1,27 +1,36 @@
-use crate::VelloCpuScenePainter;
-use anyrender::ImageRenderer;
+use crate::VelloCpuImageScenePainter;
+use anyrender::{ImageRenderer, PixelRegion, RenderError};
 use debug_timer::debug_timer;
-use vello_cpu::{RenderContext, RenderMode};
+use kurbo::{Affine, Rect};
+use vello_cpu::RenderMode;
 
 pub struct VelloCpuImageRenderer {
-    scene: VelloCpuScenePainter,
+    scene: VelloCpuImageScenePainter,
+    /// Scene used for rendering regions (kept to reuse its allocations between renders of the same size)
+    region_scene: Option<VelloCpuImageScenePainter>,
+    region_buffer: Vec<u8>,
 }
 
 impl ImageRenderer for VelloCpuImageRenderer {
-    type ScenePainter<'a> = VelloCpuScenePainter;
+    type ScenePainter<'a> = VelloCpuImageScenePainter;
 
     fn new(width: u32, height: u32) -> Self {
         Self {
-            scene: VelloCpuScenePainter(RenderContext::new(width as u16, height as u16)),
+            scene: VelloCpuImageScenePainter::new(width as u16, height as u16, Affine::IDENTITY),
+            region_scene: None,
+            region_buffer: Vec::new(),
         }
     }
 
     fn resize(&mut self, width: u32, height: u32) {
-        self.scene.0 = RenderContext::new(width as u16, height as u16);
+        self.scene = VelloCpuImageScenePainter::new(width as u16, height as u16, Affine::IDENTITY);
     }
 
     fn reset(&mut self) {
-        self.scene.0.reset();
+        self.scene.context_mut().reset();
+        if let Some(region_scene) = &mut self.region_scene {
+            region_scene.context_mut().reset();
+        }
     }
 
     fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F, buffer: &mut [u8]) {
@@ -30,13 +39,14 @@ impl ImageRenderer for VelloCpuImageRenderer {
         draw_fn(&mut self.scene);
         timer.record_time("cmds");
 
-        self.scene.0.flush();
+        self.scene.context_mut().flush();
         timer.record_time("flush");
 
-        self.scene.0.render_to_buffer(
+        let context = self.scene.context();
+        context.render_to_buffer(
             buffer,
-            self.scene.0.width(),
-            self.scene.0.height(),
+            context.width(),
+            context.height(),
             RenderMode::OptimizeSpeed,
         );
         timer.record_time("render");
@@ -49,9 +59,92 @@ impl ImageRenderer for VelloCpuImageRenderer {
         draw_fn: F,
         buffer: &mut Vec<u8>,
     ) {
-        let width = self.scene.0.width();
-        let height = self.scene.0.height();
+        let width = self.scene.context().width();
+        let height = self.scene.context().height();
         buffer.resize(width as usize * height as usize * 4, 0);
         self.render(draw_fn, buffer);
     }
+
+    fn render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) {
+        self.try_render_region(draw_fn, region, buffer, stride)
+            .expect("Error rendering region");
+    }
+
+    fn try_render_region<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+        region: Rect,
+        buffer: &mut [u8],
+        stride: usize,
+    ) -> Result<(), RenderError> {
+        let width = self.scene.context().width() as u32;
+        let height = self.scene.context().height() as u32;
+        let Some(region) = PixelRegion::new(region, width, height) else {
+            return Ok(());
+        };
+
+        let row_len = region.width as usize * 4;
+        let row_end = region.x as usize * 4 + row_len;
+        let required_len = (region.y + region.height - 1) as usize * stride + row_end;
+        if stride < row_end || buffer.len() < required_len {
+            return Err(RenderError::render(format!(
+                "buffer of {} bytes with a stride of {stride} is too small for a {width}x{height} image",
+                buffer.len()
+            )));
+        }
+
+        debug_timer!(timer, feature = "log_frame_times");
+
+        // Render into a region-sized context with all drawing offset by the region's origin
+        let base_transform = Affine::translate((-(region.x as f64), -(region.y as f64)));
+        let region_width = region.width as u16;
+        let region_height = region.height as u16;
+        let region_scene = match &mut self.region_scene {
+            Some(scene)
+                if scene.context().width() == region_width
+                    && scene.context().height() == region_height =>
+            {
+                scene.context_mut().reset();
+                scene.set_base_transform(base_transform);
+                scene
+            }
+            _ => self.region_scene.insert(VelloCpuImageScenePainter::new(
+                region_width,
+                region_height,
+                base_transform,
+            )),
+        };
+
+        draw_fn(region_scene);
+        timer.record_time("cmds");
+
+        region_scene.context_mut().flush();
+        timer.record_time("flush");
+
+        self.region_buffer
+            .resize(row_len * region.height as usize, 0);
+        region_scene.context().render_to_buffer(
+            &mut self.region_buffer,
+            region_width,
+            region_height,
+            RenderMode::OptimizeSpeed,
+        );
+        timer.record_time("render");
+
+        for (row, src) in self.region_buffer.chunks_exact(row_len).enumerate() {
+            let start = (region.y as usize + row) * stride + region.x as usize * 4;
+            buffer[start..start + row_len].copy_from_slice(src);
+        }
+        timer.record_time("copy");
+
+        timer.print_times("vello_cpu (region): ");
+
+        Ok(())
+    }
 }
@@ -173,3 +173,142 @@ impl PaintScene for VelloCpuScenePainter {
             .fill_blurred_rounded_rect(&rect, radius as f32, std_dev as f32);
     }
 }
+
+/// The [`PaintScene`] used by [`VelloCpuImageRenderer`](crate::VelloCpuImageRenderer).
+///
+/// This wraps a [`VelloCpuScenePainter`], applying a base transform to all drawing so that a region of the frame
+/// can be rendered into a region-sized context.
+pub struct VelloCpuImageScenePainter {
+    scene: VelloCpuScenePainter,
+    base_transform: Affine,
+}
+
+impl VelloCpuImageScenePainter {
+    pub(crate) fn new(width: u16, height: u16, base_transform: Affine) -> Self {
+        Self {
+            scene: VelloCpuScenePainter(vello_cpu::RenderContext::new(width, height)),
+            base_transform,
+        }
+    }
+
+    /// The underlying painter (which doesn't apply the base transform)
+    pub fn inner(&mut self) -> &mut VelloCpuScenePainter {
+        &mut self.scene
+    }
+
+    pub(crate) fn context(&self) -> &vello_cpu::RenderContext {
+        &self.scene.0
+    }
+
+    pub(crate) fn context_mut(&mut self) -> &mut vello_cpu::RenderContext {
+        &mut self.scene.0
+    }
+
+    pub(crate) fn set_base_transform(&mut self, base_transform: Affine) {
+        self.base_transform = base_transform;
+    }
+}
+
+impl PaintScene for VelloCpuImageScenePainter {
+    fn reset(&mut self) {
+        self.scene.reset();
+    }
+
+    fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.scene
+            .push_layer(blend, alpha, self.base_transform * transform, clip);
+    }
+
+    fn push_clip_layer(&mut self, transform: Affine, clip: &impl Shape) {
+        self.scene
+            .push_clip_layer(self.base_transform * transform, clip);
+    }
+
+    fn pop_layer(&mut self) {
+        self.scene.pop_layer();
+    }
+
+    fn stroke<'a>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        paint: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.scene.stroke(
+            style,
+            self.base_transform * transform,
+            paint,
+            brush_transform,
+            shape,
+        );
+    }
+
+    fn fill<'a>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        paint: impl Into<PaintRef<'a>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        self.scene.fill(
+            style,
+            self.base_transform * transform,
+            paint,
+            brush_transform,
+            shape,
+        );
+    }
+
+    fn draw_glyphs<'a, 's: 'a>(
+        &'a mut self,
+        font: &'a FontData,
+        font_size: f32,
+        hint: bool,
+        normalized_coords: &'a [NormalizedCoord],
+        style: impl Into<StyleRef<'a>>,
+        paint: impl Into<PaintRef<'a>>,
+        brush_alpha: f32,
+        transform: Affine,
+        glyph_transform: Option<Affine>,
+        glyphs: impl Iterator<Item = anyrender::Glyph>,
+    ) {
+        self.scene.draw_glyphs(
+            font,
+            font_size,
+            hint,
+            normalized_coords,
+            style,
+            paint,
+            brush_alpha,
+            self.base_transform * transform,
+            glyph_transform,
+            glyphs,
+        );
+    }
+
+    fn draw_box_shadow(
+        &mut self,
+        transform: Affine,
+        rect: Rect,
+        color: Color,
+        radius: f64,
+        std_dev: f64,
+    ) {
+        self.scene.draw_box_shadow(
+            self.base_transform * transform,
+            rect,
+            color,
+            radius,
+            std_dev,
+        );
+    }
+}
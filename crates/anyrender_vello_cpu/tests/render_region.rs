@@ -0,0 +1,94 @@
+use anyrender::{ImageRenderer, PaintScene, RenderError};
+use anyrender_vello_cpu::VelloCpuImageRenderer;
+use kurbo::{Affine, Circle, Rect};
+use peniko::{Color, Fill};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+fn draw(scene: &mut impl PaintScene) {
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        Color::WHITE,
+        None,
+        &Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::translate((10.0, 5.0)),
+        Color::from_rgb8(255, 0, 0),
+        None,
+        &Circle::new((20.0, 20.0), 15.0),
+    );
+}
+
+#[test]
+fn render_region_matches_full_render() {
+    let mut renderer = VelloCpuImageRenderer::new(WIDTH, HEIGHT);
+    let mut full = Vec::new();
+    renderer.render_to_vec(draw, &mut full);
+    renderer.reset();
+
+    // Start from a buffer filled with a sentinel value so that we can check untouched pixels
+    let stride = WIDTH as usize * 4;
+    let mut partial = vec![7u8; stride * HEIGHT as usize];
+    let region = Rect::new(12.5, 8.0, 40.0, 30.2);
+    renderer.render_region(draw, region, &mut partial, stride);
+
+    for y in 0..HEIGHT as usize {
+        for x in 0..WIDTH as usize {
+            let idx = y * stride + x * 4;
+            let inside = (12..40).contains(&x) && (8..31).contains(&y);
+            if inside {
+                assert_eq!(
+                    partial[idx..idx + 4],
+                    full[idx..idx + 4],
+                    "pixel ({x}, {y})"
+                );
+            } else {
+                assert_eq!(partial[idx..idx + 4], [7; 4], "pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn render_region_in_the_corner_matches_full_render() {
+    let mut renderer = VelloCpuImageRenderer::new(WIDTH, HEIGHT);
+    let mut full = Vec::new();
+    renderer.render_to_vec(draw, &mut full);
+    renderer.reset();
+
+    // Use a stride with padding at the end of each row, which must be left untouched
+    let stride = WIDTH as usize * 4 + 8;
+    let mut partial = vec![7u8; stride * HEIGHT as usize];
+    let region = Rect::new(WIDTH as f64 - 10.0, HEIGHT as f64 - 10.0, 100.0, 100.0);
+    renderer.render_region(draw, region, &mut partial, stride);
+
+    for y in 0..HEIGHT as usize {
+        for x in 0..stride / 4 {
+            let actual = &partial[y * stride + x * 4..][..4];
+            if (WIDTH as usize - 10..WIDTH as usize).contains(&x) && y >= HEIGHT as usize - 10 {
+                let idx = (y * WIDTH as usize + x) * 4;
+                assert_eq!(actual, &full[idx..idx + 4], "pixel ({x}, {y})");
+            } else {
+                assert_eq!(actual, [7; 4], "pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn try_render_region_reports_a_short_buffer() {
+    let mut renderer = VelloCpuImageRenderer::new(WIDTH, HEIGHT);
+    let stride = WIDTH as usize * 4;
+    let mut buffer = vec![0u8; stride * 10];
+    let result = renderer.try_render_region(
+        draw,
+        Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+        &mut buffer,
+        stride,
+    );
+    assert!(matches!(result, Err(RenderError::Render(_))));
+}
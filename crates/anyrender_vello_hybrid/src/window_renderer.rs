@@ -1,4 +1,4 @@
-use anyrender::{WindowHandle, WindowRenderer};
+use anyrender::{RenderError, WindowHandle, WindowRenderer};
 use debug_timer::debug_timer;
 use rustc_hash::FxHashMap;
 use std::sync::{
@@ -123,6 +123,16 @@ impl WindowRenderer for VelloHybridWindowRenderer {
     }
 
     fn resume(&mut self, window_handle: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        self.try_resume(window_handle, width, height)
+            .expect("Error creating surface");
+    }
+
+    fn try_resume(
+        &mut self,
+        window_handle: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
         // Create wgpu_context::SurfaceRenderer
         let render_surface = pollster::block_on(self.wgpu_context.create_surface(
             window_handle.clone(),
@@ -141,7 +151,7 @@ impl WindowRenderer for VelloHybridWindowRenderer {
             //     usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
             // }),
         ))
-        .expect("Error creating surface");
+        .map_err(RenderError::resume)?;
 
         // Create vello::Renderer
         let renderer = VelloHybridRenderer::new(
@@ -169,6 +179,8 @@ impl WindowRenderer for VelloHybridWindowRenderer {
             renderer,
             render_surface,
         });
+
+        Ok(())
     }
 
     fn suspend(&mut self) {
@@ -195,8 +207,16 @@ impl WindowRenderer for VelloHybridWindowRenderer {
     }
 
     fn render<F: FnOnce(&mut Self::ScenePainter<'_>)>(&mut self, draw_fn: F) {
+        self.try_render(draw_fn)
+            .expect("failed to render to texture");
+    }
+
+    fn try_render<F: FnOnce(&mut Self::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
         let RenderState::Active(state) = &mut self.render_state else {
-            return;
+            return Ok(());
         };
 
         let render_surface = &mut state.render_surface;
@@ -228,20 +248,21 @@ impl WindowRenderer for VelloHybridWindowRenderer {
 
         let texture_view = render_surface.target_texture_view();
 
-        state
-            .renderer
-            .render(
-                &self.scene,
-                render_surface.device(),
-                render_surface.queue(),
-                &mut encoder,
-                &RenderSize {
-                    width: render_surface.config.width,
-                    height: render_surface.config.height,
-                },
-                &texture_view,
-            )
-            .expect("failed to render to texture");
+        let result = state.renderer.render(
+            &self.scene,
+            render_surface.device(),
+            render_surface.queue(),
+            &mut encoder,
+            &RenderSize {
+                width: render_surface.config.width,
+                height: render_surface.config.height,
+            },
+            &texture_view,
+        );
+        if let Err(err) = result {
+            self.scene.reset();
+            return Err(RenderError::render(err));
+        }
         render_surface.queue().submit([encoder.finish()]);
         timer.record_time("render");
 
@@ -250,10 +271,9 @@ impl WindowRenderer for VelloHybridWindowRenderer {
         render_surface.maybe_blit_and_present();
         timer.record_time("present");
 
-        render_surface
+        let poll_result = render_surface
             .device()
-            .poll(wgpu::PollType::wait_indefinitely())
-            .unwrap();
+            .poll(wgpu::PollType::wait_indefinitely());
 
         timer.record_time("wait");
         timer.print_times("vello_hybrid: ");
@@ -263,5 +283,9 @@ impl WindowRenderer for VelloHybridWindowRenderer {
 
         // Empty the Vello scene (memory optimisation)
         self.scene.reset();
+
+        poll_result.map_err(RenderError::render)?;
+
+        Ok(())
     }
 }
@@ -2,7 +2,7 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-use anyrender::{ImageRenderer, WindowHandle, WindowRenderer};
+use anyrender::{ImageRenderer, RenderError, WindowHandle, WindowRenderer};
 use debug_timer::debug_timer;
 use pixels::{Pixels, SurfaceTexture, wgpu::Color};
 use std::sync::Arc;
@@ -33,6 +33,11 @@ impl<Renderer: ImageRenderer> PixelsWindowRenderer<Renderer> {
         Self::with_renderer(Renderer::new(0, 0))
     }
 
+    /// Fallible version of [`new`](Self::new)
+    pub fn try_new() -> Result<Self, RenderError> {
+        Ok(Self::with_renderer(Renderer::try_new(0, 0)?))
+    }
+
     pub fn with_renderer<R: ImageRenderer>(renderer: R) -> PixelsWindowRenderer<R> {
         PixelsWindowRenderer {
             render_state: RenderState::Suspended,
@@ -40,6 +45,32 @@ impl<Renderer: ImageRenderer> PixelsWindowRenderer<Renderer> {
             renderer,
         }
     }
+
+    // Shared by `render` and `try_render`. Calling `WindowRenderer::try_render` from `render` directly
+    // would require `Renderer: 'static` due to the `Self: 'a` bound on `ScenePainter`.
+    fn render_frame<F: FnOnce(&mut Renderer::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        let RenderState::Active(state) = &mut self.render_state else {
+            return Ok(());
+        };
+
+        debug_timer!(timer, feature = "log_frame_times");
+
+        // Paint
+        let result = self.renderer.try_render(draw_fn, state.pixels.frame_mut());
+        timer.record_time("render");
+
+        let result = result.and_then(|()| state.pixels.render().map_err(RenderError::render));
+        timer.record_time("present");
+        timer.print_times("pixels: ");
+
+        // Reset the renderer ready for the next render
+        self.renderer.reset();
+
+        result
+    }
 }
 
 impl<Renderer: ImageRenderer> WindowRenderer for PixelsWindowRenderer<Renderer> {
@@ -53,8 +84,18 @@ impl<Renderer: ImageRenderer> WindowRenderer for PixelsWindowRenderer<Renderer>
     }
 
     fn resume(&mut self, window_handle: Arc<dyn WindowHandle>, width: u32, height: u32) {
+        self.try_resume(window_handle, width, height)
+            .expect("Error creating pixels surface");
+    }
+
+    fn try_resume(
+        &mut self,
+        window_handle: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
         let surface = SurfaceTexture::new(width, height, window_handle.clone());
-        let mut pixels = Pixels::new(width, height, surface).unwrap();
+        let mut pixels = Pixels::new(width, height, surface).map_err(RenderError::resume)?;
         pixels.enable_vsync(true);
         pixels.clear_color(Color {
             r: 1.0,
@@ -66,6 +107,8 @@ impl<Renderer: ImageRenderer> WindowRenderer for PixelsWindowRenderer<Renderer>
         self.window_handle = Some(window_handle);
 
         self.set_size(width, height);
+
+        Ok(())
     }
 
     fn suspend(&mut self) {
@@ -87,21 +130,14 @@ impl<Renderer: ImageRenderer> WindowRenderer for PixelsWindowRenderer<Renderer>
     }
 
     fn render<F: FnOnce(&mut Renderer::ScenePainter<'_>)>(&mut self, draw_fn: F) {
-        let RenderState::Active(state) = &mut self.render_state else {
-            return;
-        };
-
-        debug_timer!(timer, feature = "log_frame_times");
-
-        // Paint
-        self.renderer.render(draw_fn, state.pixels.frame_mut());
-        timer.record_time("render");
-
-        state.pixels.render().unwrap();
-        timer.record_time("present");
-        timer.print_times("pixels: ");
+        self.render_frame(draw_fn)
+            .expect("Error rendering to pixels surface");
+    }
 
-        // Reset the renderer ready for the next render
-        self.renderer.reset();
+    fn try_render<F: FnOnce(&mut Renderer::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        self.render_frame(draw_fn)
     }
 }
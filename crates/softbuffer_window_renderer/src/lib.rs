@@ -2,7 +2,7 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-use anyrender::{ImageRenderer, WindowHandle, WindowRenderer};
+use anyrender::{ImageRenderer, RenderError, WindowHandle, WindowRenderer};
 use debug_timer::debug_timer;
 use softbuffer::{Context, Surface};
 use std::{num::NonZero, sync::Arc};
@@ -11,6 +11,8 @@ use std::{num::NonZero, sync::Arc};
 pub struct ActiveRenderState {
     _context: Context<Arc<dyn WindowHandle>>,
     surface: Surface<Arc<dyn WindowHandle>, Arc<dyn WindowHandle>>,
+    /// The error from the last failed resize of the surface, reported by the next render
+    resize_error: Option<RenderError>,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -34,6 +36,11 @@ impl<Renderer: ImageRenderer> SoftbufferWindowRenderer<Renderer> {
         Self::with_renderer(Renderer::new(0, 0))
     }
 
+    /// Fallible version of [`new`](Self::new)
+    pub fn try_new() -> Result<Self, RenderError> {
+        Ok(Self::with_renderer(Renderer::try_new(0, 0)?))
+    }
+
     pub fn with_renderer<R: ImageRenderer>(renderer: R) -> SoftbufferWindowRenderer<R> {
         SoftbufferWindowRenderer {
             render_state: RenderState::Suspended,
@@ -42,6 +49,62 @@ impl<Renderer: ImageRenderer> SoftbufferWindowRenderer<Renderer> {
             buffer: Vec::new(),
         }
     }
+
+    // Shared by `render` and `try_render`. Calling `WindowRenderer::try_render` from `render` directly
+    // would require `Renderer: 'static` due to the `Self: 'a` bound on `ScenePainter`.
+    fn render_frame<F: FnOnce(&mut Renderer::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        let RenderState::Active(state) = &mut self.render_state else {
+            return Ok(());
+        };
+        if let Some(err) = state.resize_error.take() {
+            return Err(err);
+        }
+
+        debug_timer!(timer, feature = "log_frame_times");
+
+        // Skip the frame if there is no buffer to render into, which is expected while the window is minimized or
+        // zero-sized
+        let Ok(mut surface_buffer) = state.surface.buffer_mut() else {
+            return Ok(());
+        };
+        timer.record_time("buffer_mut");
+
+        // Paint
+        let result = self.renderer.try_render_to_vec(draw_fn, &mut self.buffer);
+        timer.record_time("render");
+        if let Err(err) = result {
+            self.renderer.reset();
+            return Err(err);
+        }
+
+        let out = surface_buffer.as_mut();
+
+        let (chunks, remainder) = self.buffer.as_chunks::<4>();
+        assert_eq!(chunks.len(), out.len());
+        assert_eq!(remainder.len(), 0);
+
+        for (&src, dest) in chunks.iter().zip(out.iter_mut()) {
+            let [r, g, b, a] = src;
+            if a == 0 {
+                *dest = u32::MAX;
+            } else {
+                *dest = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+            }
+        }
+        timer.record_time("swizel");
+
+        let present_result = surface_buffer.present();
+        timer.record_time("present");
+        timer.print_times("softbuffer: ");
+
+        // Reset the renderer ready for the next render
+        self.renderer.reset();
+
+        present_result.map_err(|err| RenderError::render(err.to_string()))
+    }
 }
 
 impl<Renderer: ImageRenderer> WindowRenderer for SoftbufferWindowRenderer<Renderer> {
@@ -55,15 +118,31 @@ impl<Renderer: ImageRenderer> WindowRenderer for SoftbufferWindowRenderer<Render
     }
 
     fn resume(&mut self, window_handle: Arc<dyn WindowHandle>, width: u32, height: u32) {
-        let context = Context::new(window_handle.clone()).unwrap();
-        let surface = Surface::new(&context, window_handle.clone()).unwrap();
+        self.try_resume(window_handle, width, height)
+            .expect("Error creating softbuffer surface");
+    }
+
+    fn try_resume(
+        &mut self,
+        window_handle: Arc<dyn WindowHandle>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), RenderError> {
+        // SoftBufferError is not Send + Sync, so it is converted to a string
+        let context = Context::new(window_handle.clone())
+            .map_err(|err| RenderError::resume(err.to_string()))?;
+        let surface = Surface::new(&context, window_handle.clone())
+            .map_err(|err| RenderError::resume(err.to_string()))?;
         self.render_state = RenderState::Active(ActiveRenderState {
             _context: context,
             surface,
+            resize_error: None,
         });
         self.window_handle = Some(window_handle);
 
         self.set_size(width, height);
+
+        Ok(())
     }
 
     fn suspend(&mut self) {
@@ -72,54 +151,28 @@ impl<Renderer: ImageRenderer> WindowRenderer for SoftbufferWindowRenderer<Render
 
     fn set_size(&mut self, physical_width: u32, physical_height: u32) {
         if let RenderState::Active(state) = &mut self.render_state {
-            state
+            state.resize_error = state
                 .surface
                 .resize(
                     NonZero::new(physical_width.max(1)).unwrap(),
                     NonZero::new(physical_height.max(1)).unwrap(),
                 )
-                .unwrap();
+                .err()
+                .map(|err| RenderError::render(err.to_string()));
             self.renderer.resize(physical_width, physical_height);
         };
     }
 
     fn render<F: FnOnce(&mut Renderer::ScenePainter<'_>)>(&mut self, draw_fn: F) {
-        let RenderState::Active(state) = &mut self.render_state else {
-            return;
-        };
-
-        debug_timer!(timer, feature = "log_frame_times");
-
-        let Ok(mut surface_buffer) = state.surface.buffer_mut() else {
-            return;
-        };
-        timer.record_time("buffer_mut");
-
-        // Paint
-        self.renderer.render_to_vec(draw_fn, &mut self.buffer);
-        timer.record_time("render");
-
-        let out = surface_buffer.as_mut();
-
-        let (chunks, remainder) = self.buffer.as_chunks::<4>();
-        assert_eq!(chunks.len(), out.len());
-        assert_eq!(remainder.len(), 0);
-
-        for (&src, dest) in chunks.iter().zip(out.iter_mut()) {
-            let [r, g, b, a] = src;
-            if a == 0 {
-                *dest = u32::MAX;
-            } else {
-                *dest = (r as u32) << 16 | (g as u32) << 8 | b as u32;
-            }
+        if let Err(err) = self.render_frame(draw_fn) {
+            panic!("Error rendering to softbuffer surface: {err}");
         }
-        timer.record_time("swizel");
-
-        surface_buffer.present().unwrap();
-        timer.record_time("present");
-        timer.print_times("softbuffer: ");
+    }
 
-        // Reset the renderer ready for the next render
-        self.renderer.reset();
+    fn try_render<F: FnOnce(&mut Renderer::ScenePainter<'_>)>(
+        &mut self,
+        draw_fn: F,
+    ) -> Result<(), RenderError> {
+        self.render_frame(draw_fn)
     }
 }
@@ -7,6 +7,7 @@ use std::path::Path;
 use anyrender::recording::Scene;
 use anyrender::{Glyph, PaintScene, render_to_buffer};
 use anyrender_serialize::{SceneArchive, SerializeConfig};
+use anyrender_test_utils::{CompareOptions, compare_images};
 use anyrender_vello_cpu::VelloCpuImageRenderer;
 use image::{ImageBuffer, RgbaImage};
 use kurbo::{Affine, Circle, Point, Rect, RoundedRect, Stroke};
@@ -52,10 +53,26 @@ fn main() {
     img.save(Path::new(OUTPUT_DIR).join("roundtrip.png"))
         .unwrap();
 
-    // Assert that `original.png` and `roundtrip.png` are the same
-    let original_img = image::open(Path::new(OUTPUT_DIR).join("original.png")).unwrap();
-    let roundtrip_img = image::open(Path::new(OUTPUT_DIR).join("roundtrip.png")).unwrap();
-    assert_eq!(original_img.to_rgba8(), roundtrip_img.to_rgba8());
+    // Assert that `original.png` and `roundtrip.png` match, allowing for tiny rasterization differences
+    let original_img = image::open(Path::new(OUTPUT_DIR).join("original.png"))
+        .unwrap()
+        .to_rgba8();
+    let roundtrip_img = image::open(Path::new(OUTPUT_DIR).join("roundtrip.png"))
+        .unwrap()
+        .to_rgba8();
+    let options = CompareOptions::new().with_channel_tolerance(2);
+    let comparison = compare_images(&roundtrip_img, &original_img, WIDTH, HEIGHT, &options);
+    if !comparison.matches {
+        let diff_path = Path::new(OUTPUT_DIR).join("diff.png");
+        comparison
+            .write_diff_image(&roundtrip_img, &original_img, &diff_path)
+            .unwrap();
+        panic!(
+            "roundtrip doesn't match original: {} pixels differ (see {})",
+            comparison.differing_pixels,
+            diff_path.display()
+        );
+    }
 }
 
 fn create_demo_scene() -> Scene {
@@ -1,4 +1,4 @@
-use anyrender::{NullWindowRenderer, PaintScene, WindowRenderer};
+use anyrender::{DynWindowRenderer, NullWindowRenderer, PaintScene};
 use anyrender_skia::SkiaWindowRenderer;
 use anyrender_vello::VelloWindowRenderer;
 use anyrender_vello_cpu::{PixelsWindowRenderer, SoftbufferWindowRenderer, VelloCpuImageRenderer};
@@ -23,72 +23,48 @@ struct App {
 type VelloCpuSBWindowRenderer = SoftbufferWindowRenderer<VelloCpuImageRenderer>;
 type VelloCpuWindowRenderer = PixelsWindowRenderer<VelloCpuImageRenderer>;
 
-type InitialBackend = SkiaWindowRenderer;
-// type InitialBackend = VelloWindowRenderer;
-// type InitialBackend = VelloHybridWindowRenderer;
-// type InitialBackend = VelloCpuWindowRenderer;
-// type InitialBackend = VelloCpuSBWindowRenderer;
-// type InitialBackend = NullWindowRenderer;
-
-enum Renderer {
-    Gpu(Box<VelloWindowRenderer>),
-    Hybrid(Box<VelloHybridWindowRenderer>),
-    Cpu(Box<VelloCpuWindowRenderer>),
-    CpuSoftbuffer(Box<VelloCpuSBWindowRenderer>),
-    Skia(Box<SkiaWindowRenderer>),
-    Null(NullWindowRenderer),
-}
-impl From<VelloWindowRenderer> for Renderer {
-    fn from(renderer: VelloWindowRenderer) -> Self {
-        Self::Gpu(Box::new(renderer))
-    }
-}
-impl From<VelloHybridWindowRenderer> for Renderer {
-    fn from(renderer: VelloHybridWindowRenderer) -> Self {
-        Self::Hybrid(Box::new(renderer))
-    }
-}
-impl From<VelloCpuWindowRenderer> for Renderer {
-    fn from(renderer: VelloCpuWindowRenderer) -> Self {
-        Self::Cpu(Box::new(renderer))
-    }
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Backend {
+    Gpu,
+    Hybrid,
+    Cpu,
+    CpuSoftbuffer,
+    Skia,
+    Null,
 }
-impl From<VelloCpuSBWindowRenderer> for Renderer {
-    fn from(renderer: VelloCpuSBWindowRenderer) -> Self {
-        Self::CpuSoftbuffer(Box::new(renderer))
-    }
-}
-impl From<SkiaWindowRenderer> for Renderer {
-    fn from(renderer: SkiaWindowRenderer) -> Self {
-        Self::Skia(Box::new(renderer))
-    }
-}
-impl From<NullWindowRenderer> for Renderer {
-    fn from(renderer: NullWindowRenderer) -> Self {
-        Self::Null(renderer)
+
+const INITIAL_BACKEND: Backend = Backend::Skia;
+
+impl Backend {
+    fn create(self) -> Box<dyn DynWindowRenderer> {
+        match self {
+            Backend::Gpu => Box::new(VelloWindowRenderer::new()),
+            Backend::Hybrid => Box::new(VelloHybridWindowRenderer::new()),
+            Backend::Cpu => Box::new(VelloCpuWindowRenderer::new()),
+            Backend::CpuSoftbuffer => Box::new(VelloCpuSBWindowRenderer::new()),
+            Backend::Skia => Box::new(SkiaWindowRenderer::new()),
+            Backend::Null => Box::new(NullWindowRenderer::new()),
+        }
     }
-}
 
-impl Renderer {
-    fn is_active(&self) -> bool {
+    fn next(self) -> Self {
         match self {
-            Renderer::Gpu(r) => r.is_active(),
-            Renderer::Hybrid(r) => r.is_active(),
-            Renderer::Cpu(r) => r.is_active(),
-            Renderer::CpuSoftbuffer(r) => r.is_active(),
-            Renderer::Null(r) => r.is_active(),
-            Renderer::Skia(r) => r.is_active(),
+            Backend::Cpu | Backend::CpuSoftbuffer => Backend::Hybrid,
+            Backend::Hybrid => Backend::Gpu,
+            Backend::Gpu => Backend::Skia,
+            Backend::Skia => Backend::Null,
+            Backend::Null => Backend::Cpu,
         }
     }
 
-    fn set_size(&mut self, w: u32, h: u32) {
+    fn color(self) -> Color {
         match self {
-            Renderer::Gpu(r) => r.set_size(w, h),
-            Renderer::Hybrid(r) => r.set_size(w, h),
-            Renderer::Cpu(r) => r.set_size(w, h),
-            Renderer::CpuSoftbuffer(r) => r.set_size(w, h),
-            Renderer::Null(r) => r.set_size(w, h),
-            Renderer::Skia(r) => r.set_size(w, h),
+            Backend::Skia => Color::from_rgb8(128, 128, 128),
+            Backend::Gpu => Color::from_rgb8(255, 0, 0),
+            Backend::Hybrid => Color::from_rgb8(0, 0, 0),
+            Backend::Cpu => Color::from_rgb8(0, 255, 0),
+            Backend::CpuSoftbuffer => Color::from_rgb8(0, 0, 255),
+            Backend::Null => Color::from_rgb8(0, 0, 0),
         }
     }
 }
@@ -96,7 +72,8 @@ impl Renderer {
 enum RenderState {
     Active {
         window: Arc<Window>,
-        renderer: Renderer,
+        backend: Backend,
+        renderer: Box<dyn DynWindowRenderer>,
     },
     Suspended(Option<Arc<Window>>),
 }
@@ -104,7 +81,9 @@ enum RenderState {
 impl App {
     fn request_redraw(&mut self) {
         let window = match &self.render_state {
-            RenderState::Active { window, renderer } => {
+            RenderState::Active {
+                window, renderer, ..
+            } => {
                 if renderer.is_active() {
                     Some(window)
                 } else {
@@ -119,7 +98,7 @@ impl App {
         }
     }
 
-    fn draw_scene<T: PaintScene>(scene: &mut T, color: Color) {
+    fn draw_scene<T: PaintScene + ?Sized>(scene: &mut T, color: Color) {
         scene.fill(
             Fill::NonZero,
             Affine::IDENTITY,
@@ -143,11 +122,7 @@ impl App {
         );
     }
 
-    fn set_backend<R: WindowRenderer + Into<Renderer>>(
-        &mut self,
-        mut renderer: R,
-        event_loop: &ActiveEventLoop,
-    ) {
+    fn set_backend(&mut self, backend: Backend, event_loop: &ActiveEventLoop) {
         let mut window = match &self.render_state {
             RenderState::Active { window, .. } => Some(window.clone()),
             RenderState::Suspended(cached_window) => cached_window.clone(),
@@ -162,9 +137,13 @@ impl App {
             Arc::new(event_loop.create_window(attr).unwrap())
         });
 
+        let mut renderer = backend.create();
         renderer.resume(window.clone(), self.width, self.height);
-        let renderer = renderer.into();
-        self.render_state = RenderState::Active { window, renderer };
+        self.render_state = RenderState::Active {
+            window,
+            backend,
+            renderer,
+        };
         self.request_redraw();
     }
 }
@@ -177,7 +156,7 @@ impl ApplicationHandler for App {
     }
 
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        self.set_backend(InitialBackend::new(), event_loop);
+        self.set_backend(INITIAL_BACKEND, event_loop);
     }
 
     fn window_event(
@@ -186,7 +165,12 @@ impl ApplicationHandler for App {
         window_id: WindowId,
         event: WindowEvent,
     ) {
-        let RenderState::Active { window, renderer } = &mut self.render_state else {
+        let RenderState::Active {
+            window,
+            backend,
+            renderer,
+        } = &mut self.render_state
+        else {
             return;
         };
 
@@ -202,18 +186,10 @@ impl ApplicationHandler for App {
                 renderer.set_size(self.width, self.height);
                 self.request_redraw();
             }
-            WindowEvent::RedrawRequested => match renderer {
-                Renderer::Skia(r) => {
-                    r.render(|p| App::draw_scene(p, Color::from_rgb8(128, 128, 128)))
-                }
-                Renderer::Gpu(r) => r.render(|p| App::draw_scene(p, Color::from_rgb8(255, 0, 0))),
-                Renderer::Hybrid(r) => r.render(|p| App::draw_scene(p, Color::from_rgb8(0, 0, 0))),
-                Renderer::Cpu(r) => r.render(|p| App::draw_scene(p, Color::from_rgb8(0, 255, 0))),
-                Renderer::CpuSoftbuffer(r) => {
-                    r.render(|p| App::draw_scene(p, Color::from_rgb8(0, 0, 255)))
-                }
-                Renderer::Null(r) => r.render(|p| App::draw_scene(p, Color::from_rgb8(0, 0, 0))),
-            },
+            WindowEvent::RedrawRequested => {
+                let color = backend.color();
+                renderer.render(&mut |p| App::draw_scene(p, color));
+            }
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
@@ -222,23 +198,10 @@ impl ApplicationHandler for App {
                         ..
                     },
                 ..
-            } => match renderer {
-                Renderer::Cpu(_) | Renderer::CpuSoftbuffer(_) => {
-                    self.set_backend(VelloHybridWindowRenderer::new(), event_loop);
-                }
-                Renderer::Hybrid(_) => {
-                    self.set_backend(VelloWindowRenderer::new(), event_loop);
-                }
-                Renderer::Gpu(_) => {
-                    self.set_backend(SkiaWindowRenderer::new(), event_loop);
-                }
-                Renderer::Skia(_) => {
-                    self.set_backend(NullWindowRenderer::new(), event_loop);
-                }
-                Renderer::Null(_) => {
-                    self.set_backend(VelloCpuWindowRenderer::new(), event_loop);
-                }
-            },
+            } => {
+                let next = backend.next();
+                self.set_backend(next, event_loop);
+            }
             _ => {}
         }
     }